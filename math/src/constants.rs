@@ -0,0 +1,80 @@
+use radix_common::prelude::*;
+
+pub const TICK_BASE_SQRT: PreciseDecimal = pdec!(1.000049998750062496094023416993798697);
+
+pub const MAX_TICK: i32 = 887272;
+pub const MIN_TICK: i32 = -MAX_TICK;
+
+// TICK_BASE_SQRT^(2^i), every entry is the truncated square of the previous one.
+pub const TICK_BASE_SQRT_POWERS: [PreciseDecimal; 20] = [
+    pdec!(1.000049998750062496094023416993798697),
+    pdec!(1.000099999999999999999999999999999999),
+    pdec!(1.000200009999999999999999999999999997),
+    pdec!(1.000400060004000099999999999999999993),
+    pdec!(1.000800280056007000560028000800009985),
+    pdec!(1.001601200560182043688009144128711410),
+    pdec!(1.003204964963598014666528690811055190),
+    pdec!(1.006420201727613920156533908409419147),
+    pdec!(1.012881622445451097078095631935005317),
+    pdec!(1.025929181087729343658708608578965346),
+    pdec!(1.052530684607338948386589370372922778),
+    pdec!(1.107820842039993613899215811078811760),
+    pdec!(1.227267018058200482050503815090803892),
+    pdec!(1.506184333613467388107955981199139600),
+    pdec!(2.268591246822644826925609859343570728),
+    pdec!(5.146506245160322222537991751503698320),
+    pdec!(26.486526531474198664033811812784064439),
+    pdec!(701.536087702486644953017488493704107404),
+    pdec!(492152.882348911033633683861778228258526803),
+    pdec!(242214459604.341065650571799093415035594111255255),
+];
+
+// (1 / TICK_BASE_SQRT)^(2^i), every entry is the truncated square of the previous one.
+pub const TICK_BASE_SQRT_INVERSE_POWERS: [PreciseDecimal; 20] = [
+    pdec!(0.999950003749687527341289288064992198),
+    pdec!(0.999900009999000099990000999900009999),
+    pdec!(0.999800029996000499940006999200089990),
+    pdec!(0.999600099980003499440083988001649780),
+    pdec!(0.999200359880032992081715656864338561),
+    pdec!(0.998401359184387445014246950501832820),
+    pdec!(0.996805274021232232403522589364153181),
+    pdec!(0.993620754316543878477700834205485589),
+    pdec!(0.987282203408577650514394977141072597),
+    pdec!(0.974726149167296094959767608987808672),
+    pdec!(0.950091065870505957793733166276036650),
+    pdec!(0.902673033446954090815966611100428238),
+    pdec!(0.814818605312325898736980010634637379),
+    pdec!(0.663929359563123931326022095378263079),
+    pdec!(0.440802194489899902842800301640728586),
+    pdec!(0.194306574667111540266988334939575096),
+    pdec!(0.037755044958865792175320904477540266),
+    pdec!(0.001425443419845977266771800329344359),
+    pdec!(0.000002031888943182195016802617844386),
+    pdec!(0.000000000004128572677426057329255071),
+];
+
+pub const MAX_LIQUIDITY: PreciseDecimal = pdec!(3138668841663005800034);
+
+pub const MAX_DISTRIBUTION_BINS: u32 = 50;
+
+pub const DIVISIBILITY_UNITS: [Decimal; 19] = [
+    dec!(1),
+    dec!(0.1),
+    dec!(0.01),
+    dec!(0.001),
+    dec!(0.0001),
+    dec!(0.00001),
+    dec!(0.000001),
+    dec!(0.0000001),
+    dec!(0.00000001),
+    dec!(0.000000001),
+    dec!(0.0000000001),
+    dec!(0.00000000001),
+    dec!(0.000000000001),
+    dec!(0.0000000000001),
+    dec!(0.00000000000001),
+    dec!(0.000000000000001),
+    dec!(0.0000000000000001),
+    dec!(0.00000000000000001),
+    dec!(0.000000000000000001),
+];
@@ -0,0 +1,68 @@
+use radix_common::prelude::*;
+
+/// Rounds decimal numbers to the divisibility of a token, i.e. to a number of decimal places.
+pub(crate) trait Rounding {
+    /// Rounds towards negative infinity to `divisibility` decimal places.
+    fn floor_to(self, divisibility: u8) -> Decimal;
+    /// Rounds towards positive infinity to `divisibility` decimal places.
+    fn ceil_to(self, divisibility: u8) -> Decimal;
+}
+
+impl Rounding for Decimal {
+    fn floor_to(self, divisibility: u8) -> Decimal {
+        self.checked_round(divisibility, RoundingMode::ToNegativeInfinity)
+            .unwrap()
+    }
+
+    fn ceil_to(self, divisibility: u8) -> Decimal {
+        self.checked_round(divisibility, RoundingMode::ToPositiveInfinity)
+            .unwrap()
+    }
+}
+
+impl Rounding for PreciseDecimal {
+    fn floor_to(self, divisibility: u8) -> Decimal {
+        Decimal::try_from(
+            self.checked_round(divisibility, RoundingMode::ToNegativeInfinity)
+                .unwrap(),
+        )
+        .unwrap()
+    }
+
+    fn ceil_to(self, divisibility: u8) -> Decimal {
+        Decimal::try_from(
+            self.checked_round(divisibility, RoundingMode::ToPositiveInfinity)
+                .unwrap(),
+        )
+        .unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Rounding;
+    use radix_common::prelude::*;
+    use test_case::test_case;
+
+    #[test_case(dec!("1.25"), 1, dec!("1.2"), dec!("1.3"))]
+    #[test_case(dec!("-1.25"), 1, dec!("-1.3"), dec!("-1.2"))]
+    #[test_case(dec!("1.2"), 1, dec!("1.2"), dec!("1.2"))]
+    #[test_case(dec!("0.000000000000000001"), 0, dec!(0), dec!(1))]
+    fn test_round_decimal(value: Decimal, divisibility: u8, floor: Decimal, ceil: Decimal) {
+        assert_eq!(value.floor_to(divisibility), floor);
+        assert_eq!(value.ceil_to(divisibility), ceil);
+    }
+
+    #[test_case(pdec!("0.000000000000000000000000000000000001"), 18, dec!(0), dec!("0.000000000000000001"))]
+    #[test_case(pdec!("-0.000000000000000000000000000000000001"), 18, dec!("-0.000000000000000001"), dec!(0))]
+    #[test_case(pdec!("2.5"), 0, dec!(2), dec!(3))]
+    fn test_round_precise_decimal(
+        value: PreciseDecimal,
+        divisibility: u8,
+        floor: Decimal,
+        ceil: Decimal,
+    ) {
+        assert_eq!(value.floor_to(divisibility), floor);
+        assert_eq!(value.ceil_to(divisibility), ceil);
+    }
+}
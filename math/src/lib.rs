@@ -0,0 +1,13 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+pub mod constants;
+mod decimal;
+pub mod pool_math;
+pub mod simulator;
+pub mod snapshot;
+pub mod tick;
+
+pub use pool_math::*;
+pub use simulator::*;
+pub use snapshot::*;
+pub use tick::*;
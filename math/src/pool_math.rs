@@ -0,0 +1,2090 @@
+use radix_common::prelude::*;
+use sbor::rust::cmp::{max, min};
+use sbor::rust::fmt::Display;
+use sbor::rust::ops::{Add, Sub};
+
+use crate::constants::*;
+use crate::decimal::Rounding;
+use crate::tick::{Tick, TickOutside};
+
+/// The direction of a swap, seen from token X.
+#[derive(ScryptoSbor, ManifestSbor, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SwapType {
+    /// Token Y is swapped for token X.
+    BuyX,
+    /// Token X is swapped for token Y.
+    SellX,
+}
+
+/// The token in which the swap fees of a pool are collected.
+#[derive(ScryptoSbor, ManifestSbor, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FeeCollectionMode {
+    /// The fee is taken from the input token of each swap.
+    Input,
+    /// The fee is taken from the output token of each swap.
+    Output,
+    /// The fee is always collected in token X.
+    X,
+    /// The fee is always collected in token Y.
+    Y,
+}
+
+impl FeeCollectionMode {
+    /// Determines whether the fee of a swap with the given `swap_type` is taken from the output token.
+    ///
+    /// # Arguments
+    /// * `swap_type` - The type of the swap (`SellX` or `BuyX`).
+    ///
+    /// # Returns
+    /// * `bool` - True if the fee is taken from the output, false if it is taken from the input.
+    pub fn fee_on_output(&self, swap_type: SwapType) -> bool {
+        match self {
+            FeeCollectionMode::Input => false,
+            FeeCollectionMode::Output => true,
+            FeeCollectionMode::X => swap_type == SwapType::BuyX,
+            FeeCollectionMode::Y => swap_type == SwapType::SellX,
+        }
+    }
+}
+
+/// Tells the caller of a swap step whether to continue with the next tick.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StepControl {
+    Continue,
+    Break,
+}
+
+/// Calculates the total number of ticks based on the specified tick spacing.
+///
+/// This function determines the number of discrete price levels (ticks) that can exist within the range
+/// defined by `MAX_TICK`, given a specific `spacing`. The formula used ensures that the number of ticks
+/// covers the entire price range symmetrically around zero.
+///
+/// # Arguments
+/// * `spacing` - The distance between each tick.
+///
+/// # Returns
+/// The total number of ticks as a `u32`.
+fn number_of_ticks(spacing: u32) -> u32 {
+    2 * ((MAX_TICK as u32) / spacing) + 1
+}
+
+/// Computes the maximum liquidity allowed per tick for a given tick spacing.
+///
+/// This function divides the total maximum liquidity (`MAX_LIQUIDITY`) by the number of ticks
+/// to find the maximum amount of liquidity that can be allocated to each tick. This ensures
+/// that liquidity is distributed evenly across all ticks and helps in managing the liquidity concentration.
+///
+/// # Arguments
+/// * `spacing` - The distance between each tick.
+///
+/// # Returns
+/// The maximum liquidity per tick as a `PreciseDecimal`.
+pub fn max_liquidity_per_tick(spacing: u32) -> PreciseDecimal {
+    MAX_LIQUIDITY / number_of_ticks(spacing)
+}
+
+/// Converts a tick index to its corresponding price square root.
+///
+/// This function calculates the square root of the price for a given tick index. Converting the discrete tick index into a continuous price
+/// representation used in various pool calculations.
+///
+/// Instead of exponentiating `TICK_BASE_SQRT` on every call, the precomputed powers `TICK_BASE_SQRT^(2^i)`
+/// (or of its inverse for negative ticks) are multiplied for every set bit of `|tick|`. This needs at most
+/// 19 multiplications for ticks between `MIN_TICK` and `MAX_TICK`. The table holds exactly the squaring chain
+/// of `checked_powi` and the multiplications are done in the same order, so the result is bit-identical to
+/// `TICK_BASE_SQRT.checked_powi(tick)`.
+///
+/// Error bound: every truncation to 36 decimal places has an absolute error below `10^-36`. All intermediate
+/// values are at least `min(1, p)`, where `p` is the exact result, so each truncation adds a relative error of at
+/// most `10^-36 / min(1, p)`. Squaring doubles the relative error of an entry, hence the entry `2^i` carries at most
+/// `2^i` truncations worth of error, and the final product adds at most 19 more. Including the rounding of the base
+/// itself, the relative error of the result is bounded by `(|tick| + 21) * 10^-36 / min(1, p)`.
+///
+/// # Arguments
+/// * `tick` - The tick index.
+///
+/// # Returns
+/// The price square root as a `PreciseDecimal`.
+///
+/// # Panics
+/// If `|tick|` does not fit into the table, i.e. is `2^20` or larger.
+pub fn tick_to_price_sqrt(tick: i32) -> PreciseDecimal {
+    let powers = match tick < 0 {
+        true => &TICK_BASE_SQRT_INVERSE_POWERS,
+        false => &TICK_BASE_SQRT_POWERS,
+    };
+    let exponent = tick.unsigned_abs();
+    assert!(
+        exponent >> powers.len() == 0,
+        "[Tick to price]: The tick is outside the supported tick range."
+    );
+    if exponent == 0 {
+        return PreciseDecimal::ONE;
+    }
+
+    // Multiply from the highest set bit downwards to reproduce the multiplication order of `checked_powi`.
+    let highest_bit = 31 - exponent.leading_zeros() as usize;
+    (0..highest_bit)
+        .rev()
+        .filter(|bit| exponent & (1 << bit) != 0)
+        .fold(powers[highest_bit], |price_sqrt, bit| {
+            powers[bit].checked_mul(price_sqrt).unwrap()
+        })
+}
+
+/// Aligns a given tick index to the nearest valid tick that is a multiple of the specified tick spacing.
+///
+/// # Arguments
+/// * `tick` - The tick index to align.
+/// * `spacing` - The tick spacing that defines the grid.
+///
+/// # Returns
+/// The aligned tick index as an `i32`.
+pub fn align_tick(tick: i32, spacing: u32) -> i32 {
+    let spacing = spacing as i32;
+
+    (tick / spacing) * spacing
+}
+
+/// Converts a price square root to the greatest tick index whose price square root is not larger.
+///
+/// This is the exact inverse of `tick_to_price_sqrt`, i.e. `price_sqrt_to_tick(tick_to_price_sqrt(tick)) == tick`
+/// for every tick between `MIN_TICK` and `MAX_TICK`. Prices between two ticks are rounded down to the lower tick.
+///
+/// # Arguments
+/// * `price_sqrt` - The price square root.
+///
+/// # Returns
+/// The tick index as an `i32`.
+///
+/// # Panics
+/// If `price_sqrt` is outside the price range covered by `MIN_TICK` and `MAX_TICK`.
+pub fn price_sqrt_to_tick(price_sqrt: PreciseDecimal) -> i32 {
+    assert!(
+        price_sqrt >= tick_to_price_sqrt(MIN_TICK) && price_sqrt <= tick_to_price_sqrt(MAX_TICK),
+        "[Price to tick]: The price is outside the supported price range."
+    );
+
+    // Binary search for the greatest tick with a price square root less than or equal to `price_sqrt`.
+    let (mut lower, mut upper) = (MIN_TICK, MAX_TICK);
+    while lower < upper {
+        let middle = lower + (upper - lower + 1) / 2;
+        match tick_to_price_sqrt(middle) <= price_sqrt {
+            true => lower = middle,
+            false => upper = middle - 1,
+        }
+    }
+    lower
+}
+
+/// Converts a price to the greatest tick index whose price is not larger.
+///
+/// # Arguments
+/// * `price` - The price of X in terms of Y.
+///
+/// # Returns
+/// The tick index as an `i32`.
+///
+/// # Panics
+/// If `price` is not positive or outside the price range covered by `MIN_TICK` and `MAX_TICK`.
+pub fn price_to_tick(price: Decimal) -> i32 {
+    assert!(
+        price > dec!(0),
+        "[Price to tick]: The price needs to be positive."
+    );
+    price_sqrt_to_tick(PreciseDecimal::from(price).checked_sqrt().unwrap())
+}
+
+/// Rounding modes for mapping a price range onto the tick grid of a pool.
+#[derive(ScryptoSbor, ManifestSbor, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TickRounding {
+    /// Round both bounds towards the inside of the range, the resulting range is never wider than requested.
+    Inward,
+    /// Round both bounds towards the outside of the range, the resulting range always covers the requested one.
+    Outward,
+    /// Round both bounds to the closest aligned tick in terms of relative price difference.
+    Nearest,
+}
+
+/// Calculates the aligned ticks directly below and above a price square root.
+///
+/// In contrast to `align_tick` the ticks are rounded towards negative and positive infinity respectively.
+/// If the price square root matches an aligned tick exactly, both ticks are equal.
+///
+/// # Arguments
+/// * `price_sqrt` - The price square root.
+/// * `spacing` - The tick spacing that defines the grid.
+///
+/// # Returns
+/// A tuple containing the aligned tick below and the aligned tick above the price square root.
+fn enclosing_aligned_ticks(price_sqrt: PreciseDecimal, spacing: u32) -> (i32, i32) {
+    let spacing = spacing as i32;
+    let lower = price_sqrt_to_tick(price_sqrt).div_euclid(spacing) * spacing;
+    match tick_to_price_sqrt(lower) == price_sqrt {
+        true => (lower, lower),
+        false => (lower, lower + spacing),
+    }
+}
+
+/// Maps a price range onto the tick grid of a pool using the given rounding mode.
+///
+/// # Arguments
+/// * `price_left` - The lower price of the range.
+/// * `price_right` - The upper price of the range.
+/// * `spacing` - The tick spacing that defines the grid.
+/// * `rounding` - How prices between aligned ticks are rounded.
+///
+/// # Returns
+/// A tuple containing the aligned left and right bound.
+///
+/// # Panics
+/// - If a price is not positive or outside the price range covered by `MIN_TICK` and `MAX_TICK`.
+/// - If the left bound is not smaller than the right bound after rounding.
+pub fn price_range_to_ticks(
+    price_left: Decimal,
+    price_right: Decimal,
+    spacing: u32,
+    rounding: TickRounding,
+) -> (i32, i32) {
+    let round = |price: Decimal, round_up: bool| {
+        assert!(
+            price > dec!(0),
+            "[Price to tick]: The price needs to be positive."
+        );
+        let price_sqrt = PreciseDecimal::from(price).checked_sqrt().unwrap();
+        let (lower, upper) = enclosing_aligned_ticks(price_sqrt, spacing);
+        match rounding {
+            TickRounding::Inward | TickRounding::Outward => match round_up {
+                true => upper,
+                false => lower,
+            },
+            // The geometric mean of the enclosing prices is the midpoint in terms of relative price difference.
+            TickRounding::Nearest => {
+                match price_sqrt * price_sqrt
+                    < tick_to_price_sqrt(lower) * tick_to_price_sqrt(upper)
+                {
+                    true => lower,
+                    false => upper,
+                }
+            }
+        }
+    };
+
+    let left_bound = round(price_left, rounding == TickRounding::Inward);
+    let right_bound = round(price_right, rounding == TickRounding::Outward);
+    assert!(
+        left_bound < right_bound,
+        "[Price to tick]: The price range does not contain at least one tick spacing after rounding."
+    );
+    (left_bound, right_bound)
+}
+
+/// Calculates the allowed amounts of tokens `x` and `y` that can be added to the pool based on the current
+/// pool price and price bounds.
+///
+/// It accounts for precision errors by adjusting the amounts with a margin derived from the divisibility of each token.
+///
+/// # Arguments
+/// - `x_amount`: The initial amount of token `x`.
+/// - `x_divisibility`: The divisibility of token `x`, used for precision adjustments.
+/// - `y_amount`: The initial amount of token `y`.
+/// - `y_divisibility`: The divisibility of token `y`, used for precision adjustments.
+/// - `price_sqrt`: The current square root of the pool's price.
+/// - `price_left_bound_sqrt`: The lower bound square root of the price for the liquidity position.
+/// - `price_right_bound_sqrt`: The upper bound square root of the price for the liquidity position.
+///
+/// # Returns
+/// A tuple containing:
+/// - `liquidity`: The maximum liquidity that can be added given the token amounts and price bounds.
+/// - `x_amount_allowed`: The adjusted amount of token `x` that can be added.
+/// - `y_amount_allowed`: The adjusted amount of token `y` that can be added.
+pub fn addable_amounts(
+    x_amount: Decimal,
+    x_divisibility: u8,
+    y_amount: Decimal,
+    y_divisibility: u8,
+    price_sqrt: PreciseDecimal,
+    price_left_bound_sqrt: PreciseDecimal,
+    price_right_bound_sqrt: PreciseDecimal,
+) -> (PreciseDecimal, Decimal, Decimal) {
+    /*
+    Deduct two units of divisibility from the initial token amounts:
+     - One unit to account for rounding up in the final calculations.
+     - Another unit to correct for potential precision errors.
+
+    We add PreciseDecimal::ATTO to intermediate calculations when necessary to ensure:
+    - The computed liquidity is consistently less than or equal to the theoretical value with perfect precision.
+    - The calculated allowed token amounts are always greater than or equal to their theoretical values with perfect precision.
+    */
+
+    let x_precision_margin = divisibility_unit(x_divisibility) * dec!(2);
+    let y_precision_margin = divisibility_unit(y_divisibility) * dec!(2);
+
+    let x_amount_safe =
+        PreciseDecimal::from(subtract_precision_margin(x_amount, x_precision_margin));
+    let y_amount_safe =
+        PreciseDecimal::from(subtract_precision_margin(y_amount, y_precision_margin));
+
+    // Determine the scale and liquidity based on the price bounds.
+    if price_sqrt <= price_left_bound_sqrt {
+        let x_scale = x_scale_safe(price_left_bound_sqrt, price_right_bound_sqrt);
+        let x_liquidity = x_amount_safe / x_scale;
+        return (x_liquidity, x_amount, dec!(0));
+    }
+
+    if price_sqrt >= price_right_bound_sqrt {
+        let y_scale = y_scale_safe(price_left_bound_sqrt, price_right_bound_sqrt);
+        let y_liquidity = y_amount_safe / y_scale;
+        return (y_liquidity, dec!(0), y_amount);
+    }
+
+    let x_scale = x_scale_safe(price_sqrt, price_right_bound_sqrt);
+    let x_liquidity = x_amount_safe / x_scale;
+
+    let y_scale = y_scale_safe(price_left_bound_sqrt, price_sqrt);
+    let y_liquidity = y_amount_safe / y_scale;
+
+    let liquidity = min(x_liquidity, y_liquidity);
+
+    /*
+    Removing 1 divisibility unit from the input for precision correction is enough because:
+    - PreciseDecimal::ATTO * x_scale + 2 * PreciseDecimal::ATTO < Decimal::ATTO
+    - PreciseDecimal::ATTO * y_scale + 2 * PreciseDecimal::ATTO < Decimal::ATTO
+    */
+    let x_amount_allowed = (liquidity * x_scale + Decimal::ATTO).ceil_to(x_divisibility);
+    let y_amount_allowed = (liquidity * y_scale + Decimal::ATTO).ceil_to(y_divisibility);
+
+    // Adjust the allowed amounts to ensure they are within the precision margin.
+    (
+        liquidity,
+        adjust_within_margin(x_amount, x_amount_allowed, x_precision_margin),
+        adjust_within_margin(y_amount, y_amount_allowed, y_precision_margin),
+    )
+}
+
+/// Calculates the safe scale for token `x` based on the price bounds.
+///
+/// This function determines the scale factor for token `x` to ensure that the liquidity calculations
+/// remain within the specified price bounds.
+///
+/// # Arguments
+/// - `lower_price_sqrt`: The lower bound square root of the price.
+/// - `upper_price_sqrt`: The upper bound square root of the price.
+///
+/// # Returns
+/// The scale factor as a `PreciseDecimal`.
+fn x_scale_safe(
+    lower_price_sqrt: PreciseDecimal,
+    upper_price_sqrt: PreciseDecimal,
+) -> PreciseDecimal {
+    pdec!(1) / lower_price_sqrt + PreciseDecimal::ATTO - pdec!(1) / upper_price_sqrt
+}
+
+/// Calculates the safe scale for token `y` based on the price bounds.
+///
+/// This function determines the scale factor for token `y` to ensure that the liquidity
+/// calculations remain within the specified price bounds.
+///
+/// # Arguments
+/// - `lower_price_sqrt`: The lower bound square root of the price.
+/// - `upper_price_sqrt`: The upper bound square root of the price.
+///
+/// # Returns
+/// The scale factor as a `PreciseDecimal`.
+fn y_scale_safe(
+    lower_price_sqrt: PreciseDecimal,
+    upper_price_sqrt: PreciseDecimal,
+) -> PreciseDecimal {
+    // Both price_sqrt are treated as constants in the current adding of liquidity and therefore
+    // no arithmetic correction is necessary.
+    upper_price_sqrt - lower_price_sqrt
+}
+
+/// Returns the divisibility unit for a given divisibility.
+///
+/// # Arguments
+/// - `divisibility`: The divisibility level.
+///
+/// # Returns
+/// The corresponding precision unit as a `Decimal`.
+fn divisibility_unit(divisibility: u8) -> Decimal {
+    DIVISIBILITY_UNITS[divisibility as usize]
+}
+
+/// Subtracts a precision margin from an amount, ensuring the result is non-negative.
+///
+/// # Arguments
+/// - `amount`: The original amount.
+/// - `margin`: The margin to subtract.
+///
+/// # Returns
+/// The adjusted amount as a `Decimal`.
+fn subtract_precision_margin(amount: Decimal, margin: Decimal) -> Decimal {
+    max(amount - margin, dec!(0))
+}
+
+/// Adjusts an amount to ensure it is within a specified margin of an allowed amount.
+///
+/// This function ensures that the actual amount does not exceed the allowed amount by more than the
+/// specified margin, adjusting if necessary.
+///
+/// # Arguments
+/// - `amount`: The original amount.
+/// - `allowed_amount`: The maximum allowed amount.
+/// - `margin`: The margin within which the amount must fall.
+///
+/// # Returns
+/// The adjusted amount as a `Decimal`.
+fn adjust_within_margin(amount: Decimal, allowed_amount: Decimal, margin: Decimal) -> Decimal {
+    assert!(
+        amount >= allowed_amount,
+        "Amount needs to be greater or equal than allowed_amount"
+    );
+    if amount - allowed_amount <= margin {
+        return amount;
+    }
+    allowed_amount
+}
+
+/// Calculates the withdrawable amounts of x and y tokens based on the provided liquidity and price bounds.
+/// This function is crucial for determining how much of each token can be safely removed from a liquidity position
+/// without violating the constraints set by the current and boundary prices.
+///
+/// # Arguments
+///
+/// - `liquidity`: The total liquidity available in the position.
+/// - `price_sqrt`: The square root of the current pool price.
+/// - `price_left_bound_sqrt`: The square root of the lower price boundary of the liquidity position.
+/// - `price_right_bound_sqrt`: The square root of the upper price boundary of the liquidity position.
+/// - `x_divisibility`: The divisibility of token x, used for rounding down the withdrawable amount.
+/// - `y_divisibility`: The divisibility of token y, used for rounding down the withdrawable amount.
+///
+/// # Returns
+///
+/// A tuple containing the withdrawable amounts of token `x` and token `y`.
+///
+pub fn removable_amounts(
+    liquidity: PreciseDecimal,
+    price_sqrt: PreciseDecimal,
+    price_left_bound_sqrt: PreciseDecimal,
+    price_right_bound_sqrt: PreciseDecimal,
+    x_divisibility: u8,
+    y_divisibility: u8,
+) -> (Decimal, Decimal) {
+    // When the current price is below the lower bound, all liquidity can be withdrawn as token x.
+    if price_sqrt <= price_left_bound_sqrt {
+        let x_amount = max(
+            liquidity / price_left_bound_sqrt
+                - (liquidity / price_right_bound_sqrt + PreciseDecimal::ATTO),
+            pdec!(0),
+        );
+        return (x_amount.floor_to(x_divisibility), dec!(0));
+    }
+
+    // When the current price is above the upper bound, all liquidity can be withdran as token y.
+    if price_sqrt >= price_right_bound_sqrt {
+        let y_amount = liquidity * (price_right_bound_sqrt - price_left_bound_sqrt);
+        return (dec!(0), y_amount.floor_to(y_divisibility));
+    }
+
+    // When the current price is within the bounds, calculate the withdrawable amounts for both tokens.
+    let x_amount = max(
+        liquidity / price_sqrt - (liquidity / price_right_bound_sqrt + PreciseDecimal::ATTO),
+        pdec!(0),
+    );
+    let y_amount = liquidity * (price_sqrt - price_left_bound_sqrt);
+
+    (
+        x_amount.floor_to(x_divisibility),
+        y_amount.floor_to(y_divisibility),
+    )
+}
+
+/// Calculates the net input amount after deducting referrer, liquidity provider and protocol fees.
+///
+/// Determining the actual amount of tokens that can be used in further transactions after accounting for
+/// the fees associated with the transaction. It ensures transparency in fee distribution and protects against
+/// negative input amounts due to rounding errors in fee calculations.
+///
+/// The referrer fee and the input fee of the pool are both applied to the gross input. The referrer fee is rounded
+/// down in favour of the trader, the input fee is rounded up in favour of the pool. Since the pool fees do not depend
+/// on the referrer fee, a trader can not lower the fees paid to the liquidity providers by referring themselves.
+///
+/// # Arguments
+/// * `input_amount` - The total amount of tokens before fees are applied.
+/// * `input_fee_rate` - The rate at which the input fee is applied to the `input_amount`.
+/// * `fee_protocol_share` - The fraction of the total fee that goes to the protocol.
+/// * `referrer_fee_rate` - The rate at which the referrer fee is applied to the `input_amount`.
+/// * `divisibility` - The precision to which the amounts should be rounded, typically the token divisibility.
+///
+/// # Returns
+/// * `(Decimal, Decimal, Decimal, Decimal)` - A tuple containing:
+///   - `input_amount_net`: The amount of tokens after fees are deducted.
+///   - `input_fee_lp`: The fee amount allocated to the liquidity provider.
+///   - `protocol_fee_input`: The fee amount allocated to the protocol.
+///   - `referrer_fee_input`: The fee amount allocated to the referrer.
+pub fn input_amount_net(
+    input_amount: Decimal,
+    input_fee_rate: Decimal,
+    fee_protocol_share: Decimal,
+    referrer_fee_rate: Decimal,
+    divisibility: u8,
+) -> (Decimal, Decimal, Decimal, Decimal) {
+    // Referrer fees are being rounded down in favour of the trader
+    let referrer_fee_input: Decimal =
+        (PreciseDecimal::from(input_amount) * referrer_fee_rate).floor_to(divisibility);
+    let input_amount_gross: PreciseDecimal = input_amount.into();
+    /*
+    Valid pre-conditions:
+      `0 <= input_fee_rate <= 1`
+      => `0 <= input_amount_gross * input_fee_rate <= input_amount_gross`
+      => ceiling to the 18th decimal can lead to `input_fee_lp > input_amount_gross` (with input_fee_rate = 1)
+         but only if input_amount_gross has non-zero digits afte the 18th decimal place
+         otherwise it is guaranteed that `input_fee_lp <= input_amount_gross`
+      => since input_amount_gross is converted from Decimal (with only 18 decimal places) it is strictly true that:
+         `input_fee_lp < input_amount_gross`
+    Therefore:
+      input_amount_net >= 0
+    In other words the calculated input_amount_net is always positve or equal zero.
+    With a referrer fee the referrer fee rate is bounded by `REFERRER_FEE_RATE_MAX`, so the sum of both fees stays
+    below the input amount for all valid fee rates and the assertion below only guards against dust inputs.
+    */
+    let input_fee_total: Decimal = (input_amount_gross * input_fee_rate).ceil_to(divisibility);
+    // Protocol fees are being rounded down in favour of liquidity provider fees
+    let protocol_fee_input = (input_fee_total * fee_protocol_share).floor_to(divisibility);
+    let input_fee_lp = input_fee_total - protocol_fee_input;
+    let input_amount_net: Decimal = input_amount - referrer_fee_input - input_fee_total;
+
+    assert!(
+        input_amount_net > Decimal::ZERO,
+        "Input amount net needs to be positive!"
+    );
+
+    (
+        input_amount_net,
+        input_fee_lp,
+        protocol_fee_input,
+        referrer_fee_input,
+    )
+}
+
+/// Calculates the new price after a swap operation, adjusting for precision errors.
+///
+/// This function determines the new square root price (`price_sqrt`) after a swap has occurred.
+/// It accounts for precision errors by adjusting the input amount slightly before performing the calculation.
+/// The adjustment ensures that the resulting price is slightly less favorable, compensating forthe inherent
+/// imprecision in fixed-point arithmetic.
+///
+/// # Arguments
+/// * `swap_type` - The type of swap (`BuyX` or `SellX`) which determines the direction of the price change.
+/// * `liquidity` - The current liquidity in the pool, affecting how much the price changes per unit of input.
+/// * `price_sqrt` - The square root of the current price.
+/// * `input_amount` - The amount of the input token.
+/// * `input_divisibility` - The divisibility of the input token, used to adjust the input amount for precision.
+///
+/// # Returns
+/// * `PreciseDecimal` - The new square root price after the swap.
+pub fn new_price(
+    swap_type: SwapType,
+    liquidity: PreciseDecimal,
+    price_sqrt: PreciseDecimal,
+    input_amount: Decimal,
+    input_divisibility: u8,
+) -> PreciseDecimal {
+    /*
+    For BuyX transactions, the adjusted new price should be marginally less favorable, specifically, it should be
+    slightly lower than the precise arithmetic result yet still above the current price. Conversely, for SellX
+    transactions, the adjusted new price should be marginally higher than the precise arithmetic result but remain
+    below the current price.
+    To account for the precision error in input_step, we subtract one divisibility unit from input_amount.
+    */
+    let input_amount = max(
+        PreciseDecimal::from(input_amount) - divisibility_unit(input_divisibility),
+        pdec!(0),
+    );
+    match swap_type {
+        SwapType::BuyX => max(input_amount / liquidity + price_sqrt, price_sqrt),
+        SwapType::SellX => min(
+            (liquidity * price_sqrt + PreciseDecimal::ATTO)
+                / (liquidity + input_amount * price_sqrt)
+                + PreciseDecimal::ATTO,
+            price_sqrt,
+        ),
+    }
+}
+
+/// Adjusts the current liquidity based on the swap type and the change in liquidity.
+///
+/// # Arguments
+/// * `swap_type` - The type of swap (`BuyX` or `SellX`) which determines the direction of liquidity adjustment.
+/// * `liquidity` - The current liquidity of the pool.
+/// * `delta_liquidity` - The amount by which the liquidity needs to be adjusted.
+///
+/// # Returns
+/// * `PreciseDecimal` - The new liquidity after applying the adjustment.
+fn new_liquidity(
+    swap_type: SwapType,
+    liquidity: PreciseDecimal,
+    delta_liquidity: PreciseDecimal,
+) -> PreciseDecimal {
+    match swap_type {
+        SwapType::BuyX => liquidity + delta_liquidity,
+        SwapType::SellX => liquidity - delta_liquidity,
+    }
+}
+
+/// Calculates the input amount required to move the price from `price_sqrt` to `price_next_sqrt`.
+///
+/// This function ensures that the input amount respects the divisibility of the token and adheres to precision
+/// constraints inherent in fixed-point arithmetic operations. The result is always rounded up to the nearest
+/// valid divisibility unit, ensuring that the price movement is achievable with the given input amount by always
+/// rounding in favour of the pool.
+///
+/// # Arguments
+/// * `swap_type` - The type of swap (`BuyX` or `SellX`) which determines the direction of the price movement.
+/// * `liquidity` - The current liquidity in the (sub) pool, which affects how much the price moves per unit of input.
+/// * `price_sqrt` - The square root of the current price.
+/// * `price_next_sqrt` - The square root of the target price after the swap.
+/// * `input_divisibility` - The divisibility of the input token, used to round the result.
+///
+/// # Returns
+/// * `Decimal` - The input amount, adjusted for divisibility and rounded up.
+///
+/// # Panics
+/// Panics if the absolute difference calculation fails, which should not occur with valid input data.
+pub fn input_step(
+    swap_type: SwapType,
+    liquidity: PreciseDecimal,
+    price_sqrt: PreciseDecimal,
+    price_next_sqrt: PreciseDecimal,
+    input_divisibility: u8,
+) -> Decimal {
+    /*
+    // Liquidity, price_sqrt, and price_next_sqrt are treated as constants within a single step.
+    // The precision error is limited to 10^36 due to the use of PreciseDecimal units.
+
+    // To address precision errors when amounts or prices are nearly identical, the input amount in
+    // new_price is reduced by one divisibility unit and rounded down to the nearest divisibility unit.
+    // This adjustment guarantees that the input_step is always at least as large as needed but does not
+    // exceed the specified input amount.
+
+    // The result is guaranteed to be greater than or equal to the precise numerical result. The precision
+    // error is limited to one divisibility unit.
+    */
+    (match swap_type {
+        SwapType::BuyX => {
+            // abs is calculated on exact result, precision correction is independent of abs
+            liquidity * (price_sqrt - price_next_sqrt).checked_abs().unwrap() + PreciseDecimal::ATTO
+        }
+        SwapType::SellX => {
+            (liquidity / price_sqrt - liquidity / price_next_sqrt)
+                .checked_abs()
+                .unwrap()
+                + PreciseDecimal::ATTO
+        }
+    })
+    .ceil_to(input_divisibility)
+}
+
+/// Calculates the output amount for a given swap step, ensuring the result is always less than or equal to the
+/// exact numeric result.
+///
+/// This function is crucial for maintaining the integrity of the pool's state by preventing overestimation of
+/// output amounts during swaps. It uses the liquidity and the square roots of the prices at the current and
+/// next ticks to compute the output amount, adjusted for divisibility.
+///
+/// # Arguments
+/// * `swap_type` - The type of swap (`BuyX` or `SellX`) which determines the calculation method.
+/// * `liquidity` - The liquidity available at the current tick.
+/// * `price_sqrt` - The square root of the price at the current tick.
+/// * `price_next_sqrt` - The square root of the price at the next tick.
+/// * `output_divisibility` - The divisibility of the output token, used to round the result.
+///
+/// # Returns
+/// * `Decimal` - The calculated output amount, rounded down to ensure it does not exceed the exact result.
+///
+/// # Panics
+/// Panics if the absolute difference calculation fails, which should not occur with valid input data.
+pub fn output_step(
+    swap_type: SwapType,
+    liquidity: PreciseDecimal,
+    price_sqrt: PreciseDecimal,
+    price_next_sqrt: PreciseDecimal,
+    output_divisibility: u8,
+) -> Decimal {
+    (match swap_type {
+        SwapType::BuyX => {
+            // For BuyX, calculate the difference in liquidity per price unit, adjust for precision, and ensure non-negative result.
+            max(
+                (liquidity / price_sqrt - liquidity / price_next_sqrt)
+                    .checked_abs()
+                    .unwrap()
+                    - PreciseDecimal::ATTO,
+                pdec!(0),
+            )
+        }
+        SwapType::SellX => {
+            // For SellX, directly calculate the output based on liquidity change across price difference.
+            liquidity * (price_sqrt - price_next_sqrt).checked_abs().unwrap()
+        }
+    })
+    .floor_to(output_divisibility) // Round down to the nearest output divisibility unit.
+}
+
+/// Number of bisection iterations used to determine the zap swap amount.
+const ZAP_BISECTION_ITERATIONS: u32 = 64;
+
+/// Determines which token is in excess for a position between the given price bounds.
+///
+/// # Arguments
+/// * `x_amount` - The amount of token `x` available.
+/// * `y_amount` - The amount of token `y` available.
+/// * `price_sqrt` - The current square root price of the pool.
+/// * `price_left_bound_sqrt` - The lower bound square root price of the position.
+/// * `price_right_bound_sqrt` - The upper bound square root price of the position.
+///
+/// # Returns
+/// * `SwapType` - `SellX` if token `x` is in excess and has to be swapped, `BuyX` otherwise.
+pub fn rebalance_swap_type(
+    x_amount: Decimal,
+    y_amount: Decimal,
+    price_sqrt: PreciseDecimal,
+    price_left_bound_sqrt: PreciseDecimal,
+    price_right_bound_sqrt: PreciseDecimal,
+) -> SwapType {
+    if price_sqrt <= price_left_bound_sqrt {
+        return SwapType::BuyX;
+    }
+    if price_sqrt >= price_right_bound_sqrt {
+        return SwapType::SellX;
+    }
+    let x_required = pdec!(1) / price_sqrt - pdec!(1) / price_right_bound_sqrt;
+    let y_required = price_sqrt - price_left_bound_sqrt;
+    match PreciseDecimal::from(x_amount) * y_required > PreciseDecimal::from(y_amount) * x_required
+    {
+        true => SwapType::SellX,
+        false => SwapType::BuyX,
+    }
+}
+
+/// Calculates the amount of the input token that has to be swapped before adding liquidity.
+///
+/// The swap is approximated against the active (sub) pool only, i.e. assuming that no tick is crossed.
+/// The amount is determined by bisection such that the available output token plus the swap output and the
+/// remaining input match the ratio required by a position between `price_left_bound_sqrt` and
+/// `price_right_bound_sqrt` at the price after the swap.
+/// If the swap crosses ticks the resulting ratio is slightly off, which only leads to a small remainder.
+///
+/// # Arguments
+/// * `swap_type` - The type of swap (`BuyX` or `SellX`) the input token would perform.
+/// * `input_amount` - The total amount of the input token available.
+/// * `output_amount` - The amount of the output token already available, zero for single-sided liquidity.
+/// * `input_fee_rate` - The input fee rate of the pool.
+/// * `fee_collection_mode` - The fee collection mode of the pool, determining whether the fee reduces the
+///   swapped input or the swap output.
+/// * `liquidity` - The active liquidity of the pool.
+/// * `price_sqrt` - The current square root price of the pool.
+/// * `price_left_bound_sqrt` - The lower bound square root price of the position.
+/// * `price_right_bound_sqrt` - The upper bound square root price of the position.
+/// * `input_divisibility` - The divisibility of the input token, used to round the result down.
+///
+/// # Returns
+/// * `Decimal` - The amount of the input token to swap, between zero and `input_amount`.
+pub fn zap_swap_amount(
+    swap_type: SwapType,
+    input_amount: Decimal,
+    output_amount: Decimal,
+    input_fee_rate: Decimal,
+    fee_collection_mode: FeeCollectionMode,
+    liquidity: PreciseDecimal,
+    price_sqrt: PreciseDecimal,
+    price_left_bound_sqrt: PreciseDecimal,
+    price_right_bound_sqrt: PreciseDecimal,
+    input_divisibility: u8,
+) -> Decimal {
+    // Positions outside of the current price only require one of the tokens.
+    match swap_type {
+        SwapType::SellX if price_sqrt <= price_left_bound_sqrt => return dec!(0),
+        SwapType::SellX if price_sqrt >= price_right_bound_sqrt => return input_amount,
+        SwapType::BuyX if price_sqrt >= price_right_bound_sqrt => return dec!(0),
+        SwapType::BuyX if price_sqrt <= price_left_bound_sqrt => return input_amount,
+        _ => {}
+    }
+    if liquidity == pdec!(0) {
+        return dec!(0);
+    }
+
+    let input_total = PreciseDecimal::from(input_amount);
+    let output_total = PreciseDecimal::from(output_amount);
+    // The fee either reduces the input moving the price or the output of the swap.
+    let fee_share = PreciseDecimal::from(Decimal::ONE - input_fee_rate);
+    let (input_share, output_share) = match fee_collection_mode.fee_on_output(swap_type) {
+        true => (pdec!(1), fee_share),
+        false => (fee_share, pdec!(1)),
+    };
+    let mut lower = pdec!(0);
+    let mut upper = input_total;
+    for _ in 0..ZAP_BISECTION_ITERATIONS {
+        let swap_amount = (lower + upper) / 2;
+        let input_net = swap_amount * input_share;
+        // Compare the ratio of output to remaining input with the ratio the position requires at the new price.
+        // A positive surplus indicates that too little of the input token has been swapped.
+        let surplus = match swap_type {
+            SwapType::SellX => {
+                let price_new_sqrt = liquidity * price_sqrt / (liquidity + input_net * price_sqrt);
+                if price_new_sqrt <= price_left_bound_sqrt {
+                    upper = swap_amount;
+                    continue;
+                }
+                let y_output = liquidity * (price_sqrt - price_new_sqrt) * output_share;
+                (input_total - swap_amount) * (price_new_sqrt - price_left_bound_sqrt)
+                    - (output_total + y_output)
+                        * (pdec!(1) / price_new_sqrt - pdec!(1) / price_right_bound_sqrt)
+            }
+            SwapType::BuyX => {
+                let price_new_sqrt = price_sqrt + input_net / liquidity;
+                if price_new_sqrt >= price_right_bound_sqrt {
+                    upper = swap_amount;
+                    continue;
+                }
+                let x_output = (liquidity / price_sqrt - liquidity / price_new_sqrt) * output_share;
+                (input_total - swap_amount)
+                    * (pdec!(1) / price_new_sqrt - pdec!(1) / price_right_bound_sqrt)
+                    - (output_total + x_output) * (price_new_sqrt - price_left_bound_sqrt)
+            }
+        };
+        if surplus > pdec!(0) {
+            lower = swap_amount;
+        } else {
+            upper = swap_amount;
+        }
+    }
+    min(lower.floor_to(input_divisibility), input_amount)
+}
+
+/// Predefined shapes of how liquidity is distributed over multiple bins by `add_liquidity_distribution`.
+#[derive(ScryptoSbor, ManifestSbor, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LiquidityShape {
+    /// Equal liquidity in every bin.
+    Uniform,
+    /// Bell curve following the binomial coefficients, most liquidity around the center.
+    Normal,
+    /// Triangle with weights increasing linearly towards the center.
+    Curve,
+    /// Inverted triangle with weights increasing linearly towards the outer bins.
+    BidAsk,
+    /// Weights doubling with every bin towards the center.
+    Exponential,
+}
+
+/// Calculates the relative weights of the bins for a liquidity shape.
+///
+/// The weights are symmetric around the center and normalized to sum up to one.
+///
+/// # Arguments
+/// * `shape` - The liquidity shape.
+/// * `num_bins` - The number of bins, between one and `MAX_DISTRIBUTION_BINS`.
+///
+/// # Returns
+/// * `Vec<Decimal>` - The weight of every bin from left to right.
+///
+/// # Panics
+/// If `num_bins` is zero or larger than `MAX_DISTRIBUTION_BINS`.
+pub fn shape_weights(shape: LiquidityShape, num_bins: u32) -> Vec<Decimal> {
+    assert!(
+        (1..=MAX_DISTRIBUTION_BINS).contains(&num_bins),
+        "[Liquidity shape]: The number of bins needs to be between 1 and {}.",
+        MAX_DISTRIBUTION_BINS
+    );
+    let n = num_bins as i64;
+    let max_distance = n - 1;
+    // Twice the distance of a bin to the center, which keeps the distance integral for an even number of bins.
+    let distance = |i: i64| (2 * i - max_distance).abs();
+
+    let weights: Vec<Decimal> = (0..n)
+        .map(|i| match shape {
+            LiquidityShape::Uniform => dec!(1),
+            LiquidityShape::Normal => binomial_coefficient(max_distance, i),
+            LiquidityShape::Curve => Decimal::from((max_distance - distance(i)) / 2 + 1),
+            LiquidityShape::BidAsk => Decimal::from(distance(i) / 2 + 1),
+            LiquidityShape::Exponential => {
+                Decimal::from(1i64 << ((max_distance - distance(i)) / 2))
+            }
+        })
+        .collect();
+
+    let total: Decimal = weights.iter().fold(dec!(0), |sum, weight| sum + *weight);
+    weights.into_iter().map(|weight| weight / total).collect()
+}
+
+/// Calculates the binomial coefficient `n over k`.
+fn binomial_coefficient(n: i64, k: i64) -> Decimal {
+    let k = min(k, n - k);
+    (0..k).fold(dec!(1), |coefficient, i| {
+        coefficient * Decimal::from(n - i) / Decimal::from(i + 1)
+    })
+}
+
+/// Result of a simulated swap, see `quote_swap` of the pool and `PoolSimulator::swap`.
+#[derive(ScryptoSbor, Clone, Debug, PartialEq)]
+pub struct SwapQuote {
+    pub output_amount: Decimal,
+    pub remainder_amount: Decimal,
+    pub input_fee_lp: Decimal,
+    pub input_fee_protocol: Decimal,
+    pub input_fee_referrer: Decimal,
+    pub output_fee_lp: Decimal,
+    pub output_fee_protocol: Decimal,
+    pub price_sqrt: PreciseDecimal,
+    pub active_tick: Option<i32>,
+    pub ticks_crossed: u32,
+    pub partial_fill: bool,
+}
+
+/// The state of a pool a swap starts from, see `SwapState::new`.
+#[derive(Clone, Debug)]
+pub struct SwapPoolState {
+    pub pool_address: ComponentAddress,
+    pub x_address: ResourceAddress,
+    pub y_address: ResourceAddress,
+    pub x_divisibility: u8,
+    pub y_divisibility: u8,
+    pub price_sqrt: PreciseDecimal,
+    pub active_tick: Option<i32>,
+    pub active_liquidity: PreciseDecimal,
+    pub x_lp_fee: PreciseDecimal,
+    pub y_lp_fee: PreciseDecimal,
+    pub input_fee_rate: Decimal,
+    pub fee_protocol_share: Decimal,
+    pub fee_collection_mode: FeeCollectionMode,
+    pub seconds_global: u64,
+}
+
+#[derive(ScryptoSbor, Clone, Debug)]
+pub struct SwapState {
+    pub pool_address: ComponentAddress,
+    pub input_address: ResourceAddress,
+    pub output_address: ResourceAddress,
+    pub input: Decimal,
+    pub input_divisibility: u8,
+    pub output: Decimal,
+    pub output_divisibility: u8,
+    pub remainder: Decimal,
+    pub remainder_fee_lp: Decimal,
+    pub liquidity: PreciseDecimal,
+    pub active_tick: Option<i32>,
+    pub price_sqrt: PreciseDecimal,
+    pub price_sqrt_sell_cache: Option<PreciseDecimal>,
+    pub swap_type: SwapType,
+    pub input_fee_rate: Decimal,
+    pub fee_protocol_share: Decimal,
+    pub fee_lp_share: Decimal,
+    pub input_share: Decimal,
+    pub input_fee_share: Decimal,
+    pub fee_lp_input: Decimal,
+    pub fee_protocol_input: Decimal,
+    pub fee_protocol_max: Decimal,
+    pub fee_referrer_input: Decimal,
+    pub fee_referrer_max: Decimal,
+    pub fee_on_output: bool,
+    pub fee_lp_output: Decimal,
+    pub fee_protocol_output: Decimal,
+    pub global_input_fee_lp: PreciseDecimal,
+    pub global_output_fee_lp: PreciseDecimal,
+    pub global_seconds: u64,
+    pub crossed_ticks: Vec<TickOutside>,
+    pub max_ticks_crossed: Option<u32>,
+    pub partial_fill: bool,
+}
+
+impl SwapState {
+    /// Creates the initial state of a swap based on the current pool state.
+    ///
+    /// # Arguments
+    /// * `pool` - The state of the pool before the swap.
+    /// * `swap_type` - The type of the swap (`SellX` or `BuyX`).
+    /// * `input_amount` - The gross input amount including fees.
+    /// * `max_ticks_crossed` - The optional maximum number of ticks the swap is allowed to cross.
+    /// * `referrer_fee_rate` - The rate of the referrer fee applied to the `input_amount`.
+    ///
+    /// # Returns
+    /// * `SwapState` - The state before any tick has been processed.
+    pub fn new(
+        pool: &SwapPoolState,
+        swap_type: SwapType,
+        input_amount: Decimal,
+        max_ticks_crossed: Option<u32>,
+        referrer_fee_rate: Decimal,
+    ) -> Self {
+        let (input_address, output_address, input_divisibility, output_divisibility) =
+            match swap_type {
+                SwapType::BuyX => (
+                    pool.y_address,
+                    pool.x_address,
+                    pool.y_divisibility,
+                    pool.x_divisibility,
+                ),
+                SwapType::SellX => (
+                    pool.x_address,
+                    pool.y_address,
+                    pool.x_divisibility,
+                    pool.y_divisibility,
+                ),
+            };
+        let (global_input_fee_lp, global_output_fee_lp) = match swap_type {
+            SwapType::BuyX => (pool.y_lp_fee, pool.x_lp_fee),
+            SwapType::SellX => (pool.x_lp_fee, pool.y_lp_fee),
+        };
+
+        // If the fee is collected from the output, no fee is applied to the input.
+        let fee_on_output = pool.fee_collection_mode.fee_on_output(swap_type);
+        let applied_input_fee_rate = match fee_on_output {
+            true => Decimal::ZERO,
+            false => pool.input_fee_rate,
+        };
+
+        /*
+        The following invariants are valid:
+            input_share + input_fee_share + referrer_fee_rate = 1
+            fee_lp_share + fee_protocol_share = 1
+         */
+        let (input_amount_net, input_fee_lp, input_fee_protocol, input_fee_referrer) =
+            input_amount_net(
+                input_amount,
+                applied_input_fee_rate,
+                pool.fee_protocol_share,
+                referrer_fee_rate,
+                input_divisibility,
+            );
+
+        Self {
+            pool_address: pool.pool_address,
+            input_address,
+            output_address,
+            swap_type,
+            output: dec!(0),
+            output_divisibility,
+            input: dec!(0),
+            input_divisibility,
+            remainder: input_amount_net,
+            remainder_fee_lp: input_fee_lp,
+            liquidity: pool.active_liquidity,
+            active_tick: pool.active_tick,
+            price_sqrt: pool.price_sqrt,
+            price_sqrt_sell_cache: None,
+            input_fee_rate: pool.input_fee_rate,
+            fee_protocol_share: pool.fee_protocol_share,
+            fee_lp_share: dec!(1) - pool.fee_protocol_share,
+            input_share: dec!(1) - applied_input_fee_rate - referrer_fee_rate,
+            input_fee_share: applied_input_fee_rate,
+            fee_lp_input: dec!(0),
+            fee_protocol_input: dec!(0),
+            fee_protocol_max: input_fee_protocol,
+            fee_referrer_input: dec!(0),
+            fee_referrer_max: input_fee_referrer,
+            fee_on_output,
+            fee_lp_output: dec!(0),
+            fee_protocol_output: dec!(0),
+            global_input_fee_lp,
+            global_output_fee_lp,
+            global_seconds: pool.seconds_global,
+            crossed_ticks: vec![],
+            max_ticks_crossed,
+            partial_fill: false,
+        }
+    }
+
+    /// Calculates the new price based on the provided liquidity for the remaining input.
+    ///
+    /// # Arguments
+    /// * `liquidity` - The updated liquidity value.
+    ///
+    /// # Returns
+    /// * `PreciseDecimal` - The newly calculated price.
+    pub fn new_price(&self, liquidity: PreciseDecimal) -> PreciseDecimal {
+        new_price(
+            self.swap_type,
+            liquidity,
+            self.price_sqrt,
+            self.remainder,
+            self.input_divisibility,
+        )
+    }
+
+    /// Checks if the liquidity is zero.
+    ///
+    /// This method is used to determine if the pool has any active liquidity.
+    /// A result of `true` indicates that the current sub-pool has no liquidity and there is a liquidity gap.
+    ///
+    /// # Returns
+    /// * `bool` - True if liquidity is zero, otherwise false.
+    pub fn liquidity_is_zero(&self) -> bool {
+        self.liquidity == PreciseDecimal::ZERO
+    }
+
+    /// Checks if the remainder is zero or negative.
+    ///
+    /// This method is used to verify if all inputs have been fully utilized in the swap.
+    /// A non-positive remainder indicates that there are no leftover inputs.
+    ///
+    /// # Returns
+    /// * `bool` - True if remainder is zero or negative, otherwise false.
+    pub fn remainder_is_empty(&self) -> bool {
+        self.remainder <= Decimal::ZERO
+    }
+
+    /// Checks whether the swap has to stop because it already crossed `max_ticks_crossed` ticks.
+    ///
+    /// If the limit is reached the swap is marked as partially filled, the remainder is refunded.
+    ///
+    /// # Returns
+    /// * `bool` - True if no further tick may be crossed, otherwise false.
+    pub fn stop_at_tick_limit(&mut self) -> bool {
+        let limit_reached = self.max_ticks_crossed.is_some_and(|max_ticks_crossed| {
+            self.crossed_ticks.len() >= max_ticks_crossed as usize
+        });
+        self.partial_fill |= limit_reached;
+        limit_reached
+    }
+
+    /// Determines if the new price does not reach the specified tick price.
+    ///
+    /// This method is essential for deciding whether a partial or full step swap should be executed,
+    /// based on the direction of the swap and the new price relative to the tick price.
+    ///
+    /// # Arguments
+    /// * `tick_price_sqrt` - The square root of the tick's price.
+    /// * `price_new_sqrt` - The square root of the new price.
+    ///
+    /// # Returns
+    /// * `bool` - True if the new price does not reach the tick price, otherwise false.
+    pub fn not_reaching_tick(
+        &self,
+        tick_price_sqrt: PreciseDecimal,
+        price_new_sqrt: PreciseDecimal,
+    ) -> bool {
+        match self.swap_type {
+            SwapType::BuyX => price_new_sqrt < tick_price_sqrt,
+            SwapType::SellX => tick_price_sqrt < price_new_sqrt,
+        }
+    }
+
+    /// Adjusts the liquidity and updates the active tick index during a tick crossing.
+    ///
+    /// This method is critical for maintaining accurate state when the price crosses a tick boundary.
+    /// It updates the liquidity and the active tick index, ensuring the pool's state is consistent.
+    ///
+    /// # Arguments
+    /// * `crossed_tick` - The tick that has been crossed.
+    /// * `next_active_tick_index` - The index of the next active tick.
+    pub fn cross_tick(&mut self, crossed_tick: &mut Tick, next_active_tick_index: i32) {
+        self.adjust_liquidity(crossed_tick.delta_liquidity);
+        self.active_tick = Some(next_active_tick_index);
+        let (global_x_fee_lp, global_y_fee_lp) = match self.swap_type {
+            SwapType::BuyX => (self.global_output_fee_lp, self.global_input_fee_lp),
+            SwapType::SellX => (self.global_input_fee_lp, self.global_output_fee_lp),
+        };
+        self.crossed_ticks.push(crossed_tick.update_outside_values(
+            global_x_fee_lp,
+            global_y_fee_lp,
+            self.global_seconds,
+        ));
+    }
+
+    /// Executes a partial step in the swap process when the new price does not reach the next tick.
+    ///
+    /// This method is used when the price movement is insufficient to reach the next tick.
+    /// It updates the output based on the new price and adjusts the remainder and fees accordingly.
+    ///
+    /// # Arguments
+    /// * `price_new_sqrt` - The square root of the new price.
+    pub fn partial_step_swap(&mut self, price_new_sqrt: PreciseDecimal) {
+        let output = output_step(
+            self.swap_type,
+            self.liquidity,
+            self.price_sqrt,
+            price_new_sqrt,
+            self.output_divisibility,
+        );
+        self.output += output;
+        self.accrue_output_fee(output);
+        self.input += self.remainder;
+        self.remainder = dec!(0);
+
+        /*
+        remainder_fee_lp has the full remainder, meaning there are no rounding errors
+        and the partial swap simply gets the remaining fees
+         */
+        self.global_input_fee_lp += self.remainder_fee_lp / self.liquidity;
+        self.fee_lp_input += self.remainder_fee_lp;
+        self.remainder_fee_lp = dec!(0);
+
+        self.price_sqrt = price_new_sqrt;
+    }
+
+    /// Takes protocol fees from the liquidity pool's fee reserves.
+    ///
+    /// This method is invoked to allocate the maximum allowable protocol fees or
+    /// calculate partial fees in case of a swap with a remainder.
+    pub fn take_protocol_fees(&mut self) {
+        if self.remainder_is_empty() {
+            self.fee_protocol_input = self.fee_protocol_max;
+            return;
+        }
+
+        let partial_protocol_fee = (PreciseDecimal::from(self.fee_lp_input) / self.fee_lp_share
+            - self.fee_lp_input)
+            .ceil_to(self.input_divisibility);
+        // Ensure that the calculated and ceiled protocol fees do not exceed the predefined maximum.
+        self.fee_protocol_input = min(self.fee_protocol_max, partial_protocol_fee);
+    }
+
+    /// Takes the referrer fees from the input.
+    ///
+    /// The full referrer fee is taken if the whole input has been swapped. Otherwise only the share of the
+    /// referrer fee corresponding to the swapped share of the net input is taken and the rest is refunded.
+    pub fn take_referrer_fees(&mut self) {
+        if self.remainder_is_empty() {
+            self.fee_referrer_input = self.fee_referrer_max;
+            return;
+        }
+
+        let partial_referrer_fee = (PreciseDecimal::from(self.fee_referrer_max) * self.input
+            / (self.input + self.remainder))
+            .ceil_to(self.input_divisibility);
+        // Ensure that the calculated and ceiled referrer fees do not exceed the predefined maximum.
+        self.fee_referrer_input = min(self.fee_referrer_max, partial_referrer_fee);
+    }
+
+    /// Accrues the liquidity provider share of the output fee of a swap step to the active liquidity.
+    ///
+    /// Only applies if the fee is taken from the output. The fee is rounded down per step, the rounding
+    /// difference is distributed in `take_output_fees`.
+    ///
+    /// # Arguments
+    /// * `step_output` - The output of the swap step before fees.
+    fn accrue_output_fee(&mut self, step_output: Decimal) {
+        if !self.fee_on_output {
+            return;
+        }
+        let fee_lp_output_delta =
+            (PreciseDecimal::from(step_output) * self.input_fee_rate * self.fee_lp_share)
+                .floor_to(self.output_divisibility);
+        if fee_lp_output_delta == Decimal::ZERO {
+            return;
+        }
+        self.global_output_fee_lp += fee_lp_output_delta / self.liquidity;
+        self.fee_lp_output += fee_lp_output_delta;
+    }
+
+    /// Takes the fees from the output after all ticks have been processed.
+    ///
+    /// Only applies if the fee is taken from the output. The total fee is rounded up in favour of the pool and
+    /// the protocol fee is rounded down in favour of the liquidity providers. The rounding difference to the
+    /// fees accrued per step is given to the active liquidity or to the protocol if there is no active liquidity.
+    /// Afterwards `output` is the amount returned to the trader.
+    pub fn take_output_fees(&mut self) {
+        if !self.fee_on_output {
+            return;
+        }
+        let fee_output_total = (PreciseDecimal::from(self.output) * self.input_fee_rate)
+            .ceil_to(self.output_divisibility);
+        self.fee_protocol_output = (PreciseDecimal::from(fee_output_total)
+            * self.fee_protocol_share)
+            .floor_to(self.output_divisibility);
+        let fee_lp_output_rest = fee_output_total - self.fee_protocol_output - self.fee_lp_output;
+        if fee_lp_output_rest > Decimal::ZERO {
+            if self.liquidity_is_zero() {
+                self.fee_protocol_output += fee_lp_output_rest;
+            } else {
+                self.global_output_fee_lp += fee_lp_output_rest / self.liquidity;
+                self.fee_lp_output += fee_lp_output_rest;
+            }
+        }
+        self.output -= fee_output_total;
+    }
+
+    /// Summarizes the swap after all ticks have been processed and the protocol fees have been taken.
+    ///
+    /// # Arguments
+    /// * `input_gross_amount` - The input amount including fees.
+    ///
+    /// # Returns
+    /// * `SwapQuote` - The amounts, the price afterwards and the number of crossed ticks.
+    pub fn quote(&self, input_gross_amount: Decimal) -> SwapQuote {
+        SwapQuote {
+            output_amount: self.output,
+            remainder_amount: input_gross_amount
+                - self.input
+                - self.fee_lp_input
+                - self.fee_protocol_input
+                - self.fee_referrer_input,
+            input_fee_lp: self.fee_lp_input,
+            input_fee_protocol: self.fee_protocol_input,
+            input_fee_referrer: self.fee_referrer_input,
+            output_fee_lp: self.fee_lp_output,
+            output_fee_protocol: self.fee_protocol_output,
+            price_sqrt: self.price_sqrt,
+            active_tick: self.active_tick,
+            ticks_crossed: self.crossed_ticks.len() as u32,
+            partial_fill: self.partial_fill,
+        }
+    }
+
+    /// Executes a full step in the swap process when the new price reaches the next tick.
+    ///
+    /// This method is used when the price movement is sufficient to reach or surpass the next tick.
+    /// It updates the output and input based on the tick's price and adjusts the remainder and fees accordingly.
+    ///
+    /// # Arguments
+    /// * `tick` - The tick that defines the next price level.
+    pub fn full_step_swap(&mut self, tick: &Tick) {
+        self.full_step_swap_to_price(tick.price_sqrt);
+    }
+
+    /// Executes a full step in the swap process up to the given price.
+    ///
+    /// Besides reaching a tick, this is used to fill the active range right up to a tick which
+    /// can not be crossed anymore because the swap reached its tick crossing limit.
+    ///
+    /// # Arguments
+    /// * `price_target_sqrt` - The square root of the price to move to.
+    pub fn full_step_swap_to_price(&mut self, price_target_sqrt: PreciseDecimal) {
+        let output = output_step(
+            self.swap_type,
+            self.liquidity,
+            self.price_sqrt,
+            price_target_sqrt,
+            self.output_divisibility,
+        );
+        self.output += output;
+        self.accrue_output_fee(output);
+        let step_input = input_step(
+            self.swap_type,
+            self.liquidity,
+            self.price_sqrt,
+            price_target_sqrt,
+            self.input_divisibility,
+        );
+        self.input += step_input;
+        self.remainder -= step_input;
+
+        let total_fee_step =
+            PreciseDecimal::from(step_input) * self.input_fee_share / self.input_share;
+        let fee_lp_input_delta =
+            (total_fee_step * self.fee_lp_share).floor_to(self.input_divisibility);
+        self.global_input_fee_lp += fee_lp_input_delta / self.liquidity;
+        self.fee_lp_input += fee_lp_input_delta;
+        self.remainder_fee_lp -= fee_lp_input_delta;
+
+        self.price_sqrt = price_target_sqrt;
+    }
+
+    /// Adjusts the pool's liquidity by adding or subtracting the specified amount.
+    ///
+    /// This method is essential for correctly updating the pool's liquidity during swaps or liquidity changes.
+    /// It ensures that the liquidity is adjusted accurately, reflecting the actual state of the pool.
+    ///
+    /// # Arguments
+    /// * `delta_liquidity` - The amount by which to adjust the liquidity.
+    pub fn adjust_liquidity(&mut self, delta_liquidity: PreciseDecimal) {
+        self.liquidity = new_liquidity(self.swap_type, self.liquidity, delta_liquidity);
+    }
+
+    /// Determines the type of swap step (partial or full) based on whether the new price reaches the next tick.
+    ///
+    /// # Arguments
+    /// * `tick` - The tick that defines the next price level.
+    /// * `price_new_sqrt` - The square root of the new price.
+    ///
+    /// # Returns
+    /// * `Option<StepControl>` - Control flow signal for iterator, `Some(StepControl::Break)` if partial step is taken, otherwise `None`.
+    pub fn step_swap(
+        &mut self,
+        tick: &Tick,
+        price_new_sqrt: PreciseDecimal,
+    ) -> Option<StepControl> {
+        if self.not_reaching_tick(tick.price_sqrt, price_new_sqrt) {
+            self.partial_step_swap(price_new_sqrt);
+            return Some(StepControl::Break);
+        }
+        self.full_step_swap(tick);
+        None
+    }
+}
+
+/// Calculates the fees a position earned between two fee checkpoints.
+///
+/// # Arguments
+/// * `liquidity` - The liquidity of the position.
+/// * `fee_checkpoint` - The fee checkpoint stored in the position.
+/// * `new_fee_checkpoint` - The current fee checkpoint of the position's range, see `value_in_range`.
+/// * `divisibility` - The divisibility of the fee token, the result is rounded down to it.
+///
+/// # Returns
+/// * `Decimal` - The earned fees.
+pub fn earned_fees(
+    liquidity: PreciseDecimal,
+    fee_checkpoint: PreciseDecimal,
+    new_fee_checkpoint: PreciseDecimal,
+    divisibility: u8,
+) -> Decimal {
+    ((new_fee_checkpoint - fee_checkpoint) * liquidity).floor_to(divisibility)
+}
+
+/// Calculates the effective value within a specified range, adjusting for values outside the range.
+///
+/// This function is essential for determining the net contribution of a variable (like fees or seconds) within a specified range.
+/// It subtracts the contributions outside the specified bounds to provide a precise measure of the variable's effective value within the range.
+/// This is particularly useful in scenarios where only the contributions within a certain price or time range are relevant,
+/// such as calculating fees accrued within specific price boundaries in a liquidity pool.
+///
+/// # Type Parameters
+/// * `T`: A type that supports addition, subtraction, copying, and display. Typically used for numerical calculations.
+///
+/// # Arguments
+/// * `value_global`: The total value from which contributions outside the specified range will be subtracted.
+/// * `value_outside_left`: The value outside the left boundary of the range.
+/// * `value_outside_right`: The value outside the right boundary of the range.
+/// * `active_tick`: An optional tick index representing the current position. If `None`, it defaults to the minimum possible tick.
+/// * `left_bound`: The left boundary of the range.
+/// * `right_bound`: The right boundary of the range.
+///
+/// # Returns
+/// * `T`: The net value within the specified range after adjusting for external contributions.
+pub fn value_in_range<T: Add<Output = T> + Sub<Output = T> + Copy + Display>(
+    value_global: T,
+    value_outside_left: T,
+    value_outside_right: T,
+    active_tick: Option<i32>,
+    left_bound: i32,
+    right_bound: i32,
+) -> T {
+    let active_tick = active_tick.unwrap_or(i32::MIN);
+
+    let value_below_left = if active_tick >= left_bound {
+        value_outside_left
+    } else {
+        value_global - value_outside_left
+    };
+
+    let value_above_right = if active_tick >= right_bound {
+        value_global - value_outside_right
+    } else {
+        value_outside_right
+    };
+
+    value_global - value_below_left - value_above_right
+}
+
+/// Executes a buy step in the swap process.
+///
+/// This function is called during a swap when the swap type is buying. It adjusts the state based on the current tick and the liquidity available.
+/// It determines the new price, crosses the tick if necessary, and decides whether to continue or break the iteration based on the remainder.
+///
+/// # Arguments
+/// * `state` - The mutable reference to the current swap state.
+/// * `tick` - The mutable reference to the current tick data.
+/// * `tick_index` - The index of the current tick.
+///
+/// # Returns
+/// * `StepControl` - An enum indicating whether to continue or break the iteration.
+pub fn buy_step(state: &mut SwapState, tick: &mut Tick, tick_index: i32) -> StepControl {
+    if state.liquidity_is_zero() {
+        if state.stop_at_tick_limit() {
+            return StepControl::Break;
+        }
+        state.price_sqrt = tick.price_sqrt;
+        state.cross_tick(tick, tick_index);
+        return StepControl::Continue;
+    }
+
+    let price_new_sqrt = state.new_price(state.liquidity);
+    // Stop right below a tick which can not be crossed anymore, so the price stays inside the active range.
+    if !state.not_reaching_tick(tick.price_sqrt, price_new_sqrt) && state.stop_at_tick_limit() {
+        let price_limit_sqrt = tick.price_sqrt - PreciseDecimal::ATTO;
+        if state.price_sqrt < price_limit_sqrt {
+            state.full_step_swap_to_price(price_limit_sqrt);
+        }
+        return StepControl::Break;
+    }
+    if let Some(control) = state.step_swap(tick, price_new_sqrt) {
+        return control;
+    }
+
+    state.cross_tick(tick, tick_index);
+
+    if state.remainder_is_empty() {
+        return StepControl::Break;
+    }
+
+    return StepControl::Continue;
+}
+
+/// Executes a sell step in the swap process.
+///
+/// This function is called during a swap when the swap type is selling. It adjusts the state based on the current and next ticks, and the liquidity available.
+/// It determines the new price, potentially caches it for future use, and decides whether to continue or break the iteration based on the remainder and next tick index.
+///
+/// # Arguments
+/// * `state` - The mutable reference to the current swap state.
+/// * `tick` - The mutable reference to the current tick data.
+/// * `next_tick_index` - The optional index of the next tick.
+///
+/// # Returns
+/// * `StepControl` - An enum indicating whether to continue or break the iteration.
+pub fn sell_step(
+    state: &mut SwapState,
+    tick: &mut Tick,
+    next_tick_index: Option<i32>,
+) -> StepControl {
+    if state.liquidity == pdec!(0) {
+        state.price_sqrt = tick.price_sqrt;
+    }
+
+    if state.price_sqrt != tick.price_sqrt {
+        let price_new_sqrt = state
+            .price_sqrt_sell_cache
+            .take()
+            .unwrap_or_else(|| state.new_price(state.liquidity));
+        if let Some(control) = state.step_swap(tick, price_new_sqrt) {
+            return control;
+        }
+    }
+
+    // crossing tick if there is liquidity and the remainder is enough to move the price
+    if state.remainder_is_empty() || next_tick_index.is_none() {
+        return StepControl::Break;
+    }
+
+    let next_liquidity = new_liquidity(state.swap_type, state.liquidity, tick.delta_liquidity);
+    if next_liquidity.is_zero() {
+        if state.stop_at_tick_limit() {
+            return StepControl::Break;
+        }
+        state.cross_tick(tick, next_tick_index.unwrap());
+        return StepControl::Continue;
+    }
+
+    let price_new_sqrt = state.new_price(next_liquidity);
+    if price_new_sqrt == state.price_sqrt || state.stop_at_tick_limit() {
+        return StepControl::Break;
+    }
+
+    state.cross_tick(tick, next_tick_index.unwrap());
+    state.price_sqrt_sell_cache = Some(price_new_sqrt);
+
+    StepControl::Continue
+}
+
+#[cfg(test)]
+mod test {
+    use radix_common::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn test_zap_swap_amount_outside_range() {
+        let (left, right) = (tick_to_price_sqrt(-100), tick_to_price_sqrt(100));
+        let below = tick_to_price_sqrt(-200);
+        let above = tick_to_price_sqrt(200);
+        let liquidity = pdec!(1000);
+        assert_eq!(
+            zap_swap_amount(
+                SwapType::SellX,
+                dec!(10),
+                dec!(0),
+                dec!(0),
+                FeeCollectionMode::Input,
+                liquidity,
+                below,
+                left,
+                right,
+                18
+            ),
+            dec!(0)
+        );
+        assert_eq!(
+            zap_swap_amount(
+                SwapType::SellX,
+                dec!(10),
+                dec!(0),
+                dec!(0),
+                FeeCollectionMode::Input,
+                liquidity,
+                above,
+                left,
+                right,
+                18
+            ),
+            dec!(10)
+        );
+        assert_eq!(
+            zap_swap_amount(
+                SwapType::BuyX,
+                dec!(10),
+                dec!(0),
+                dec!(0),
+                FeeCollectionMode::Input,
+                liquidity,
+                above,
+                left,
+                right,
+                18
+            ),
+            dec!(0)
+        );
+        assert_eq!(
+            zap_swap_amount(
+                SwapType::BuyX,
+                dec!(10),
+                dec!(0),
+                dec!(0),
+                FeeCollectionMode::Input,
+                liquidity,
+                below,
+                left,
+                right,
+                18
+            ),
+            dec!(10)
+        );
+    }
+
+    #[test]
+    fn test_zap_swap_amount_symmetric_range() {
+        // For a symmetric range around price 1 and negligible price impact roughly half needs to be swapped.
+        let (left, right) = (tick_to_price_sqrt(-100), tick_to_price_sqrt(100));
+        let liquidity = pdec!(1000000000);
+        let amount = zap_swap_amount(
+            SwapType::SellX,
+            dec!(10),
+            dec!(0),
+            dec!(0),
+            FeeCollectionMode::Input,
+            liquidity,
+            pdec!(1),
+            left,
+            right,
+            18,
+        );
+        assert!(dec!("4.99") < amount && amount < dec!("5.01"), "{}", amount);
+    }
+
+    #[test]
+    fn test_zap_swap_amount_already_balanced() {
+        let (left, right) = (tick_to_price_sqrt(-100), tick_to_price_sqrt(100));
+        let amount = zap_swap_amount(
+            SwapType::SellX,
+            dec!(5),
+            dec!(5),
+            dec!(0),
+            FeeCollectionMode::Input,
+            pdec!(1000000000),
+            pdec!(1),
+            left,
+            right,
+            18,
+        );
+        assert!(amount < dec!("0.0001"), "{}", amount);
+    }
+
+    #[test]
+    #[test]
+    fn test_zap_swap_amount_fee_collection_mode() {
+        // With a significant price impact the fee mode changes the swap amount, the resulting
+        // tokens need to match the ratio of the position after the output fee is deducted.
+        let (left, right) = (tick_to_price_sqrt(-5000), tick_to_price_sqrt(5000));
+        let liquidity = pdec!(100);
+        let input_fee_rate = dec!("0.1");
+        let input_amount = zap_swap_amount(
+            SwapType::SellX,
+            dec!(10),
+            dec!(0),
+            input_fee_rate,
+            FeeCollectionMode::Input,
+            liquidity,
+            pdec!(1),
+            left,
+            right,
+            18,
+        );
+        let output_amount = zap_swap_amount(
+            SwapType::SellX,
+            dec!(10),
+            dec!(0),
+            input_fee_rate,
+            FeeCollectionMode::Output,
+            liquidity,
+            pdec!(1),
+            left,
+            right,
+            18,
+        );
+        assert_ne!(input_amount, output_amount);
+
+        let swap_amount = PreciseDecimal::from(output_amount);
+        let price_new_sqrt = liquidity / (liquidity + swap_amount);
+        let y_output = liquidity * (pdec!(1) - price_new_sqrt) * pdec!("0.9");
+        let x_value = (pdec!(10) - swap_amount) * (price_new_sqrt - left);
+        let y_value = y_output * (pdec!(1) / price_new_sqrt - pdec!(1) / right);
+        assert!(
+            (x_value - y_value).checked_abs().unwrap() < pdec!("0.000001"),
+            "{} {}",
+            x_value,
+            y_value
+        );
+    }
+
+    fn test_rebalance_swap_type() {
+        let (left, right) = (tick_to_price_sqrt(-100), tick_to_price_sqrt(100));
+        assert!(matches!(
+            rebalance_swap_type(dec!(10), dec!(1), pdec!(1), left, right),
+            SwapType::SellX
+        ));
+        assert!(matches!(
+            rebalance_swap_type(dec!(1), dec!(10), pdec!(1), left, right),
+            SwapType::BuyX
+        ));
+        assert!(matches!(
+            rebalance_swap_type(dec!(10), dec!(0), tick_to_price_sqrt(200), left, right),
+            SwapType::SellX
+        ));
+        assert!(matches!(
+            rebalance_swap_type(dec!(0), dec!(10), tick_to_price_sqrt(-200), left, right),
+            SwapType::BuyX
+        ));
+    }
+
+    #[test]
+    fn test_shape_weights_sum_to_one() {
+        for shape in [
+            LiquidityShape::Uniform,
+            LiquidityShape::Normal,
+            LiquidityShape::Curve,
+            LiquidityShape::BidAsk,
+            LiquidityShape::Exponential,
+        ] {
+            for num_bins in [1, 2, 7, MAX_DISTRIBUTION_BINS] {
+                let weights = shape_weights(shape, num_bins);
+                assert_eq!(weights.len(), num_bins as usize);
+                let total = weights.iter().fold(dec!(0), |sum, weight| sum + *weight);
+                assert!(dec!(1) - total < dec!("0.000000000000001"), "{:?}", shape);
+            }
+        }
+    }
+
+    #[test]
+    fn test_shape_weights_symmetric() {
+        for shape in [
+            LiquidityShape::Normal,
+            LiquidityShape::Curve,
+            LiquidityShape::BidAsk,
+            LiquidityShape::Exponential,
+        ] {
+            let weights = shape_weights(shape, 6);
+            let reversed: Vec<Decimal> = weights.iter().rev().cloned().collect();
+            assert_eq!(weights, reversed, "{:?}", shape);
+        }
+    }
+
+    #[test]
+    fn test_shape_weights_ratios() {
+        let uniform = shape_weights(LiquidityShape::Uniform, 4);
+        assert_eq!(uniform, vec![dec!("0.25"); 4]);
+
+        let normal = shape_weights(LiquidityShape::Normal, 5);
+        assert_eq!(normal[2] / normal[0], dec!(6));
+        assert_eq!(normal[1] / normal[0], dec!(4));
+
+        let curve = shape_weights(LiquidityShape::Curve, 5);
+        assert_eq!(curve[2] / curve[0], dec!(3));
+        assert_eq!(curve[1] / curve[0], dec!(2));
+
+        let bid_ask = shape_weights(LiquidityShape::BidAsk, 5);
+        assert_eq!(bid_ask[0] / bid_ask[2], dec!(3));
+        assert_eq!(bid_ask[1] / bid_ask[2], dec!(2));
+
+        let exponential = shape_weights(LiquidityShape::Exponential, 5);
+        assert_eq!(exponential[2] / exponential[1], dec!(2));
+        assert_eq!(exponential[1] / exponential[0], dec!(2));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_shape_weights_too_many_bins() {
+        shape_weights(LiquidityShape::Uniform, MAX_DISTRIBUTION_BINS + 1);
+    }
+
+    #[test]
+    fn test_new_price() {
+        assert_eq!(
+            new_price(SwapType::SellX, pdec!(10), pdec!(1), dec!(0), 18),
+            pdec!(1)
+        );
+    }
+
+    #[test]
+    fn test_input_amount_net_with_referrer() {
+        assert_eq!(
+            input_amount_net(dec!(100), dec!("0.01"), dec!("0.25"), dec!("0.005"), 18),
+            (dec!("98.5"), dec!("0.75"), dec!("0.25"), dec!("0.5"))
+        );
+        assert_eq!(
+            input_amount_net(dec!(100), dec!("0.01"), dec!("0.25"), dec!(0), 18),
+            (dec!(99), dec!("0.75"), dec!("0.25"), dec!(0))
+        );
+    }
+
+    #[test]
+    fn test_fee_collection_mode_fee_on_output() {
+        assert!(!FeeCollectionMode::Input.fee_on_output(SwapType::SellX));
+        assert!(!FeeCollectionMode::Input.fee_on_output(SwapType::BuyX));
+        assert!(FeeCollectionMode::Output.fee_on_output(SwapType::SellX));
+        assert!(FeeCollectionMode::Output.fee_on_output(SwapType::BuyX));
+        assert!(!FeeCollectionMode::X.fee_on_output(SwapType::SellX));
+        assert!(FeeCollectionMode::X.fee_on_output(SwapType::BuyX));
+        assert!(FeeCollectionMode::Y.fee_on_output(SwapType::SellX));
+        assert!(!FeeCollectionMode::Y.fee_on_output(SwapType::BuyX));
+    }
+
+    #[test]
+    fn test_input_amount_net_referrer_rounding() {
+        // The referrer fee is rounded down, the input fee is rounded up.
+        assert_eq!(
+            input_amount_net(dec!("1.99"), dec!("0.01"), dec!("0.25"), dec!("0.005"), 2),
+            (dec!("1.97"), dec!("0.02"), dec!(0), dec!(0))
+        );
+        assert_eq!(
+            input_amount_net(dec!("3.99"), dec!("0.01"), dec!("0.25"), dec!("0.005"), 2),
+            (dec!("3.94"), dec!("0.03"), dec!("0.01"), dec!("0.01"))
+        );
+    }
+
+    fn swap_pool_state(fee_collection_mode: FeeCollectionMode) -> SwapPoolState {
+        SwapPoolState {
+            pool_address: ComponentAddress::new_or_panic([192; NodeId::LENGTH]),
+            x_address: ResourceAddress::new_or_panic([93; NodeId::LENGTH]),
+            y_address: ResourceAddress::new_or_panic([94; NodeId::LENGTH]),
+            x_divisibility: 18,
+            y_divisibility: 6,
+            price_sqrt: pdec!(1),
+            active_tick: Some(0),
+            active_liquidity: pdec!(10),
+            x_lp_fee: pdec!(1),
+            y_lp_fee: pdec!(2),
+            input_fee_rate: dec!("0.01"),
+            fee_protocol_share: dec!("0.25"),
+            fee_collection_mode,
+            seconds_global: 42,
+        }
+    }
+
+    #[test]
+    fn test_swap_state_new_fee_on_input() {
+        let pool = swap_pool_state(FeeCollectionMode::Input);
+        let state = SwapState::new(&pool, SwapType::SellX, dec!(100), Some(3), dec!("0.005"));
+
+        assert_eq!(
+            (state.input_address, state.output_address),
+            (pool.x_address, pool.y_address)
+        );
+        assert_eq!(
+            (state.input_divisibility, state.output_divisibility),
+            (18, 6)
+        );
+        assert_eq!(
+            (state.global_input_fee_lp, state.global_output_fee_lp),
+            (pdec!(1), pdec!(2))
+        );
+        assert_eq!(
+            (
+                state.remainder,
+                state.remainder_fee_lp,
+                state.fee_protocol_max,
+                state.fee_referrer_max
+            ),
+            (dec!("98.5"), dec!("0.75"), dec!("0.25"), dec!("0.5"))
+        );
+        assert_eq!(state.input_share, dec!("0.985"));
+        assert_eq!(state.input_fee_share, dec!("0.01"));
+        assert_eq!(state.fee_lp_share, dec!("0.75"));
+        assert_eq!(state.global_seconds, 42);
+        assert_eq!(state.max_ticks_crossed, Some(3));
+        assert!(!state.fee_on_output);
+    }
+
+    #[test]
+    fn test_swap_state_new_fee_on_output() {
+        let pool = swap_pool_state(FeeCollectionMode::X);
+        let state = SwapState::new(&pool, SwapType::BuyX, dec!(100), None, dec!("0.005"));
+
+        assert_eq!(
+            (state.input_address, state.output_address),
+            (pool.y_address, pool.x_address)
+        );
+        assert_eq!(
+            (state.global_input_fee_lp, state.global_output_fee_lp),
+            (pdec!(2), pdec!(1))
+        );
+        assert_eq!(
+            (
+                state.remainder,
+                state.remainder_fee_lp,
+                state.fee_referrer_max
+            ),
+            (dec!("99.5"), dec!(0), dec!("0.5"))
+        );
+        assert_eq!(state.input_share, dec!("0.995"));
+        assert_eq!(state.input_fee_share, dec!(0));
+        assert_eq!(state.input_fee_rate, dec!("0.01"));
+        assert!(state.fee_on_output);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        max_liquidity_per_tick, number_of_ticks, price_range_to_ticks, price_sqrt_to_tick,
+        price_to_tick, tick_to_price_sqrt, TickRounding,
+    };
+    use crate::constants::*;
+    use pretty_assertions::assert_eq;
+    use radix_common::prelude::*;
+    use test_case::test_case;
+
+    #[test_case(MAX_TICK, 3)]
+    #[test_case(MAX_TICK - 1, 3)]
+    #[test_case(MAX_TICK / 2 + 1, 3)]
+    #[test_case(MAX_TICK / 2, 5)]
+    #[test_case(1, MAX_TICK * 2 + 1)]
+    fn test_number_of_ticks(spacing: i32, result: i32) {
+        assert_eq!(number_of_ticks(spacing as u32), result as u32)
+    }
+
+    #[test_case(MAX_TICK, MAX_LIQUIDITY / 3)] // 3 ticks
+    #[test_case(MAX_TICK - 1, MAX_LIQUIDITY / 3)] // 3 ticks
+    #[test_case(MAX_TICK / 2, MAX_LIQUIDITY / 5)] // 5 ticks
+    #[test_case(1, MAX_LIQUIDITY / (2 * MAX_TICK + 1))] // 5 ticks
+    fn test_max_liquidity_per_tick(spacing: i32, result: PreciseDecimal) {
+        assert_eq!(max_liquidity_per_tick(spacing as u32), result)
+    }
+
+    #[test_case(MIN_TICK)]
+    #[test_case(MIN_TICK + 1)]
+    #[test_case(-10000)]
+    #[test_case(-1)]
+    #[test_case(0)]
+    #[test_case(1)]
+    #[test_case(6931)]
+    #[test_case(10000)]
+    #[test_case(MAX_TICK - 1)]
+    #[test_case(MAX_TICK)]
+    fn test_price_sqrt_to_tick_inverse(tick: i32) {
+        assert_eq!(price_sqrt_to_tick(tick_to_price_sqrt(tick)), tick)
+    }
+
+    #[test_case(-10000)]
+    #[test_case(0)]
+    #[test_case(10000)]
+    #[test_case(MAX_TICK - 1)]
+    fn test_price_sqrt_to_tick_between_ticks(tick: i32) {
+        let price_sqrt = (tick_to_price_sqrt(tick) + tick_to_price_sqrt(tick + 1)) / 2;
+        assert_eq!(price_sqrt_to_tick(price_sqrt), tick)
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_price_sqrt_to_tick_below_min_tick() {
+        price_sqrt_to_tick(tick_to_price_sqrt(MIN_TICK) * pdec!("0.99"));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_price_sqrt_to_tick_above_max_tick() {
+        price_sqrt_to_tick(tick_to_price_sqrt(MAX_TICK) * pdec!("1.01"));
+    }
+
+    #[test_case(dec!(1), 0)]
+    #[test_case(dec!(2), 6931)]
+    #[test_case(dec!("0.5"), -6932)]
+    fn test_price_to_tick(price: Decimal, tick: i32) {
+        assert_eq!(price_to_tick(price), tick)
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_price_to_tick_zero() {
+        price_to_tick(dec!(0));
+    }
+
+    // Price 0.5 is at tick -6931.8 and price 2 at tick 6931.8.
+    #[test_case(TickRounding::Inward, -6930, 6930)]
+    #[test_case(TickRounding::Outward, -6940, 6940)]
+    #[test_case(TickRounding::Nearest, -6930, 6930)]
+    fn test_price_range_to_ticks(rounding: TickRounding, left_bound: i32, right_bound: i32) {
+        assert_eq!(
+            price_range_to_ticks(dec!("0.5"), dec!(2), 10, rounding),
+            (left_bound, right_bound)
+        )
+    }
+
+    #[test_case(TickRounding::Inward)]
+    #[test_case(TickRounding::Outward)]
+    #[test_case(TickRounding::Nearest)]
+    fn test_price_range_to_ticks_exact(rounding: TickRounding) {
+        assert_eq!(price_range_to_ticks(dec!(1), dec!(2), 1, rounding).0, 0)
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_price_range_to_ticks_empty_after_rounding() {
+        price_range_to_ticks(dec!("1.0001"), dec!("1.0002"), 10, TickRounding::Inward);
+    }
+
+    #[test]
+    fn test_tick_to_price_sqrt_powers() {
+        for (bit, power) in TICK_BASE_SQRT_POWERS.iter().enumerate() {
+            assert_eq!(*power, TICK_BASE_SQRT.checked_powi(1 << bit).unwrap());
+        }
+        for (bit, power) in TICK_BASE_SQRT_INVERSE_POWERS.iter().enumerate() {
+            assert_eq!(*power, TICK_BASE_SQRT.checked_powi(-(1 << bit)).unwrap());
+        }
+    }
+
+    #[test_case(0)]
+    #[test_case(1)]
+    #[test_case(-1)]
+    #[test_case(MIN_TICK)]
+    #[test_case(MAX_TICK)]
+    #[test_case(524287)]
+    #[test_case(-524287)]
+    #[test_case(524288)]
+    #[test_case(-524288)]
+    fn test_tick_to_price_sqrt_matches_powi(tick: i32) {
+        assert_eq!(
+            tick_to_price_sqrt(tick),
+            TICK_BASE_SQRT.checked_powi(tick.into()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_tick_to_price_sqrt_matches_powi_sampled() {
+        // A prime stride samples ticks with all kinds of bit patterns over the full range.
+        for tick in (MIN_TICK..=MAX_TICK).step_by(997) {
+            assert_eq!(
+                tick_to_price_sqrt(tick),
+                TICK_BASE_SQRT.checked_powi(tick.into()).unwrap(),
+                "Tick {}",
+                tick
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_tick_to_price_sqrt_outside_table() {
+        tick_to_price_sqrt(1 << 20);
+    }
+}
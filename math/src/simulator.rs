@@ -0,0 +1,624 @@
+use radix_common::prelude::*;
+use sbor::rust::collections::BTreeMap;
+use sbor::rust::ops::Bound;
+
+use crate::constants::*;
+use crate::pool_math::*;
+use crate::snapshot::{PoolSnapshot, TickSnapshotV1};
+use crate::tick::*;
+
+/// A liquidity position added to a `PoolSimulator`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SimulatedPosition {
+    pub left_bound: i32,
+    pub right_bound: i32,
+    pub liquidity: PreciseDecimal,
+    pub x_fee_checkpoint: PreciseDecimal,
+    pub y_fee_checkpoint: PreciseDecimal,
+}
+
+/// In-memory copy of a pool to simulate swaps and liquidity changes off-ledger.
+///
+/// The simulator is loaded from a `PoolSnapshot` and all pages of `snapshot_ticks`. It uses the same functions as the
+/// blueprint, hence executing the same operations in the same order yields identical results as on-ledger.
+/// Hooks are not executed and the protocol fee share is not synchronized with the registry.
+#[derive(Clone, Debug)]
+pub struct PoolSimulator {
+    pool_address: ComponentAddress,
+    x_address: ResourceAddress,
+    y_address: ResourceAddress,
+    x_divisibility: u8,
+    y_divisibility: u8,
+
+    tick_spacing: u32,
+    max_liquidity_per_tick: PreciseDecimal,
+
+    price_sqrt: PreciseDecimal,
+    active_tick: Option<i32>,
+    active_liquidity: PreciseDecimal,
+    ticks: BTreeMap<i32, Tick>,
+
+    input_fee_rate: Decimal,
+    fee_protocol_share: Decimal,
+    fee_collection_mode: FeeCollectionMode,
+
+    x_lp_fee: PreciseDecimal,
+    y_lp_fee: PreciseDecimal,
+
+    x_liquidity: Decimal,
+    y_liquidity: Decimal,
+    x_fees: Decimal,
+    y_fees: Decimal,
+    x_protocol_fee: Decimal,
+    y_protocol_fee: Decimal,
+
+    instantiated_at: u64,
+    seconds_global: u64,
+}
+
+impl PoolSimulator {
+    /// Loads a pool from its snapshot.
+    ///
+    /// # Arguments
+    /// * `snapshot` - The snapshot of the pool state.
+    /// * `ticks` - All ticks of the pool, e.g. the concatenated pages of `snapshot_ticks`.
+    /// * `now` - The current Unix timestamp in seconds, see `set_time`.
+    ///
+    /// # Returns
+    /// * `PoolSimulator` - The simulator holding a copy of the pool state.
+    ///
+    /// # Panics
+    /// If `now` is before the instantiation of the pool.
+    pub fn new(snapshot: PoolSnapshot, ticks: Vec<TickSnapshotV1>, now: u64) -> Self {
+        let PoolSnapshot::V1(snapshot) = snapshot;
+        let mut simulator = Self {
+            pool_address: snapshot.pool_address,
+            x_address: snapshot.x_address,
+            y_address: snapshot.y_address,
+            x_divisibility: snapshot.x_divisibility,
+            y_divisibility: snapshot.y_divisibility,
+            tick_spacing: snapshot.tick_spacing,
+            max_liquidity_per_tick: snapshot.max_liquidity_per_tick,
+            price_sqrt: snapshot.price_sqrt,
+            active_tick: snapshot.active_tick,
+            active_liquidity: snapshot.active_liquidity,
+            ticks: ticks
+                .into_iter()
+                .map(|tick| {
+                    (
+                        tick.index,
+                        Tick {
+                            index: tick.index,
+                            delta_liquidity: tick.delta_liquidity,
+                            total_liquidity: tick.total_liquidity,
+                            price_sqrt: tick.price_sqrt,
+                            x_fee_outside: tick.x_fee_outside,
+                            y_fee_outside: tick.y_fee_outside,
+                            seconds_outside: tick.seconds_outside,
+                        },
+                    )
+                })
+                .collect(),
+            input_fee_rate: snapshot.input_fee_rate,
+            fee_protocol_share: snapshot.fee_protocol_share,
+            fee_collection_mode: snapshot.fee_collection_mode,
+            x_lp_fee: snapshot.x_lp_fee,
+            y_lp_fee: snapshot.y_lp_fee,
+            x_liquidity: snapshot.x_liquidity,
+            y_liquidity: snapshot.y_liquidity,
+            x_fees: snapshot.x_fees,
+            y_fees: snapshot.y_fees,
+            x_protocol_fee: snapshot.x_protocol_fee,
+            y_protocol_fee: snapshot.y_protocol_fee,
+            instantiated_at: snapshot.instantiated_at,
+            seconds_global: 0,
+        };
+        simulator.set_time(now);
+        simulator
+    }
+
+    /// Sets the time used for the seconds spent inside of ticks by the following operations.
+    ///
+    /// # Arguments
+    /// * `now` - The current Unix timestamp in seconds, as returned by the ledger clock.
+    ///
+    /// # Panics
+    /// If `now` is before the instantiation of the pool.
+    pub fn set_time(&mut self, now: u64) {
+        assert!(
+            now >= self.instantiated_at,
+            "[Simulator]: The time is before the instantiation of the pool."
+        );
+        self.seconds_global = now - self.instantiated_at;
+    }
+
+    /// Simulates a swap and applies it to the simulated pool state.
+    ///
+    /// # Arguments
+    /// * `input_address` - The address of the token to be swapped.
+    /// * `input_amount` - The gross amount of the token to be swapped, including fees.
+    /// * `max_ticks_crossed` - The optional maximum number of ticks the swap is allowed to cross.
+    ///
+    /// # Returns
+    /// * `SwapQuote` - The same values as `quote_swap` of the pool would return before the swap.
+    ///
+    /// # Panics
+    /// - If `input_address` is neither the X nor the Y token address.
+    /// - If `input_amount` is not positive.
+    pub fn swap(
+        &mut self,
+        input_address: ResourceAddress,
+        input_amount: Decimal,
+        max_ticks_crossed: Option<u32>,
+    ) -> SwapQuote {
+        assert!(
+            input_address == self.x_address || input_address == self.y_address,
+            "[Simulator]: The input address is neither the X nor the Y token address."
+        );
+        let swap_type = match input_address == self.x_address {
+            true => SwapType::SellX,
+            false => SwapType::BuyX,
+        };
+        let mut state = SwapState::new(
+            &self.swap_pool_state(),
+            swap_type,
+            input_amount,
+            max_ticks_crossed,
+            Decimal::ZERO,
+        );
+        assert!(
+            !state.remainder_is_empty(),
+            "[Simulator]: The input amount needs to be positive."
+        );
+
+        match swap_type {
+            SwapType::BuyX => {
+                let mut tick_index = self.next_tick_above(self.active_tick);
+                while let Some(index) = tick_index {
+                    let tick = self.ticks.get_mut(&index).unwrap();
+                    if let StepControl::Break = buy_step(&mut state, tick, index) {
+                        break;
+                    }
+                    tick_index = self.next_tick_above(Some(index));
+                }
+            }
+            SwapType::SellX => {
+                let mut tick_index = self.next_tick_at_or_below(self.active_tick);
+                while let Some(index) = tick_index {
+                    let next_tick_index = self.next_tick_at_or_below(Some(index - 1));
+                    let tick = self.ticks.get_mut(&index).unwrap();
+                    if let StepControl::Break = sell_step(&mut state, tick, next_tick_index) {
+                        break;
+                    }
+                    tick_index = next_tick_index;
+                }
+            }
+        }
+        state.take_protocol_fees();
+        state.take_output_fees();
+
+        self.active_liquidity = state.liquidity;
+        self.active_tick = state.active_tick;
+        self.price_sqrt = state.price_sqrt;
+        let output_gross = state.output + state.fee_lp_output + state.fee_protocol_output;
+        match swap_type {
+            SwapType::BuyX => {
+                self.x_liquidity -= output_gross;
+                self.x_fees += state.fee_lp_output;
+                self.x_protocol_fee += state.fee_protocol_output;
+                self.x_lp_fee = state.global_output_fee_lp;
+                self.y_liquidity += state.input;
+                self.y_fees += state.fee_lp_input;
+                self.y_protocol_fee += state.fee_protocol_input;
+                self.y_lp_fee = state.global_input_fee_lp;
+            }
+            SwapType::SellX => {
+                self.y_liquidity -= output_gross;
+                self.y_fees += state.fee_lp_output;
+                self.y_protocol_fee += state.fee_protocol_output;
+                self.y_lp_fee = state.global_output_fee_lp;
+                self.x_liquidity += state.input;
+                self.x_fees += state.fee_lp_input;
+                self.x_protocol_fee += state.fee_protocol_input;
+                self.x_lp_fee = state.global_input_fee_lp;
+            }
+        }
+
+        state.quote(input_amount)
+    }
+
+    /// Simulates adding liquidity and applies it to the simulated pool state.
+    ///
+    /// # Arguments
+    /// * `left_bound` - The lower tick bound of the position, aligned to the tick spacing.
+    /// * `right_bound` - The upper tick bound of the position, aligned to the tick spacing.
+    /// * `x_amount` - The provided amount of token X.
+    /// * `y_amount` - The provided amount of token Y.
+    ///
+    /// # Returns
+    /// A tuple containing:
+    /// - The added position.
+    /// - The amount of token X added, the remainder is returned on-ledger.
+    /// - The amount of token Y added, the remainder is returned on-ledger.
+    ///
+    /// # Panics
+    /// Under the same conditions as `add_liquidity` of the pool.
+    pub fn add_liquidity(
+        &mut self,
+        left_bound: i32,
+        right_bound: i32,
+        x_amount: Decimal,
+        y_amount: Decimal,
+    ) -> (SimulatedPosition, Decimal, Decimal) {
+        assert!(left_bound >= MIN_TICK, "Left bound lower than allowed.");
+        assert!(right_bound <= MAX_TICK, "Right bound higher than allowed.");
+        let left_bound = align_tick(left_bound, self.tick_spacing);
+        let right_bound = align_tick(right_bound, self.tick_spacing);
+        assert!(
+            left_bound < right_bound,
+            "Please ensure that the provided left bound is strictly smaller than the right bound, and that they are not mapped to the same tick."
+        );
+
+        let price_left_sqrt = tick_to_price_sqrt(left_bound);
+        let price_right_sqrt = tick_to_price_sqrt(right_bound);
+        let (liquidity, x_amount, y_amount) = addable_amounts(
+            x_amount,
+            self.x_divisibility,
+            y_amount,
+            self.y_divisibility,
+            self.price_sqrt,
+            price_left_sqrt,
+            price_right_sqrt,
+        );
+        assert_ne!(
+            liquidity,
+            pdec!(0),
+            "[Add liquidity]: Allowed liquidity is zero."
+        );
+        self.x_liquidity += x_amount;
+        self.y_liquidity += y_amount;
+
+        let (left_tick, right_tick) = self.pool_liquidity().add_liquidity(
+            left_bound,
+            right_bound,
+            price_left_sqrt,
+            price_right_sqrt,
+            liquidity,
+        );
+
+        let position = SimulatedPosition {
+            left_bound,
+            right_bound,
+            liquidity,
+            x_fee_checkpoint: value_in_range(
+                self.x_lp_fee,
+                left_tick.x_fee_outside,
+                right_tick.x_fee_outside,
+                self.active_tick,
+                left_bound,
+                right_bound,
+            ),
+            y_fee_checkpoint: value_in_range(
+                self.y_lp_fee,
+                left_tick.y_fee_outside,
+                right_tick.y_fee_outside,
+                self.active_tick,
+                left_bound,
+                right_bound,
+            ),
+        };
+        (position, x_amount, y_amount)
+    }
+
+    /// Calculates the fees a position can claim.
+    ///
+    /// # Arguments
+    /// * `position` - The position, which needs to be part of the simulated pool.
+    ///
+    /// # Returns
+    /// A tuple containing the claimable fees of token X and token Y.
+    pub fn claimable_fees(&self, position: &SimulatedPosition) -> (Decimal, Decimal) {
+        let left_tick = &self.ticks[&position.left_bound];
+        let right_tick = &self.ticks[&position.right_bound];
+        let x_fee_checkpoint = value_in_range(
+            self.x_lp_fee,
+            left_tick.x_fee_outside,
+            right_tick.x_fee_outside,
+            self.active_tick,
+            position.left_bound,
+            position.right_bound,
+        );
+        let y_fee_checkpoint = value_in_range(
+            self.y_lp_fee,
+            left_tick.y_fee_outside,
+            right_tick.y_fee_outside,
+            self.active_tick,
+            position.left_bound,
+            position.right_bound,
+        );
+        (
+            earned_fees(
+                position.liquidity,
+                position.x_fee_checkpoint,
+                x_fee_checkpoint,
+                self.x_divisibility,
+            ),
+            earned_fees(
+                position.liquidity,
+                position.y_fee_checkpoint,
+                y_fee_checkpoint,
+                self.y_divisibility,
+            ),
+        )
+    }
+
+    /// Simulates removing a position including its fees and applies it to the simulated pool state.
+    ///
+    /// # Arguments
+    /// * `position` - The position, which needs to be part of the simulated pool.
+    ///
+    /// # Returns
+    /// A tuple containing the total amounts of token X and token Y returned, including the claimed fees.
+    pub fn remove_liquidity(&mut self, position: SimulatedPosition) -> (Decimal, Decimal) {
+        let (x_fees, y_fees) = self.claimable_fees(&position);
+        self.x_fees -= x_fees;
+        self.y_fees -= y_fees;
+
+        let price_left_sqrt = tick_to_price_sqrt(position.left_bound);
+        let price_right_sqrt = tick_to_price_sqrt(position.right_bound);
+        let (x_amount, y_amount) = removable_amounts(
+            position.liquidity,
+            self.price_sqrt,
+            price_left_sqrt,
+            price_right_sqrt,
+            self.x_divisibility,
+            self.y_divisibility,
+        );
+
+        self.pool_liquidity().remove_liquidity(
+            position.left_bound,
+            position.right_bound,
+            price_left_sqrt,
+            price_right_sqrt,
+            position.liquidity,
+        );
+
+        self.x_liquidity -= x_amount;
+        self.y_liquidity -= y_amount;
+        (x_amount + x_fees, y_amount + y_fees)
+    }
+
+    /// Returns the square root of the current price.
+    pub fn price_sqrt(&self) -> PreciseDecimal {
+        self.price_sqrt
+    }
+
+    /// Returns the current active tick.
+    pub fn active_tick(&self) -> Option<i32> {
+        self.active_tick
+    }
+
+    /// Returns the current active liquidity.
+    pub fn active_liquidity(&self) -> PreciseDecimal {
+        self.active_liquidity
+    }
+
+    /// Returns the amounts of token X and token Y held as liquidity.
+    pub fn total_liquidity(&self) -> (Decimal, Decimal) {
+        (self.x_liquidity, self.y_liquidity)
+    }
+
+    /// Returns the tick at the given index if it is initialized.
+    pub fn tick(&self, index: i32) -> Option<&Tick> {
+        self.ticks.get(&index)
+    }
+
+    fn swap_pool_state(&self) -> SwapPoolState {
+        SwapPoolState {
+            pool_address: self.pool_address,
+            x_address: self.x_address,
+            y_address: self.y_address,
+            x_divisibility: self.x_divisibility,
+            y_divisibility: self.y_divisibility,
+            price_sqrt: self.price_sqrt,
+            active_tick: self.active_tick,
+            active_liquidity: self.active_liquidity,
+            x_lp_fee: self.x_lp_fee,
+            y_lp_fee: self.y_lp_fee,
+            input_fee_rate: self.input_fee_rate,
+            fee_protocol_share: self.fee_protocol_share,
+            fee_collection_mode: self.fee_collection_mode,
+            seconds_global: self.seconds_global,
+        }
+    }
+
+    fn pool_liquidity(&mut self) -> PoolLiquidity<'_, &mut BTreeMap<i32, Tick>> {
+        PoolLiquidity {
+            ticks: &mut self.ticks,
+            active_liquidity: &mut self.active_liquidity,
+            active_tick: &mut self.active_tick,
+            price_sqrt: self.price_sqrt,
+            x_lp_fee: self.x_lp_fee,
+            y_lp_fee: self.y_lp_fee,
+            seconds_global: self.seconds_global,
+            max_liquidity_per_tick: self.max_liquidity_per_tick,
+        }
+    }
+
+    fn next_tick_above(&self, tick: Option<i32>) -> Option<i32> {
+        let start = tick.map_or(Bound::Unbounded, Bound::Excluded);
+        self.ticks
+            .range((start, Bound::Unbounded))
+            .next()
+            .map(|(index, _)| *index)
+    }
+
+    fn next_tick_at_or_below(&self, tick: Option<i32>) -> Option<i32> {
+        self.ticks.tick_at_or_below(tick?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snapshot::PoolSnapshotV1;
+    use pretty_assertions::assert_eq;
+
+    fn empty_pool(price_sqrt: PreciseDecimal, input_fee_rate: Decimal) -> PoolSimulator {
+        empty_pool_with_fee_collection_mode(price_sqrt, input_fee_rate, FeeCollectionMode::Input)
+    }
+
+    fn empty_pool_with_fee_collection_mode(
+        price_sqrt: PreciseDecimal,
+        input_fee_rate: Decimal,
+        fee_collection_mode: FeeCollectionMode,
+    ) -> PoolSimulator {
+        let snapshot = PoolSnapshotV1 {
+            pool_address: ComponentAddress::new_or_panic([192; NodeId::LENGTH]),
+            x_address: ResourceAddress::new_or_panic([93; NodeId::LENGTH]),
+            y_address: ResourceAddress::new_or_panic([94; NodeId::LENGTH]),
+            lp_address: ResourceAddress::new_or_panic([154; NodeId::LENGTH]),
+            flash_loan_address: ResourceAddress::new_or_panic([154; NodeId::LENGTH]),
+            x_divisibility: 18,
+            y_divisibility: 18,
+            tick_spacing: 10,
+            max_liquidity_per_tick: max_liquidity_per_tick(10),
+            price_sqrt,
+            active_tick: None,
+            active_liquidity: pdec!(0),
+            x_lp_fee: pdec!(0),
+            y_lp_fee: pdec!(0),
+            x_liquidity: dec!(0),
+            y_liquidity: dec!(0),
+            x_fees: dec!(0),
+            y_fees: dec!(0),
+            x_protocol_fee: dec!(0),
+            y_protocol_fee: dec!(0),
+            input_fee_rate,
+            fee_protocol_share: dec!(0),
+            fee_collection_mode,
+            flash_loan_fee_rate: dec!(0),
+            registry_address: ComponentAddress::new_or_panic([192; NodeId::LENGTH]),
+            next_sync_time: 0,
+            hooks: vec![],
+            lp_counter: 0,
+            instantiated_at: 0,
+            observations_limit: 0,
+            observations_stored: 0,
+            oldest_observation_at: None,
+            last_observation_index: None,
+        };
+        PoolSimulator::new(PoolSnapshot::V1(snapshot), vec![], 0)
+    }
+
+    fn x_address(simulator: &PoolSimulator) -> ResourceAddress {
+        simulator.x_address
+    }
+
+    fn y_address(simulator: &PoolSimulator) -> ResourceAddress {
+        simulator.y_address
+    }
+
+    #[test]
+    fn test_add_liquidity_sets_active_state() {
+        let mut simulator = empty_pool(pdec!(1), dec!(0));
+
+        let (position, x_amount, y_amount) = simulator.add_liquidity(-100, 100, dec!(10), dec!(10));
+
+        assert_eq!(simulator.active_tick(), Some(-100));
+        assert_eq!(simulator.active_liquidity(), position.liquidity);
+        assert_eq!(simulator.total_liquidity(), (x_amount, y_amount));
+        assert_eq!(
+            simulator.tick(100).unwrap().delta_liquidity,
+            -position.liquidity
+        );
+    }
+
+    #[test]
+    fn test_swap_back_and_forth() {
+        let mut simulator = empty_pool(pdec!(1), dec!(0));
+        simulator.add_liquidity(-100, 100, dec!(10), dec!(10));
+
+        let buy = simulator.swap(y_address(&simulator), dec!(1), None);
+        let sell = simulator.swap(x_address(&simulator), buy.output_amount, None);
+
+        assert!(buy.output_amount > dec!(0));
+        assert!(sell.output_amount < dec!(1));
+        assert!(simulator.price_sqrt() <= pdec!(1));
+    }
+
+    #[test]
+    fn test_swap_crosses_ticks() {
+        let mut simulator = empty_pool(pdec!(1), dec!(0));
+        simulator.add_liquidity(0, 100, dec!(10), dec!(10));
+        simulator.add_liquidity(100, 200, dec!(10), dec!(10));
+
+        let quote = simulator.swap(y_address(&simulator), dec!(15), None);
+
+        assert_eq!(quote.ticks_crossed, 1);
+        assert_eq!(simulator.active_tick(), Some(100));
+        assert_eq!(quote.remainder_amount, dec!(0));
+    }
+
+    #[test]
+    fn test_swap_max_ticks_crossed() {
+        let mut simulator = empty_pool(pdec!(1), dec!(0));
+        simulator.add_liquidity(0, 100, dec!(10), dec!(10));
+        simulator.add_liquidity(100, 200, dec!(10), dec!(10));
+
+        let quote = simulator.swap(y_address(&simulator), dec!(15), Some(0));
+
+        assert!(quote.partial_fill);
+        assert!(quote.remainder_amount > dec!(0));
+        assert_eq!(simulator.active_tick(), Some(0));
+    }
+
+    #[test]
+    fn test_remove_liquidity_returns_fees() {
+        let mut simulator = empty_pool(pdec!(1), dec!(0.01));
+        let (position, x_amount, y_amount) = simulator.add_liquidity(-100, 100, dec!(10), dec!(10));
+        let quote = simulator.swap(x_address(&simulator), dec!(1), None);
+        let (x_fees, y_fees) = simulator.claimable_fees(&position);
+
+        let (x_output, y_output) = simulator.remove_liquidity(position);
+
+        assert!(x_fees > dec!(0) && x_fees <= quote.input_fee_lp);
+        assert_eq!(y_fees, dec!(0));
+        assert!(x_output > x_amount);
+        assert!(y_output < y_amount);
+        assert_eq!(simulator.active_tick(), None);
+        assert_eq!(simulator.active_liquidity(), pdec!(0));
+        assert!(simulator.tick(-100).is_none());
+    }
+
+    #[test]
+    fn test_remove_liquidity_returns_output_fees() {
+        let mut simulator =
+            empty_pool_with_fee_collection_mode(pdec!(1), dec!(0.01), FeeCollectionMode::Output);
+        let (position, _, _) = simulator.add_liquidity(-100, 100, dec!(10), dec!(10));
+        let quote = simulator.swap(x_address(&simulator), dec!(1), None);
+        let (x_fees, y_fees) = simulator.claimable_fees(&position);
+
+        assert_eq!(quote.input_fee_lp, dec!(0));
+        assert!(quote.output_fee_lp > dec!(0));
+        assert_eq!(x_fees, dec!(0));
+        assert!(y_fees > dec!(0) && y_fees <= quote.output_fee_lp);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_swap_invalid_address() {
+        let mut simulator = empty_pool(pdec!(1), dec!(0));
+        simulator.add_liquidity(-100, 100, dec!(10), dec!(10));
+        let lp_address = ResourceAddress::new_or_panic([154; NodeId::LENGTH]);
+        simulator.swap(lp_address, dec!(1), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_set_time_before_instantiation() {
+        let mut simulator = empty_pool(pdec!(1), dec!(0));
+        simulator.instantiated_at = 10;
+        simulator.set_time(5);
+    }
+}
@@ -0,0 +1,74 @@
+use radix_common::prelude::*;
+
+use crate::pool_math::FeeCollectionMode;
+
+/// Versioned export of the pool state, as returned by the `snapshot` method of the pool.
+///
+/// Released versions are never changed. New fields are added in a new variant, so decoders can match on
+/// the variant to support all versions.
+#[derive(ScryptoSbor, Clone, Debug, PartialEq)]
+pub enum PoolSnapshot {
+    V1(PoolSnapshotV1),
+}
+
+#[derive(ScryptoSbor, Clone, Debug, PartialEq)]
+pub struct PoolSnapshotV1 {
+    pub pool_address: ComponentAddress,
+    pub x_address: ResourceAddress,
+    pub y_address: ResourceAddress,
+    pub lp_address: ResourceAddress,
+    pub flash_loan_address: ResourceAddress,
+    pub x_divisibility: u8,
+    pub y_divisibility: u8,
+    pub tick_spacing: u32,
+    pub max_liquidity_per_tick: PreciseDecimal,
+    pub price_sqrt: PreciseDecimal,
+    pub active_tick: Option<i32>,
+    pub active_liquidity: PreciseDecimal,
+    pub x_lp_fee: PreciseDecimal,
+    pub y_lp_fee: PreciseDecimal,
+    pub x_liquidity: Decimal,
+    pub y_liquidity: Decimal,
+    pub x_fees: Decimal,
+    pub y_fees: Decimal,
+    pub x_protocol_fee: Decimal,
+    pub y_protocol_fee: Decimal,
+    pub input_fee_rate: Decimal,
+    pub fee_protocol_share: Decimal,
+    pub fee_collection_mode: FeeCollectionMode,
+    pub flash_loan_fee_rate: Decimal,
+    pub registry_address: ComponentAddress,
+    pub next_sync_time: u64,
+    pub hooks: Vec<(PackageAddress, String, ComponentAddress)>,
+    pub lp_counter: u64,
+    pub instantiated_at: u64,
+    pub observations_limit: u16,
+    pub observations_stored: u16,
+    pub oldest_observation_at: Option<u64>,
+    pub last_observation_index: Option<u16>,
+}
+
+/// Versioned page of ticks, as returned by the `snapshot_ticks` method of the pool.
+///
+/// Follows the same versioning rules as `PoolSnapshot`.
+#[derive(ScryptoSbor, Clone, Debug, PartialEq)]
+pub enum TicksSnapshot {
+    V1(TicksSnapshotV1),
+}
+
+#[derive(ScryptoSbor, Clone, Debug, PartialEq)]
+pub struct TicksSnapshotV1 {
+    pub ticks: Vec<TickSnapshotV1>,
+    pub next_cursor: Option<i32>,
+}
+
+#[derive(ScryptoSbor, Clone, Debug, PartialEq)]
+pub struct TickSnapshotV1 {
+    pub index: i32,
+    pub delta_liquidity: PreciseDecimal,
+    pub total_liquidity: PreciseDecimal,
+    pub price_sqrt: PreciseDecimal,
+    pub x_fee_outside: PreciseDecimal,
+    pub y_fee_outside: PreciseDecimal,
+    pub seconds_outside: u64,
+}
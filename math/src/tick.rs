@@ -0,0 +1,410 @@
+use radix_common::prelude::*;
+use sbor::rust::collections::BTreeMap;
+use sbor::rust::ops::Bound;
+
+use crate::pool_math::{tick_to_price_sqrt, SwapType};
+
+#[derive(ScryptoSbor, Clone, Debug)]
+pub struct Tick {
+    pub index: i32,
+    pub delta_liquidity: PreciseDecimal,
+    pub total_liquidity: PreciseDecimal,
+    pub price_sqrt: PreciseDecimal,
+    pub x_fee_outside: PreciseDecimal,
+    pub y_fee_outside: PreciseDecimal,
+    pub seconds_outside: u64,
+}
+
+#[derive(ScryptoSbor, Clone, Debug)]
+pub struct TickOutside {
+    pub index: i32,
+    pub x_fee: PreciseDecimal,
+    pub y_fee: PreciseDecimal,
+    pub seconds: u64,
+}
+
+impl Tick {
+    /// Creates a new tick without liquidity.
+    ///
+    /// By convention all fees and seconds so far are assumed to have accumulated below the tick.
+    /// Therefore, the outside values are initialized with the global values if the tick is at or below
+    /// the current price and with zero otherwise.
+    ///
+    /// # Arguments
+    /// * `index` - The index of the tick.
+    /// * `price_sqrt` - The square root of the current price of the pool.
+    /// * `x_lp_fee` - The global accumulated LP fee for token X.
+    /// * `y_lp_fee` - The global accumulated LP fee for token Y.
+    /// * `seconds_global` - The number of seconds elapsed since the pool's instantiation.
+    ///
+    /// # Returns
+    /// * `Tick` - The new tick.
+    pub fn new(
+        index: i32,
+        price_sqrt: PreciseDecimal,
+        x_lp_fee: PreciseDecimal,
+        y_lp_fee: PreciseDecimal,
+        seconds_global: u64,
+    ) -> Self {
+        let tick_price_sqrt = tick_to_price_sqrt(index);
+        let (x_fee_outside, y_fee_outside, seconds_outside) = if price_sqrt < tick_price_sqrt {
+            (pdec!(0), pdec!(0), 0)
+        } else {
+            (x_lp_fee, y_lp_fee, seconds_global)
+        };
+        Self {
+            index,
+            delta_liquidity: pdec!(0),
+            total_liquidity: pdec!(0),
+            price_sqrt: tick_price_sqrt,
+            x_fee_outside,
+            y_fee_outside,
+            seconds_outside,
+        }
+    }
+
+    /// Applies a liquidity change of a position starting or ending at this tick.
+    ///
+    /// # Arguments
+    /// * `delta_liquidity` - The change of the net liquidity, positive for left bounds when adding.
+    /// * `total_liquidity` - The change of the gross liquidity referencing this tick.
+    /// * `max_liquidity_per_tick` - The maximum gross liquidity allowed per tick.
+    ///
+    /// # Panics
+    /// Panics if the resulting total liquidity exceeds `max_liquidity_per_tick`.
+    pub fn add_liquidity(
+        &mut self,
+        delta_liquidity: PreciseDecimal,
+        total_liquidity: PreciseDecimal,
+        max_liquidity_per_tick: PreciseDecimal,
+    ) {
+        self.delta_liquidity += delta_liquidity;
+        self.total_liquidity += total_liquidity;
+        assert!(
+            self.total_liquidity <= max_liquidity_per_tick,
+            "Cannot add more total liquidity to the tick than allowed by max_liquidity_per_tick"
+        );
+    }
+
+    /// Returns the fees accumulated outside the current tick for a given swap type.
+    ///
+    /// Returns accumulated fees outside the current tick's range based on the swap type.
+    /// The returned tuple contains fees for the bought and sold tokens respectively.
+    ///
+    /// # Arguments
+    /// * `swap_type` - The type of swap (BuyX or SellX) which determines the direction of trade.
+    ///
+    /// # Returns
+    /// A tuple containing the fee amounts for the tokens involved in the swap outside the current tick.
+    pub fn fee_outside(&self, swap_type: SwapType) -> (PreciseDecimal, PreciseDecimal) {
+        match swap_type {
+            SwapType::BuyX => (self.y_fee_outside, self.x_fee_outside),
+            SwapType::SellX => (self.x_fee_outside, self.y_fee_outside),
+        }
+    }
+
+    /// Updates the tick's outside fee and time values based on global accumulations.
+    ///
+    /// Updates the tick's outside fee values and time by subtracting the stored values from the global accumulations.
+    ///
+    /// # Arguments
+    /// * `fee_x_global` - The global accumulated fee for token X.
+    /// * `fee_y_global` - The global accumulated fee for token Y.
+    /// * `seconds_global` - The global accumulated seconds outside this tick.
+    ///
+    /// # Returns
+    /// A `TickOutside` instance containing the updated outside values for this tick.
+    pub fn update_outside_values(
+        &mut self,
+        fee_x_global: PreciseDecimal,
+        fee_y_global: PreciseDecimal,
+        seconds_global: u64,
+    ) -> TickOutside {
+        self.x_fee_outside = fee_x_global - self.x_fee_outside;
+        self.y_fee_outside = fee_y_global - self.y_fee_outside;
+        self.seconds_outside = seconds_global - self.seconds_outside;
+        TickOutside {
+            index: self.index,
+            x_fee: self.x_fee_outside,
+            y_fee: self.y_fee_outside,
+            seconds: self.seconds_outside,
+        }
+    }
+}
+
+/// Calculates the active liquidity after adding or removing liquidity of a position.
+///
+/// The active liquidity only changes if the current price lies within the position, i.e.
+/// `price_left_sqrt <= price_sqrt < price_right_sqrt`.
+///
+/// # Arguments
+/// * `active_liquidity` - The current active liquidity of the pool.
+/// * `liquidity` - The liquidity of the position, negative when removing.
+/// * `price_sqrt` - The square root of the current price of the pool.
+/// * `price_left_sqrt` - The square root of the price of the position's left bound.
+/// * `price_right_sqrt` - The square root of the price of the position's right bound.
+///
+/// # Returns
+/// * `PreciseDecimal` - The new active liquidity.
+pub fn active_liquidity_after(
+    active_liquidity: PreciseDecimal,
+    liquidity: PreciseDecimal,
+    price_sqrt: PreciseDecimal,
+    price_left_sqrt: PreciseDecimal,
+    price_right_sqrt: PreciseDecimal,
+) -> PreciseDecimal {
+    if price_sqrt < price_left_sqrt || price_right_sqrt <= price_sqrt {
+        return active_liquidity;
+    }
+    active_liquidity + liquidity
+}
+
+/// Calculates the active tick after adding a position, which is the closest initialized tick left of the price.
+///
+/// # Arguments
+/// * `active_tick` - The current active tick of the pool.
+/// * `left_bound` - The left bound of the added position.
+/// * `right_bound` - The right bound of the added position.
+/// * `price_sqrt` - The square root of the current price of the pool.
+/// * `price_left_sqrt` - The square root of the price of the left bound.
+/// * `price_right_sqrt` - The square root of the price of the right bound.
+///
+/// # Returns
+/// * `Option<i32>` - The new active tick.
+pub fn active_tick_after_add(
+    active_tick: Option<i32>,
+    left_bound: i32,
+    right_bound: i32,
+    price_sqrt: PreciseDecimal,
+    price_left_sqrt: PreciseDecimal,
+    price_right_sqrt: PreciseDecimal,
+) -> Option<i32> {
+    // If active tick is None we set it to the integer minimum since every valid left or right bound
+    // is a better fit than the smallest possible active tick
+    let active_tick_or_min = active_tick.unwrap_or(i32::MIN);
+
+    // Update the active tick to the right bound if the right bound is closest left of the price.
+    if active_tick_or_min < right_bound && price_right_sqrt <= price_sqrt {
+        return Some(right_bound);
+    }
+    // Update the active tick to the left bound if the left bound is closest left of the price.
+    if active_tick_or_min < left_bound && price_left_sqrt <= price_sqrt {
+        return Some(left_bound);
+    }
+    active_tick
+}
+
+/// Storage of the initialized ticks of a pool.
+///
+/// Implemented by the tick tree and bitmap of the blueprint and the `BTreeMap` of the `PoolSimulator`.
+pub trait TickStorage {
+    /// Applies `update` to the tick at `index`.
+    ///
+    /// # Returns
+    /// * `Option<Tick>` - The updated tick or `None` if the tick is not initialized.
+    fn update_tick(&mut self, index: i32, update: impl FnOnce(&mut Tick)) -> Option<Tick>;
+
+    /// Inserts the tick at its index.
+    fn insert_tick(&mut self, tick: Tick);
+
+    /// Removes the tick at `index`.
+    fn remove_tick(&mut self, index: i32);
+
+    /// Returns the index of the highest initialized tick at or below `index`.
+    fn tick_at_or_below(&self, index: i32) -> Option<i32>;
+}
+
+impl TickStorage for BTreeMap<i32, Tick> {
+    fn update_tick(&mut self, index: i32, update: impl FnOnce(&mut Tick)) -> Option<Tick> {
+        let tick = self.get_mut(&index)?;
+        update(&mut *tick);
+        Some(tick.clone())
+    }
+
+    fn insert_tick(&mut self, tick: Tick) {
+        self.insert(tick.index, tick);
+    }
+
+    fn remove_tick(&mut self, index: i32) {
+        self.remove(&index);
+    }
+
+    fn tick_at_or_below(&self, index: i32) -> Option<i32> {
+        self.range((Bound::Unbounded, Bound::Included(index)))
+            .next_back()
+            .map(|(index, _)| *index)
+    }
+}
+
+impl<T: TickStorage> TickStorage for &mut T {
+    fn update_tick(&mut self, index: i32, update: impl FnOnce(&mut Tick)) -> Option<Tick> {
+        (**self).update_tick(index, update)
+    }
+
+    fn insert_tick(&mut self, tick: Tick) {
+        (**self).insert_tick(tick)
+    }
+
+    fn remove_tick(&mut self, index: i32) {
+        (**self).remove_tick(index)
+    }
+
+    fn tick_at_or_below(&self, index: i32) -> Option<i32> {
+        (**self).tick_at_or_below(index)
+    }
+}
+
+/// The part of a pool's state which is changed by adding and removing liquidity.
+///
+/// Used by the blueprint and the `PoolSimulator` to update the ticks, the active liquidity and the active tick
+/// in the same way.
+pub struct PoolLiquidity<'a, T: TickStorage> {
+    pub ticks: T,
+    pub active_liquidity: &'a mut PreciseDecimal,
+    pub active_tick: &'a mut Option<i32>,
+    pub price_sqrt: PreciseDecimal,
+    pub x_lp_fee: PreciseDecimal,
+    pub y_lp_fee: PreciseDecimal,
+    pub seconds_global: u64,
+    pub max_liquidity_per_tick: PreciseDecimal,
+}
+
+impl<T: TickStorage> PoolLiquidity<'_, T> {
+    /// Adds the liquidity of a position to its bound ticks and updates the active liquidity and active tick.
+    ///
+    /// # Arguments
+    /// * `left_bound` - The aligned left bound of the position.
+    /// * `right_bound` - The aligned right bound of the position.
+    /// * `price_left_sqrt` - The square root of the price of the left bound.
+    /// * `price_right_sqrt` - The square root of the price of the right bound.
+    /// * `liquidity` - The added liquidity.
+    ///
+    /// # Returns
+    /// A tuple containing the updated left and right tick.
+    ///
+    /// # Panics
+    /// If the total liquidity of a tick exceeds `max_liquidity_per_tick`.
+    pub fn add_liquidity(
+        &mut self,
+        left_bound: i32,
+        right_bound: i32,
+        price_left_sqrt: PreciseDecimal,
+        price_right_sqrt: PreciseDecimal,
+        liquidity: PreciseDecimal,
+    ) -> (Tick, Tick) {
+        *self.active_liquidity = active_liquidity_after(
+            *self.active_liquidity,
+            liquidity,
+            self.price_sqrt,
+            price_left_sqrt,
+            price_right_sqrt,
+        );
+        *self.active_tick = active_tick_after_add(
+            *self.active_tick,
+            left_bound,
+            right_bound,
+            self.price_sqrt,
+            price_left_sqrt,
+            price_right_sqrt,
+        );
+        let left_tick = self.update_or_insert_tick(left_bound, liquidity, liquidity);
+        let right_tick = self.update_or_insert_tick(right_bound, -liquidity, liquidity);
+        (left_tick, right_tick)
+    }
+
+    /// Removes the liquidity of a position from its bound ticks and updates the active liquidity and active tick.
+    ///
+    /// Ticks without remaining liquidity are removed and the active tick is refitted to the highest remaining
+    /// tick at or below it.
+    ///
+    /// # Arguments
+    /// * `left_bound` - The left bound of the position.
+    /// * `right_bound` - The right bound of the position.
+    /// * `price_left_sqrt` - The square root of the price of the left bound.
+    /// * `price_right_sqrt` - The square root of the price of the right bound.
+    /// * `liquidity` - The removed liquidity.
+    ///
+    /// # Returns
+    /// A tuple containing the updated or removed left and right tick.
+    pub fn remove_liquidity(
+        &mut self,
+        left_bound: i32,
+        right_bound: i32,
+        price_left_sqrt: PreciseDecimal,
+        price_right_sqrt: PreciseDecimal,
+        liquidity: PreciseDecimal,
+    ) -> (Tick, Tick) {
+        let left_tick = self.update_or_remove_tick(left_bound, -liquidity, -liquidity);
+        let right_tick = self.update_or_remove_tick(right_bound, liquidity, -liquidity);
+        *self.active_liquidity = active_liquidity_after(
+            *self.active_liquidity,
+            -liquidity,
+            self.price_sqrt,
+            price_left_sqrt,
+            price_right_sqrt,
+        );
+        *self.active_tick = self
+            .active_tick
+            .and_then(|active_tick| self.ticks.tick_at_or_below(active_tick));
+        (left_tick, right_tick)
+    }
+
+    /// Updates an existing tick or inserts a new one.
+    ///
+    /// # Arguments
+    /// * `tick_index` - The index of the tick to update or insert.
+    /// * `delta_liquidity` - The change of the net liquidity.
+    /// * `total_liquidity` - The change of the gross liquidity.
+    ///
+    /// # Returns
+    /// * `Tick` - The updated or newly created tick.
+    ///
+    /// # Panics
+    /// If the resulting total liquidity exceeds `max_liquidity_per_tick`.
+    pub fn update_or_insert_tick(
+        &mut self,
+        tick_index: i32,
+        delta_liquidity: PreciseDecimal,
+        total_liquidity: PreciseDecimal,
+    ) -> Tick {
+        let max_liquidity_per_tick = self.max_liquidity_per_tick;
+        if let Some(tick) = self.ticks.update_tick(tick_index, |tick| {
+            tick.add_liquidity(delta_liquidity, total_liquidity, max_liquidity_per_tick)
+        }) {
+            return tick;
+        }
+
+        let mut tick = Tick::new(
+            tick_index,
+            self.price_sqrt,
+            self.x_lp_fee,
+            self.y_lp_fee,
+            self.seconds_global,
+        );
+        tick.add_liquidity(delta_liquidity, total_liquidity, max_liquidity_per_tick);
+        self.ticks.insert_tick(tick.clone());
+        tick
+    }
+
+    /// Updates a tick and removes it if its total liquidity reaches zero.
+    ///
+    /// # Arguments
+    /// * `tick_index` - The index of the tick to update or remove.
+    /// * `delta_liquidity` - The change of the net liquidity.
+    /// * `total_liquidity` - The change of the gross liquidity.
+    ///
+    /// # Returns
+    /// * `Tick` - The updated tick, or the removed tick if its total liquidity is zero.
+    pub fn update_or_remove_tick(
+        &mut self,
+        tick_index: i32,
+        delta_liquidity: PreciseDecimal,
+        total_liquidity: PreciseDecimal,
+    ) -> Tick {
+        let tick = self.update_or_insert_tick(tick_index, delta_liquidity, total_liquidity);
+        if tick.total_liquidity == pdec!(0) {
+            self.ticks.remove_tick(tick_index);
+        }
+        tick
+    }
+}
@@ -1,4 +1,6 @@
 pub mod constants;
 pub mod pool;
 pub mod pool_math;
+pub mod position_image;
+pub mod tick_bitmap;
 pub mod utils;
@@ -1,7 +1,11 @@
-use crate::{constants::*, pool_math::*, utils::*};
+use crate::position_image::{
+    position_description, position_image_in_range, position_image_url,
+    position_name,
+};
+use crate::{constants::*, pool_math::*, tick_bitmap::TickBitmap, utils::*};
 use common::math::*;
 use common::metadata::assert_component_packages_are_approved;
-use common::pools::{token_symbol, SwapType};
+use common::pools::token_symbol;
 use common::time::*;
 use common::utils::assert_within_bounds;
 use oracle::{AccumulatedObservation, ObservationInterval, Oracle};
@@ -10,6 +14,11 @@ use scrypto::prelude::*;
 use scrypto_avltree::*;
 use std::ops::Bound;
 
+pub use crate::pool_math::{
+    FeeCollectionMode, PoolSnapshot, PoolSnapshotV1, SwapQuote, Tick,
+    TickOutside, TickSnapshotV1, TicksSnapshot, TicksSnapshotV1,
+};
+
 #[blueprint]
 #[events(
     InstantiateEvent,
@@ -17,7 +26,13 @@ use std::ops::Bound;
     RemoveLiquidityEvent,
     SwapEvent,
     ClaimFeesEvent,
-    FlashLoanEvent
+    ClaimReferrerFeesEvent,
+    FlashLoanEvent,
+    RepositionEvent,
+    MergePositionsEvent,
+    SplitPositionEvent,
+    SyncRegistryEvent,
+    RegistryChangedEvent
 )]
 mod precision_pool {
     enable_method_auth! {
@@ -26,9 +41,29 @@ mod precision_pool {
         },
         methods {
             swap                        => PUBLIC;
+            swap_with_deadline          => PUBLIC;
+            swap_with_max_ticks_crossed => PUBLIC;
+            swap_with_referrer          => PUBLIC;
+            quote_swap                  => PUBLIC;
+            quote_swap_with_referrer    => PUBLIC;
             add_liquidity               => PUBLIC;
+            add_liquidity_with_deadline => PUBLIC;
+            add_liquidity_with_limits   => PUBLIC;
+            add_liquidity_locked        => PUBLIC;
+            add_liquidity_by_price      => PUBLIC;
+            zap_in                      => PUBLIC;
+            reposition                  => PUBLIC;
+            merge_positions             => PUBLIC;
+            split_position              => PUBLIC;
             add_liquidity_shape         => PUBLIC;
+            add_liquidity_shape_with_deadline => PUBLIC;
+            add_liquidity_distribution  => PUBLIC;
             remove_liquidity            => PUBLIC;
+            remove_liquidity_with_deadline => PUBLIC;
+            remove_liquidity_with_limits => PUBLIC;
+            remove_liquidity_to         => PUBLIC;
+            remove_liquidity_without_sync => PUBLIC;
+            remove_shape                => PUBLIC;
             removable_liquidity         => PUBLIC;
             tick_spacing                => PUBLIC;
             x_address                   => PUBLIC;
@@ -41,17 +76,29 @@ mod precision_pool {
             active_tick                 => PUBLIC;
             input_fee_rate              => PUBLIC;
             fee_protocol_share          => PUBLIC;
+            fee_collection_mode         => PUBLIC;
             flash_loan_fee_rate         => PUBLIC;
             flash_loan                  => PUBLIC;
             repay_loan                  => PUBLIC;
             flash_loan_address          => PUBLIC;
             hook                        => PUBLIC;
             claim_fees                  => PUBLIC;
+            claim_shape_fees            => PUBLIC;
+            compound_fees               => PUBLIC;
             claimable_fees              => PUBLIC;
+            claim_referrer_fees         => PUBLIC;
+            withdraw_referrer_fees      => PUBLIC;
+            referrer_fees               => PUBLIC;
             seconds_in_position         => PUBLIC;
+            locked_until                => PUBLIC;
             total_fees                  => PUBLIC;
+            shape_positions             => PUBLIC;
+            shape_value                 => PUBLIC;
             registry                    => PUBLIC;
             sync_registry               => PUBLIC;
+            collect_protocol_fees       => PUBLIC;
+            protocol_fees               => PUBLIC;
+            migrate_registry            => PUBLIC;
             next_sync_time              => PUBLIC;
             observations_limit          => PUBLIC;
             observation                 => PUBLIC;
@@ -59,6 +106,9 @@ mod precision_pool {
             observation_intervals       => PUBLIC;
             observations_stored         => PUBLIC;
             last_observation_index      => PUBLIC;
+            snapshot                    => PUBLIC;
+            snapshot_ticks              => PUBLIC;
+            audit                       => PUBLIC;
             execute_after_instantiate   => restrict_to: [blueprint];
         }
     }
@@ -81,19 +131,24 @@ mod precision_pool {
 
         lp_manager: ResourceManager,
         lp_counter: u64,
+        pair_symbol: Option<String>,
+        shapes: KeyValueStore<NonFungibleLocalId, IndexSet<NonFungibleLocalId>>,
 
         ticks: AvlTree<i32, Tick>,
+        tick_bitmap: TickBitmap,
 
         registry: Global<AnyComponent>,
         next_sync_time: u64,
 
         input_fee_rate: Decimal,
         fee_protocol_share: Decimal,
+        fee_collection_mode: FeeCollectionMode,
 
         x_lp_fee: PreciseDecimal,
         y_lp_fee: PreciseDecimal,
         x_protocol_fee: Vault,
         y_protocol_fee: Vault,
+        referrer_fees: KeyValueStore<ComponentAddress, (Vault, Vault)>,
         instantiated_at: u64,
 
         flash_manager: ResourceManager,
@@ -145,6 +200,48 @@ mod precision_pool {
             registry_address: ComponentAddress,
             hook_badges: Vec<(ComponentAddress, Bucket)>,
             dapp_definition: ComponentAddress,
+        ) -> (Global<PrecisionPool>, ResourceAddress) {
+            Self::instantiate_with_fee_collection_mode(
+                x_address,
+                y_address,
+                price_sqrt,
+                tick_spacing,
+                input_fee_rate,
+                flash_loan_fee_rate,
+                registry_address,
+                hook_badges,
+                dapp_definition,
+                FeeCollectionMode::Input,
+            )
+        }
+
+        /// Instantiates a new `PrecisionPool` collecting the swap fees in the token given by `fee_collection_mode`.
+        ///
+        /// Behaves exactly like `instantiate`, which collects the fees from the input token of each swap.
+        /// With `FeeCollectionMode::Output` the fees are taken from the output token instead, with
+        /// `FeeCollectionMode::X` or `FeeCollectionMode::Y` they are always collected in the same token.
+        /// Output fees are rounded up in favour of the pool. The mode is fixed for the lifetime of the pool.
+        ///
+        /// ## Arguments
+        /// - The same as `instantiate`.
+        /// - `fee_collection_mode`: The token in which the swap fees are collected.
+        ///
+        /// ## Returns
+        /// The same as `instantiate`.
+        ///
+        /// ## Panics
+        /// The same as `instantiate`.
+        pub fn instantiate_with_fee_collection_mode(
+            x_address: ResourceAddress,
+            y_address: ResourceAddress,
+            price_sqrt: PreciseDecimal,
+            tick_spacing: u32,
+            input_fee_rate: Decimal,
+            flash_loan_fee_rate: Decimal,
+            registry_address: ComponentAddress,
+            hook_badges: Vec<(ComponentAddress, Bucket)>,
+            dapp_definition: ComponentAddress,
+            fee_collection_mode: FeeCollectionMode,
         ) -> (Global<PrecisionPool>, ResourceAddress) {
             // Ensure token addresses are valid and different to prevent erroneous pool behavior.
             assert_ne!(
@@ -234,8 +331,9 @@ mod precision_pool {
                 Runtime::allocate_component_address(
                     PrecisionPool::blueprint_id(),
                 );
+            let pair_symbol = Self::pair_symbol(x_address, y_address);
             let (pool_name, lp_name, lp_description) =
-                Self::names_and_lp_description(x_address, y_address);
+                Self::names_and_lp_description(pair_symbol.clone());
             let lp_manager = Self::set_lp_manager(
                 pool_address,
                 lp_name,
@@ -276,7 +374,10 @@ mod precision_pool {
                 max_liquidity_per_tick: max_liquidity_per_tick(tick_spacing),
                 lp_manager,
                 lp_counter: 0,
+                pair_symbol,
+                shapes: KeyValueStore::new(),
                 ticks: AvlTree::new(),
+                tick_bitmap: TickBitmap::new(tick_spacing),
                 active_tick: None,
                 active_liquidity: PreciseDecimal::ZERO,
                 registry: registry_address.into(),
@@ -285,10 +386,12 @@ mod precision_pool {
                 input_fee_rate,
                 flash_loan_fee_rate,
                 fee_protocol_share: dec!(0),
+                fee_collection_mode,
                 x_lp_fee: pdec!(0),
                 y_lp_fee: pdec!(0),
                 x_protocol_fee: Vault::new(x_address),
                 y_protocol_fee: Vault::new(y_address),
+                referrer_fees: KeyValueStore::new(),
                 instantiated_at: Clock::time_in_seconds(),
                 flash_manager,
                 hook_calls,
@@ -303,6 +406,13 @@ mod precision_pool {
             ))
             .with_address(address_reservation)
             .metadata(metadata! {
+                // Only the pool itself can update the registry entry, see `migrate_registry`.
+                roles {
+                    metadata_setter => rule!(require(global_caller(pool_address)));
+                    metadata_setter_updater => rule!(deny_all);
+                    metadata_locker => rule!(deny_all);
+                    metadata_locker_updater => rule!(deny_all);
+                },
                 init {
                     "pool_address" => pool_address, locked;
                     "name" => pool_name, locked;
@@ -313,7 +423,7 @@ mod precision_pool {
                     "tick_spacing" => tick_spacing, locked;
                     "input_fee_rate" => input_fee_rate, locked;
                     "flash_loan_fee_rate" => flash_loan_fee_rate, locked;
-                    "registry" => registry_address, locked;
+                    "registry" => registry_address, updatable;
                     "hooks" => hooks_vec.clone(), locked;
                     "dapp_definition" => dapp_definition, locked;
                 }
@@ -339,6 +449,7 @@ mod precision_pool {
                 price_sqrt,
                 tick_spacing,
                 input_fee_rate,
+                fee_collection_mode,
                 flash_loan_fee_rate,
                 registry_address,
                 hooks: hooks_vec,
@@ -433,6 +544,10 @@ mod precision_pool {
         /// * `x_bucket` - A bucket containing the X tokens to be added as liquidity.
         /// * `y_bucket` - A bucket containing the Y tokens to be added as liquidity.
         /// * `shape_id` - An optional identifier for a specific liquidity shape, used for more complex liquidity strategies.
+        /// * `added_at` - An optional timestamp to carry over when moving an existing position, defaults to the current time.
+        /// * `locked_until` - An optional Unix timestamp in seconds until which the position can not be removed or
+        ///   repositioned. It is not validated here, since locks carried over from existing positions may already
+        ///   have ended, new locks are validated by `add_liquidity_locked`.
         ///
         /// # Returns
         /// A tuple containing:
@@ -451,6 +566,8 @@ mod precision_pool {
             mut x_bucket: Bucket,
             mut y_bucket: Bucket,
             shape_id: Option<NonFungibleLocalId>,
+            added_at: Option<u64>,
+            locked_until: Option<u64>,
         ) -> (Bucket, Bucket, Bucket) {
             // Ensure the bounds are within the allowed tick range.
             assert!(left_bound >= MIN_TICK, "Left bound lower than allowed.");
@@ -485,37 +602,16 @@ mod precision_pool {
             let (x_provided, y_provided) =
                 (x_bucket.amount(), y_bucket.amount());
 
-            // Prepare the state before adding liquidity, including the current pool state and the position details.
-            let state_before = BeforeAddLiquidityState {
-                pool_address: self.pool_address,
-                x_provided,
-                y_provided,
-                active_liquidity: self.active_liquidity,
-                price_sqrt: self.price_sqrt,
-                position: LiquidityPositionType {
+            // Execute hooks before adding liquidity, allowing for custom logic or validations.
+            (x_bucket, y_bucket) = self.execute_before_add_liquidity_hooks(
+                LiquidityPositionType {
                     left_bound,
                     right_bound,
                     position_id: None,
                     shape_id: shape_id.clone(),
                 },
-            };
-
-            // Execute hooks before adding liquidity, allowing for custom logic or validations.
-            (_, x_bucket, y_bucket) = self.execute_hooks(
-                HookCall::BeforeAddLiquidity,
-                (state_before, x_bucket, y_bucket),
-            );
-
-            // Verify that the hooks have not improperly modified the amounts of X and Y tokens.
-            assert_hooks_bucket_output(
-                x_provided,
-                x_bucket.amount(),
-                "BeforeAddLiquidity",
-            );
-            assert_hooks_bucket_output(
-                y_provided,
-                y_bucket.amount(),
-                "BeforeAddLiquidity",
+                x_bucket,
+                y_bucket,
             );
 
             // Calculate the square root prices for the left and right bounds.
@@ -544,25 +640,15 @@ mod precision_pool {
             self.x_liquidity.put(x_bucket.take(x_amount));
             self.y_liquidity.put(y_bucket.take(y_amount));
 
-            // Update the pool's active liquidity and active tick based on the added liquidity.
-            self.update_active_liquidity(
-                liquidity,
-                price_left_sqrt,
-                price_right_sqrt,
-            );
-            self.update_active_tick(
+            // Update the pool's active liquidity, active tick and the tick data for the left and right bounds.
+            let (left_tick, right_tick) = self.pool_liquidity().add_liquidity(
                 left_bound,
                 right_bound,
                 price_left_sqrt,
                 price_right_sqrt,
+                liquidity,
             );
 
-            // Update or insert tick data for the left and right bounds.
-            let left_tick =
-                self.update_or_insert_tick(left_bound, liquidity, liquidity);
-            let right_tick =
-                self.update_or_insert_tick(right_bound, -liquidity, liquidity);
-
             // Calculate fee checkpoints for X and Y tokens based on the updated tick data.
             // The checkpoints can be negative since only the growth between two checkpoints is relevant.
             let x_fee_checkpoint = value_in_range(
@@ -601,30 +687,25 @@ mod precision_pool {
                     left_bound,
                     right_bound,
                     shape_id.clone(),
+                    added_at,
+                    locked_until,
                     x_fee_checkpoint,
                     y_fee_checkpoint,
                     seconds_inside_checkpoint,
                 );
 
-            let state_after = AfterAddLiquidityState {
-                pool_address: self.pool_address,
-                x_added: x_amount,
-                y_added: y_amount,
-                added_liquidity: liquidity,
-                active_liquidity: self.active_liquidity,
-                price_sqrt: self.price_sqrt,
-                position: LiquidityPositionType {
+            // Execute hooks after adding liquidity, allowing for custom logic or validations.
+            position_bucket = self.execute_after_add_liquidity_hooks(
+                LiquidityPositionType {
                     left_bound,
                     right_bound,
                     position_id: Some(position_id.clone()),
                     shape_id,
                 },
-            };
-
-            // Execute hooks after adding liquidity, allowing for custom logic or validations.
-            (_, position_bucket) = self.execute_hooks(
-                HookCall::AfterAddLiquidity,
-                (state_after, position_bucket),
+                x_amount,
+                y_amount,
+                liquidity,
+                position_bucket,
             );
 
             Runtime::emit_event(AddLiquidityEvent {
@@ -644,6 +725,91 @@ mod precision_pool {
             (position_bucket, x_bucket, y_bucket)
         }
 
+        /// Executes the before add liquidity hooks for the given position and the provided tokens.
+        ///
+        /// # Arguments
+        /// * `position` - The position the liquidity is added to, without identifier if it is minted afterwards.
+        /// * `x_bucket` - A bucket containing the X tokens to be added as liquidity.
+        /// * `y_bucket` - A bucket containing the Y tokens to be added as liquidity.
+        ///
+        /// # Returns
+        /// The X and Y buckets returned by the hooks.
+        ///
+        /// # Panics
+        /// If the hooks took more tokens than the allowed limit.
+        fn execute_before_add_liquidity_hooks(
+            &self,
+            position: LiquidityPositionType,
+            x_bucket: Bucket,
+            y_bucket: Bucket,
+        ) -> (Bucket, Bucket) {
+            let (x_provided, y_provided) =
+                (x_bucket.amount(), y_bucket.amount());
+
+            // Prepare the state before adding liquidity, including the current pool state and the position details.
+            let state_before = BeforeAddLiquidityState {
+                pool_address: self.pool_address,
+                x_provided,
+                y_provided,
+                active_liquidity: self.active_liquidity,
+                price_sqrt: self.price_sqrt,
+                position,
+            };
+            let (_, x_bucket, y_bucket): (_, Bucket, Bucket) = self
+                .execute_hooks(
+                    HookCall::BeforeAddLiquidity,
+                    (state_before, x_bucket, y_bucket),
+                );
+
+            // Verify that the hooks have not improperly modified the amounts of X and Y tokens.
+            assert_hooks_bucket_output(
+                x_provided,
+                x_bucket.amount(),
+                "BeforeAddLiquidity",
+            );
+            assert_hooks_bucket_output(
+                y_provided,
+                y_bucket.amount(),
+                "BeforeAddLiquidity",
+            );
+            (x_bucket, y_bucket)
+        }
+
+        /// Executes the after add liquidity hooks for the given position and the added liquidity.
+        ///
+        /// # Arguments
+        /// * `position` - The position the liquidity has been added to.
+        /// * `x_added` - The amount of X tokens added as liquidity.
+        /// * `y_added` - The amount of Y tokens added as liquidity.
+        /// * `added_liquidity` - The liquidity added to the position.
+        /// * `position_bucket` - A bucket containing the minted position, empty if an existing position is increased.
+        ///
+        /// # Returns
+        /// The position bucket returned by the hooks.
+        fn execute_after_add_liquidity_hooks(
+            &self,
+            position: LiquidityPositionType,
+            x_added: Decimal,
+            y_added: Decimal,
+            added_liquidity: PreciseDecimal,
+            position_bucket: Bucket,
+        ) -> Bucket {
+            let state_after = AfterAddLiquidityState {
+                pool_address: self.pool_address,
+                x_added,
+                y_added,
+                added_liquidity,
+                active_liquidity: self.active_liquidity,
+                price_sqrt: self.price_sqrt,
+                position,
+            };
+            let (_, position_bucket): (_, Bucket) = self.execute_hooks(
+                HookCall::AfterAddLiquidity,
+                (state_after, position_bucket),
+            );
+            position_bucket
+        }
+
         /// Adds liquidity to the pool within specified price bounds.
         ///
         /// This method is responsible for adding liquidity to the pool between the specified `left_bound` and `right_bound`.
@@ -681,199 +847,1089 @@ mod precision_pool {
                 x_bucket,
                 y_bucket,
                 None,
+                None,
+                None,
             )
         }
 
-        /// Adds multiple liquidity positions to the pool simultaneously.
+        /// Adds liquidity to the pool within specified price bounds and locks the position until the given time.
         ///
-        /// This method allows for batch processing of multiple liquidity additions, which can be more gas-efficient
-        /// and ensures atomicity of transactions. It is particularly useful for strategies involving multiple liquidity
-        /// positions across different price ranges.
+        /// Behaves like `add_liquidity` but the minted position can not be removed or repositioned
+        /// before `locked_until`, e.g. to commit liquidity to the pool for incentive programs.
+        /// Fees can still be claimed and compounded while the position is locked.
+        /// Positions split off a locked position inherit its lock. Merging is allowed, the merged position
+        /// carries forward the latest lock of the merged positions.
         ///
         /// # Arguments
-        /// - `positions`: A vector of tuples, each representing a liquidity position with:
-        ///   - `left_bound`: The lower price bound for adding liquidity.
-        ///   - `right_bound`: The upper price bound for adding liquidity.
-        ///   - `x_bucket`: A bucket containing the X tokens to be added as liquidity.
-        ///   - `y_bucket`: A bucket containing the Y tokens to be added as liquidity.
-        /// - `shape_proof`: An optional non-fungible proof that allows for adding new positions to an existing shape.
+        /// * `left_bound` - The lower price bound for adding liquidity.
+        /// * `right_bound` - The upper price bound for adding liquidity.
+        /// * `x_bucket` - A bucket containing the X tokens to be added as liquidity.
+        /// * `y_bucket` - A bucket containing the Y tokens to be added as liquidity.
+        /// * `locked_until` - Unix timestamp in seconds until which the position is locked.
         ///
         /// # Returns
-        /// A tuple containing:
-        /// - `Bucket`: A bucket with any remaining X tokens not added as liquidity across all positions.
-        /// - `Bucket`: A bucket with any remaining Y tokens not added as liquidity across all positions.
-        /// - `Bucket`: A bucket containing the LP tokens representing the added liquidity for all positions.
-        pub fn add_liquidity_shape(
-            &mut self,
-            positions: Vec<(i32, i32, Bucket, Bucket)>,
-            shape_proof: Option<NonFungibleProof>,
-        ) -> (Bucket, Bucket, Bucket) {
-            // Initialize output buckets for X and Y tokens using the pool's respective token addresses.
-            let mut x_output_shape = Bucket::new(self.x_address());
-            let mut y_output_shape = Bucket::new(self.y_address());
-            // Initialize an LP token bucket using the LP manager's address.
-            let mut lp_nfts = Bucket::new(self.lp_manager.address());
-            // Determine the `shape_id` based on the provided `shape_proof`, generating a new one if not provided.
-            let shape_id = match shape_proof {
-                Some(proof) => {
-                    proof
-                        .check(self.lp_manager.address())
-                        .non_fungible::<LiquidityPosition>()
-                        .data()
-                        .shape_id
-                }
-                None => {
-                    Some(NonFungibleLocalId::ruid(Runtime::generate_ruid()))
-                }
-            };
-            // Process each position, collecting resulting LP tokens and any unadded X and Y tokens.
-            for (left_bound, right_bound, x_bucket, y_bucket) in positions {
-                let (lp_nft, x_output, y_output) = self.add_liquidity_internal(
-                    left_bound,
-                    right_bound,
-                    x_bucket,
-                    y_bucket,
-                    shape_id.clone(),
-                );
-                lp_nfts.put(lp_nft);
-                x_output_shape.put(x_output);
-                y_output_shape.put(y_output);
-            }
-            // Aggregate all LP tokens and unadded tokens into their respective buckets.
-            (lp_nfts, x_output_shape, y_output_shape)
-        }
-
-        /// Mints a new liquidity position non-fungible token (LP NFT).
+        /// The same as `add_liquidity`.
         ///
-        /// This function is responsible for creating a new LP NFT that represents a liquidity position within the pool.
-        /// It increments the LP counter to ensure a unique identifier for each new position, encapsulates the liquidity details
-        /// in a `LiquidityPosition` struct, and mints the NFT using the pool's LP manager.
-        ///
-        /// ## Arguments
-        /// - `liquidity`: The amount of liquidity being added.
-        /// - `left_bound`: The lower price bound of the liquidity position.
-        /// - `right_bound`: The upper price bound of the liquidity position.
-        /// - `shape_id`: An optional identifier for a specific liquidity shape, used for more complex liquidity strategies.
-        /// - `x_fee_checkpoint`: The checkpoint for fees collected in token X up to this point.
-        /// - `y_fee_checkpoint`: The checkpoint for fees collected in token Y up to this point.
-        /// - `seconds_inside_checkpoint`: The checkpoint for total time in seconds this position has been active within the specified price range.
-        ///
-        /// ## Returns
-        /// - `NonFungibleLocalId`: The unique identifier of the newly minted LP NFT.
-        /// - `LiquidityPosition`: The liquidity position data encapsulated in the minted NFT.
-        /// - `Bucket`: A bucket containing the newly minted LP NFT.
-        fn mint_lp_token(
+        /// # Panics
+        /// - If `locked_until` is not in the future.
+        /// - In all cases `add_liquidity` panics.
+        pub fn add_liquidity_locked(
             &mut self,
-            liquidity: PreciseDecimal,
             left_bound: i32,
             right_bound: i32,
-            shape_id: Option<NonFungibleLocalId>,
-            x_fee_checkpoint: PreciseDecimal,
-            y_fee_checkpoint: PreciseDecimal,
-            seconds_inside_checkpoint: i64,
-        ) -> (NonFungibleLocalId, LiquidityPosition, Bucket) {
-            // Increment the LP counter to generate a unique position ID.
-            self.lp_counter += 1;
-            let position_id = NonFungibleLocalId::Integer(
-                IntegerNonFungibleLocalId::new(self.lp_counter),
+            x_bucket: Bucket,
+            y_bucket: Bucket,
+            locked_until: u64,
+        ) -> (Bucket, Bucket, Bucket) {
+            assert!(
+                locked_until > Clock::time_in_seconds(),
+                "[Add liquidity]: The lock needs to end in the future."
             );
-
-            let position = LiquidityPosition {
-                liquidity,
+            self.add_liquidity_internal(
                 left_bound,
                 right_bound,
-                shape_id,
-                added_at: Clock::time_in_seconds(),
-                x_fee_checkpoint,
-                y_fee_checkpoint,
-                x_total_fee_checkpoint: x_fee_checkpoint,
-                y_total_fee_checkpoint: y_fee_checkpoint,
-                seconds_inside_checkpoint,
-            };
-
-            // Mint the LP NFT using the LP manager and the newly created position.
-            let position_bucket = self
-                .lp_manager
-                .mint_non_fungible(&position_id, position.clone());
-
-            (position_id, position, position_bucket)
+                x_bucket,
+                y_bucket,
+                None,
+                None,
+                Some(locked_until),
+            )
         }
 
-        /// Updates the active liquidity in the pool based on the current price.
+        /// Adds liquidity to the pool within specified price bounds, failing if the deadline has passed.
         ///
-        /// Adjusts the pool's active liquidity by adding `liquidity` when the current price (`self.price_sqrt`)
-        /// is between `price_left_sqrt` (inclusive) and `price_right_sqrt` (exclusive).
+        /// Behaves exactly like `add_liquidity` but protects the liquidity provider against the transaction
+        /// being executed later than intended, e.g. after sitting in the mempool while the price moved.
         ///
-        /// ## Arguments
-        /// - `liquidity`: The amount of liquidity to potentially add to active liquidity.
-        /// - `price_left_sqrt`: The square root of the lower price bound.
-        /// - `price_right_sqrt`: The square root of the upper price bound.
-        fn update_active_liquidity(
-            &mut self,
-            liquidity: PreciseDecimal,
-            price_left_sqrt: PreciseDecimal,
-            price_right_sqrt: PreciseDecimal,
-        ) {
-            // Check if the current price is within the specified bounds.
-            // If it is not, exit the function without modifying the active liquidity.
-            if self.price_sqrt < price_left_sqrt
-                || price_right_sqrt <= self.price_sqrt
-            {
-                return;
-            }
-            // If the current price is within the bounds, add the specified liquidity
-            // to the pool's active liquidity.
-            self.active_liquidity += liquidity;
-        }
+        /// # Arguments
+        /// * `left_bound` - The lower price bound for adding liquidity.
+        /// * `right_bound` - The upper price bound for adding liquidity.
+        /// * `x_bucket` - A bucket containing the X tokens to be added as liquidity.
+        /// * `y_bucket` - A bucket containing the Y tokens to be added as liquidity.
+        /// * `deadline` - Optional Unix timestamp in seconds after which the transaction is rejected.
+        ///
+        /// # Returns
+        /// The same as `add_liquidity`.
+        ///
+        /// # Panics
+        /// - If the current time is past the `deadline`.
+        /// - In all cases `add_liquidity` panics.
+        pub fn add_liquidity_with_deadline(
+            &mut self,
+            left_bound: i32,
+            right_bound: i32,
+            x_bucket: Bucket,
+            y_bucket: Bucket,
+            deadline: Option<u64>,
+        ) -> (Bucket, Bucket, Bucket) {
+            assert_deadline_not_passed(deadline);
+            self.add_liquidity(left_bound, right_bound, x_bucket, y_bucket)
+        }
+
+        /// Adds liquidity to the pool within a price range instead of a tick range.
+        ///
+        /// The prices are converted to ticks aligned to the tick spacing of the pool according to `rounding`,
+        /// see `price_range_to_ticks`. Afterwards it behaves exactly like `add_liquidity`.
+        ///
+        /// # Arguments
+        /// * `price_left` - The lower price (not square root) of the range.
+        /// * `price_right` - The upper price (not square root) of the range.
+        /// * `rounding` - How prices between aligned ticks are rounded: `Inward`, `Outward` or `Nearest`.
+        /// * `x_bucket` - A bucket containing the X tokens to be added as liquidity.
+        /// * `y_bucket` - A bucket containing the Y tokens to be added as liquidity.
+        ///
+        /// # Returns
+        /// The same as `add_liquidity`.
+        ///
+        /// # Panics
+        /// - If a price is not positive or outside the price range covered by `MIN_TICK` and `MAX_TICK`.
+        /// - If the price range does not contain at least one tick spacing after rounding.
+        /// - In all cases `add_liquidity` panics.
+        pub fn add_liquidity_by_price(
+            &mut self,
+            price_left: Decimal,
+            price_right: Decimal,
+            rounding: TickRounding,
+            x_bucket: Bucket,
+            y_bucket: Bucket,
+        ) -> (Bucket, Bucket, Bucket) {
+            let (left_bound, right_bound) = price_range_to_ticks(
+                price_left,
+                price_right,
+                self.tick_spacing,
+                rounding,
+            );
+            self.add_liquidity(left_bound, right_bound, x_bucket, y_bucket)
+        }
+
+        /// Adds liquidity to the pool within specified price bounds with slippage protection.
+        ///
+        /// Behaves like `add_liquidity` but protects the liquidity provider against price movements
+        /// right before the deposit, e.g. by a sandwich attack. Since `addable_amounts` only takes the
+        /// amounts matching the current price, a moved price leads to a different ratio being deposited.
+        ///
+        /// # Arguments
+        /// * `left_bound` - The lower price bound for adding liquidity.
+        /// * `right_bound` - The upper price bound for adding liquidity.
+        /// * `x_bucket` - A bucket containing the X tokens to be added as liquidity.
+        /// * `y_bucket` - A bucket containing the Y tokens to be added as liquidity.
+        /// * `min_x` - Minimum amount of X tokens that has to be deposited into the position.
+        /// * `min_y` - Minimum amount of Y tokens that has to be deposited into the position.
+        /// * `max_price_deviation` - Optional tuple of the expected price (not square root) and the
+        ///   maximum relative deviation of the current price from it, e.g. `(dec!(2), dec!("0.01"))`.
+        /// * `deadline` - Optional Unix timestamp in seconds after which the transaction is rejected.
+        ///
+        /// # Returns
+        /// The same as `add_liquidity`.
+        ///
+        /// # Panics
+        /// - If the current time is past the `deadline`.
+        /// - If the current price deviates more than allowed from the expected price.
+        /// - If less than `min_x` X tokens or `min_y` Y tokens are deposited.
+        /// - In all cases `add_liquidity` panics.
+        pub fn add_liquidity_with_limits(
+            &mut self,
+            left_bound: i32,
+            right_bound: i32,
+            x_bucket: Bucket,
+            y_bucket: Bucket,
+            min_x: Decimal,
+            min_y: Decimal,
+            max_price_deviation: Option<(Decimal, Decimal)>,
+            deadline: Option<u64>,
+        ) -> (Bucket, Bucket, Bucket) {
+            assert_deadline_not_passed(deadline);
+            if let Some((expected_price, max_deviation)) = max_price_deviation {
+                assert_price_deviation(
+                    self.price_sqrt,
+                    expected_price,
+                    max_deviation,
+                );
+            }
+
+            let (x_provided, y_provided) =
+                (x_bucket.amount(), y_bucket.amount());
+            let (position_bucket, x_remainder, y_remainder) =
+                self.add_liquidity(left_bound, right_bound, x_bucket, y_bucket);
+
+            // Compare the amounts actually taken from the provider, including any hook adjustments.
+            assert_min_amount(
+                x_provided - x_remainder.amount(),
+                min_x,
+                "Add liquidity",
+                "X",
+            );
+            assert_min_amount(
+                y_provided - y_remainder.amount(),
+                min_y,
+                "Add liquidity",
+                "Y",
+            );
+
+            (position_bucket, x_remainder, y_remainder)
+        }
+
+        /// Adds liquidity to the pool within specified price bounds providing only a single token.
+        ///
+        /// The method swaps the fraction of the input token required by the position against the pool itself,
+        /// paying the regular input fee and executing the swap hooks, and then adds the resulting X and Y tokens
+        /// as liquidity. The swap amount is computed against the active liquidity; if the swap crosses ticks
+        /// the ratio is slightly off and the unused tokens are returned. Without active liquidity, only positions
+        /// outside of the current price can be zapped into, since nothing can be swapped at the current price.
+        ///
+        /// # Arguments
+        /// * `left_bound` - The lower price bound for adding liquidity.
+        /// * `right_bound` - The upper price bound for adding liquidity.
+        /// * `input_bucket` - A bucket containing either X or Y tokens.
+        /// * `max_price_deviation` - Tuple of the expected price (not square root) and the maximum relative deviation
+        ///   of the current price and of the swap output (including fees) from the output at the expected price,
+        ///   e.g. `(dec!(2), dec!("0.01"))` for 1%.
+        ///
+        /// # Returns
+        /// A tuple containing:
+        /// * A bucket containing the LP token representing the added liquidity.
+        /// * A bucket with any remaining X tokens not added as liquidity.
+        /// * A bucket with any remaining Y tokens not added as liquidity.
+        ///
+        /// # Panics
+        /// - If the input token is neither X nor Y.
+        /// - If the position contains the current price but there is no active liquidity to swap against.
+        /// - If the current price or the swap deviate more than allowed from the expected price.
+        /// - In all cases `add_liquidity` panics.
+        pub fn zap_in(
+            &mut self,
+            left_bound: i32,
+            right_bound: i32,
+            input_bucket: Bucket,
+            max_price_deviation: (Decimal, Decimal),
+        ) -> (Bucket, Bucket, Bucket) {
+            assert!(
+                self.active_liquidity > pdec!(0)
+                    || !self.position_in_range(
+                        align_tick(left_bound, self.tick_spacing),
+                        align_tick(right_bound, self.tick_spacing),
+                    ),
+                "[Zap in]: No active liquidity to swap the input against."
+            );
+            let (x_bucket, y_bucket) = match self
+                .swap_type(input_bucket.resource_address())
+            {
+                SwapType::SellX => {
+                    (input_bucket, Bucket::new(self.y_address()))
+                }
+                SwapType::BuyX => (Bucket::new(self.x_address()), input_bucket),
+            };
+            let (x_bucket, y_bucket) = self.rebalance(
+                x_bucket,
+                y_bucket,
+                left_bound,
+                right_bound,
+                max_price_deviation,
+            );
+            self.add_liquidity(left_bound, right_bound, x_bucket, y_bucket)
+        }
+
+        /// Swaps the excess token through the pool such that the tokens match the ratio required by a position.
+        ///
+        /// The swap amount is computed by `zap_swap_amount` against the active liquidity. The swap itself is executed
+        /// via `swap`, i.e. fees are charged and the swap hooks are executed.
+        ///
+        /// # Arguments
+        /// * `x_bucket` - A bucket containing the available X tokens.
+        /// * `y_bucket` - A bucket containing the available Y tokens.
+        /// * `left_bound` - The lower price bound of the position.
+        /// * `right_bound` - The upper price bound of the position.
+        /// * `max_price_deviation` - Tuple of the expected price (not square root) and the maximum relative deviation
+        ///   of the current price and of the swap output (including fees) from the output at the expected price.
+        ///
+        /// # Returns
+        /// A tuple containing the rebalanced X and Y buckets.
+        ///
+        /// # Panics
+        /// - If the current price deviates more than allowed from the expected price.
+        /// - If the swap output deviates more than allowed from the output at the expected price.
+        fn rebalance(
+            &mut self,
+            x_bucket: Bucket,
+            y_bucket: Bucket,
+            left_bound: i32,
+            right_bound: i32,
+            max_price_deviation: (Decimal, Decimal),
+        ) -> (Bucket, Bucket) {
+            // The price is checked against the caller's expectation, since it can be moved within the transaction.
+            let (expected_price, max_deviation) = max_price_deviation;
+            assert_price_deviation(
+                self.price_sqrt,
+                expected_price,
+                max_deviation,
+            );
+            let price_left_sqrt =
+                tick_to_price_sqrt(align_tick(left_bound, self.tick_spacing));
+            let price_right_sqrt =
+                tick_to_price_sqrt(align_tick(right_bound, self.tick_spacing));
+            let swap_type = rebalance_swap_type(
+                x_bucket.amount(),
+                y_bucket.amount(),
+                self.price_sqrt,
+                price_left_sqrt,
+                price_right_sqrt,
+            );
+            let (mut input_bucket, mut output_bucket) = match swap_type {
+                SwapType::SellX => (x_bucket, y_bucket),
+                SwapType::BuyX => (y_bucket, x_bucket),
+            };
+
+            let swap_amount = zap_swap_amount(
+                swap_type,
+                input_bucket.amount(),
+                output_bucket.amount(),
+                self.input_fee_rate,
+                self.fee_collection_mode,
+                self.active_liquidity,
+                self.price_sqrt,
+                price_left_sqrt,
+                price_right_sqrt,
+                self.input_divisibility(swap_type),
+            );
+            if swap_amount > Decimal::ZERO {
+                let (swap_output, swap_remainder) =
+                    self.swap(input_bucket.take(swap_amount));
+                assert_swap_slippage(
+                    swap_type,
+                    expected_price,
+                    swap_amount - swap_remainder.amount(),
+                    swap_output.amount(),
+                    max_deviation,
+                );
+                output_bucket.put(swap_output);
+                input_bucket.put(swap_remainder);
+            }
+
+            match swap_type {
+                SwapType::SellX => (input_bucket, output_bucket),
+                SwapType::BuyX => (output_bucket, input_bucket),
+            }
+        }
+
+        /// Moves an existing liquidity position to a new price range.
+        ///
+        /// The liquidity of the position is removed including the accrued fees, the tokens are rebalanced through
+        /// this pool to match the ratio required by the new range and then added as a new position.
+        /// The new position keeps the `shape_id` and `added_at` of the old position.
+        /// Locked positions can only be repositioned after their lock has ended.
+        /// Besides the regular remove liquidity, swap and add liquidity events a `RepositionEvent` linking
+        /// the old and the new position is emitted.
+        ///
+        /// # Arguments
+        /// * `position` - A bucket containing exactly one liquidity position NFT of this pool.
+        /// * `left_bound` - The new lower price bound.
+        /// * `right_bound` - The new upper price bound.
+        /// * `max_price_deviation` - Tuple of the expected price (not square root) and the maximum relative deviation
+        ///   of the current price and of the rebalancing swap output (including fees) from the output at the
+        ///   expected price, e.g. `(dec!(2), dec!("0.01"))` for 1%.
+        ///
+        /// # Returns
+        /// A tuple containing:
+        /// * A bucket containing the new LP token.
+        /// * A bucket with any remaining X tokens not added as liquidity.
+        /// * A bucket with any remaining Y tokens not added as liquidity.
+        ///
+        /// # Panics
+        /// - If the bucket does not contain exactly one liquidity position of this pool.
+        /// - If the position is still locked.
+        /// - If the current price or the rebalancing swap deviate more than allowed from the expected price.
+        /// - In all cases `remove_liquidity` or `add_liquidity` panic.
+        pub fn reposition(
+            &mut self,
+            position: NonFungibleBucket,
+            left_bound: i32,
+            right_bound: i32,
+            max_price_deviation: (Decimal, Decimal),
+        ) -> (Bucket, Bucket, Bucket) {
+            assert_eq!(
+                position.resource_address(),
+                self.lp_manager.address(),
+                "[Reposition]: The position is not a liquidity position of this pool."
+            );
+            assert_eq!(
+                position.amount(),
+                dec!(1),
+                "[Reposition]: Exactly one liquidity position has to be provided."
+            );
+            let old_position_nft = position.non_fungible::<LiquidityPosition>();
+            let old_position = old_position_nft.data();
+            assert_position_unlocked(old_position.locked_until, "Reposition");
+
+            let (x_bucket, y_bucket) = self.remove_liquidity(position);
+            let (x_bucket, y_bucket) = self.rebalance(
+                x_bucket,
+                y_bucket,
+                left_bound,
+                right_bound,
+                max_price_deviation,
+            );
+            let (position_bucket, x_remainder, y_remainder) = self
+                .add_liquidity_internal(
+                    left_bound,
+                    right_bound,
+                    x_bucket,
+                    y_bucket,
+                    old_position.shape_id.clone(),
+                    Some(old_position.added_at),
+                    None,
+                );
+
+            let new_position_id =
+                position_bucket.as_non_fungible().non_fungible_local_id();
+            let new_position: LiquidityPosition =
+                self.lp_manager.get_non_fungible_data(&new_position_id);
+            Runtime::emit_event(RepositionEvent {
+                old_position_id: old_position_nft.local_id().clone(),
+                new_position_id,
+                old_left_bound: old_position.left_bound,
+                old_right_bound: old_position.right_bound,
+                new_left_bound: new_position.left_bound,
+                new_right_bound: new_position.right_bound,
+                x_remainder: x_remainder.amount(),
+                y_remainder: y_remainder.amount(),
+            });
+
+            (position_bucket, x_remainder, y_remainder)
+        }
+
+        /// Merges multiple liquidity positions with identical bounds into a single position.
+        ///
+        /// The accrued fees of all positions are claimed and returned. The new position holds the sum of the liquidity
+        /// and its total fee and seconds inside checkpoints are the liquidity weighted averages of the merged positions,
+        /// so `total_fees` and `seconds_in_position` stay consistent. The merged positions are burned.
+        /// The new position is locked until the latest lock of the merged positions ends.
+        /// The ticks of the pool are not affected since the total liquidity in the range does not change.
+        ///
+        /// # Arguments
+        /// * `lp_positions` - A bucket containing the liquidity position NFTs to merge.
+        ///
+        /// # Returns
+        /// A tuple containing:
+        /// * A bucket containing the LP token of the merged position.
+        /// * A bucket containing the claimed X fees.
+        /// * A bucket containing the claimed Y fees.
+        ///
+        /// # Panics
+        /// - If the bucket does not contain at least two liquidity positions of this pool.
+        /// - If the positions do not have identical bounds.
+        pub fn merge_positions(
+            &mut self,
+            lp_positions: NonFungibleBucket,
+        ) -> (Bucket, Bucket, Bucket) {
+            assert_eq!(
+                lp_positions.resource_address(),
+                self.lp_manager.address(),
+                "[Merge positions]: The positions are not liquidity positions of this pool."
+            );
+            assert!(
+                lp_positions.amount() >= dec!(2),
+                "[Merge positions]: At least two positions are required."
+            );
+
+            let position_nfts =
+                lp_positions.non_fungibles::<LiquidityPosition>();
+            let first_position = position_nfts[0].data();
+
+            let mut x_fees = Bucket::new(self.x_address());
+            let mut y_fees = Bucket::new(self.y_address());
+            let mut liquidity = pdec!(0);
+            let mut x_total_fee_weighted = pdec!(0);
+            let mut y_total_fee_weighted = pdec!(0);
+            let mut seconds_inside_weighted = pdec!(0);
+            let mut added_at = first_position.added_at;
+            let mut shape_id = first_position.shape_id.clone();
+            let mut locked_until = first_position.locked_until;
+            for position_nft in &position_nfts {
+                let position = position_nft.data();
+                assert!(
+                    position.left_bound == first_position.left_bound
+                        && position.right_bound == first_position.right_bound,
+                    "[Merge positions]: All positions need to have identical bounds."
+                );
+
+                let (x_claimed, y_claimed) =
+                    self.claim_fees_internal(position_nft);
+                x_fees.put(x_claimed);
+                y_fees.put(y_claimed);
+
+                liquidity += position.liquidity;
+                x_total_fee_weighted +=
+                    position.x_total_fee_checkpoint * position.liquidity;
+                y_total_fee_weighted +=
+                    position.y_total_fee_checkpoint * position.liquidity;
+                seconds_inside_weighted +=
+                    PreciseDecimal::from(position.seconds_inside_checkpoint)
+                        * position.liquidity;
+                added_at = added_at.min(position.added_at);
+                locked_until = locked_until.max(position.locked_until);
+                if shape_id != position.shape_id {
+                    shape_id = None;
+                }
+            }
+
+            // After claiming, the fee checkpoints of all positions are equal to the current fee checkpoints.
+            let claimed_position: LiquidityPosition = self
+                .lp_manager
+                .get_non_fungible_data(position_nfts[0].local_id());
+            let seconds_inside_checkpoint: Decimal =
+                (seconds_inside_weighted / liquidity).floor_to(0);
+            let seconds_inside_checkpoint =
+                i64::try_from(seconds_inside_checkpoint.0 / Decimal::ONE.0)
+                    .unwrap();
+
+            let (position_id, _, position_bucket) = self.mint_lp_token(
+                liquidity,
+                first_position.left_bound,
+                first_position.right_bound,
+                shape_id,
+                Some(added_at),
+                locked_until,
+                claimed_position.x_fee_checkpoint,
+                claimed_position.y_fee_checkpoint,
+                seconds_inside_checkpoint,
+            );
+            self.lp_manager.update_non_fungible_data(
+                &position_id,
+                "x_total_fee_checkpoint",
+                x_total_fee_weighted / liquidity,
+            );
+            self.lp_manager.update_non_fungible_data(
+                &position_id,
+                "y_total_fee_checkpoint",
+                y_total_fee_weighted / liquidity,
+            );
+
+            Runtime::emit_event(MergePositionsEvent {
+                position_ids: position_nfts
+                    .iter()
+                    .map(|position_nft| position_nft.local_id().clone())
+                    .collect(),
+                new_position_id: position_id,
+                liquidity,
+            });
+
+            self.burn_lp_tokens(lp_positions);
+
+            (position_bucket, x_fees, y_fees)
+        }
+
+        /// Splits a liquidity position into multiple positions with proportional liquidity.
+        ///
+        /// For every fraction a new position with the corresponding share of the liquidity is minted,
+        /// the original position keeps the remaining liquidity. All fee, total fee and seconds inside checkpoints
+        /// are copied, since they are tracked per unit of liquidity. Thereby the claimable fees are split
+        /// proportionally as well. The new positions inherit the lock of the original position.
+        /// The ticks of the pool are not affected.
+        ///
+        /// # Arguments
+        /// * `position_proof` - A non-fungible proof of the liquidity position to split.
+        /// * `fractions` - The fractions of the liquidity to move into new positions.
+        ///
+        /// # Returns
+        /// A bucket containing the LP tokens of the new positions.
+        ///
+        /// # Panics
+        /// - If the proof does not contain exactly one liquidity position of this pool.
+        /// - If any fraction is not positive or the fractions do not sum up to less than one.
+        pub fn split_position(
+            &mut self,
+            position_proof: NonFungibleProof,
+            fractions: Vec<Decimal>,
+        ) -> Bucket {
+            let position_nft = position_proof
+                .check(self.lp_manager.address())
+                .non_fungible::<LiquidityPosition>();
+            let position_id = position_nft.local_id().clone();
+            let position = position_nft.data();
+
+            assert!(
+                !fractions.is_empty(),
+                "[Split position]: At least one fraction is required."
+            );
+            let mut fractions_sum = dec!(0);
+            for fraction in &fractions {
+                assert!(
+                    *fraction > dec!(0),
+                    "[Split position]: Fractions need to be positive."
+                );
+                fractions_sum += *fraction;
+            }
+            assert!(
+                fractions_sum < dec!(1),
+                "[Split position]: The fractions need to sum up to less than one."
+            );
+
+            let mut new_positions = Bucket::new(self.lp_manager.address());
+            let mut new_position_ids = vec![];
+            let mut new_liquidities = vec![];
+            let mut remaining_liquidity = position.liquidity;
+            for fraction in fractions {
+                let liquidity = position.liquidity * fraction;
+                remaining_liquidity -= liquidity;
+                let (new_position_id, _, new_position) = self.mint_lp_token(
+                    liquidity,
+                    position.left_bound,
+                    position.right_bound,
+                    position.shape_id.clone(),
+                    Some(position.added_at),
+                    position.locked_until,
+                    position.x_fee_checkpoint,
+                    position.y_fee_checkpoint,
+                    position.seconds_inside_checkpoint,
+                );
+                self.lp_manager.update_non_fungible_data(
+                    &new_position_id,
+                    "x_total_fee_checkpoint",
+                    position.x_total_fee_checkpoint,
+                );
+                self.lp_manager.update_non_fungible_data(
+                    &new_position_id,
+                    "y_total_fee_checkpoint",
+                    position.y_total_fee_checkpoint,
+                );
+                new_positions.put(new_position);
+                new_position_ids.push(new_position_id);
+                new_liquidities.push(liquidity);
+            }
+
+            self.lp_manager.update_non_fungible_data(
+                &position_id,
+                "liquidity",
+                remaining_liquidity,
+            );
+            self.refresh_position_image(&position_id, &position);
+
+            Runtime::emit_event(SplitPositionEvent {
+                position_id,
+                remaining_liquidity,
+                new_position_ids,
+                new_liquidities,
+            });
+
+            new_positions
+        }
+
+        /// Adds multiple liquidity positions to the pool simultaneously.
+        ///
+        /// This method allows for batch processing of multiple liquidity additions, which can be more gas-efficient
+        /// and ensures atomicity of transactions. It is particularly useful for strategies involving multiple liquidity
+        /// positions across different price ranges.
+        ///
+        /// # Arguments
+        /// - `positions`: A vector of tuples, each representing a liquidity position with:
+        ///   - `left_bound`: The lower price bound for adding liquidity.
+        ///   - `right_bound`: The upper price bound for adding liquidity.
+        ///   - `x_bucket`: A bucket containing the X tokens to be added as liquidity.
+        ///   - `y_bucket`: A bucket containing the Y tokens to be added as liquidity.
+        /// - `shape_proof`: An optional non-fungible proof that allows for adding new positions to an existing shape.
+        ///
+        /// # Returns
+        /// A tuple containing:
+        /// - `Bucket`: A bucket with any remaining X tokens not added as liquidity across all positions.
+        /// - `Bucket`: A bucket with any remaining Y tokens not added as liquidity across all positions.
+        /// - `Bucket`: A bucket containing the LP tokens representing the added liquidity for all positions.
+        pub fn add_liquidity_shape(
+            &mut self,
+            positions: Vec<(i32, i32, Bucket, Bucket)>,
+            shape_proof: Option<NonFungibleProof>,
+        ) -> (Bucket, Bucket, Bucket) {
+            // Initialize output buckets for X and Y tokens using the pool's respective token addresses.
+            let mut x_output_shape = Bucket::new(self.x_address());
+            let mut y_output_shape = Bucket::new(self.y_address());
+            // Initialize an LP token bucket using the LP manager's address.
+            let mut lp_nfts = Bucket::new(self.lp_manager.address());
+            // Determine the `shape_id` based on the provided `shape_proof`, generating a new one if not provided.
+            let shape_id = match shape_proof {
+                Some(proof) => {
+                    proof
+                        .check(self.lp_manager.address())
+                        .non_fungible::<LiquidityPosition>()
+                        .data()
+                        .shape_id
+                }
+                None => {
+                    Some(NonFungibleLocalId::ruid(Runtime::generate_ruid()))
+                }
+            };
+            // Process each position, collecting resulting LP tokens and any unadded X and Y tokens.
+            for (left_bound, right_bound, x_bucket, y_bucket) in positions {
+                let (lp_nft, x_output, y_output) = self.add_liquidity_internal(
+                    left_bound,
+                    right_bound,
+                    x_bucket,
+                    y_bucket,
+                    shape_id.clone(),
+                    None,
+                    None,
+                );
+                lp_nfts.put(lp_nft);
+                x_output_shape.put(x_output);
+                y_output_shape.put(y_output);
+            }
+            // Aggregate all LP tokens and unadded tokens into their respective buckets.
+            (lp_nfts, x_output_shape, y_output_shape)
+        }
+
+        /// Adds multiple liquidity positions to the pool simultaneously, failing if the deadline has passed.
+        ///
+        /// Behaves exactly like `add_liquidity_shape` but rejects the transaction if it is executed after `deadline`.
+        ///
+        /// # Arguments
+        /// - `positions`: A vector of `(left_bound, right_bound, x_bucket, y_bucket)` tuples, see `add_liquidity_shape`.
+        /// - `shape_proof`: An optional non-fungible proof that allows for adding new positions to an existing shape.
+        /// - `deadline`: Optional Unix timestamp in seconds after which the transaction is rejected.
+        ///
+        /// # Returns
+        /// The same as `add_liquidity_shape`.
+        ///
+        /// # Panics
+        /// - If the current time is past the `deadline`.
+        pub fn add_liquidity_shape_with_deadline(
+            &mut self,
+            positions: Vec<(i32, i32, Bucket, Bucket)>,
+            shape_proof: Option<NonFungibleProof>,
+            deadline: Option<u64>,
+        ) -> (Bucket, Bucket, Bucket) {
+            assert_deadline_not_passed(deadline);
+            self.add_liquidity_shape(positions, shape_proof)
+        }
+
+        /// Adds liquidity distributed over multiple adjacent bins following a predefined shape.
+        ///
+        /// The bins have a width of `width` ticks each and are centered around the `center` tick.
+        /// Bins above the current price only receive X tokens, bins below only Y tokens and the bin containing the
+        /// current price both. Each token is split between the bins receiving it proportionally to the weights
+        /// of the `shape`. All positions are added via `add_liquidity_shape` and therefore share one `shape_id`.
+        /// Bins that would receive no liquidity are skipped.
+        ///
+        /// # Arguments
+        /// * `shape` - The shape of the distribution, see `LiquidityShape`.
+        /// * `center` - The tick around which the bins are centered.
+        /// * `width` - The width of every bin in ticks, a positive multiple of the tick spacing.
+        /// * `num_bins` - The number of bins, between one and `MAX_DISTRIBUTION_BINS`.
+        /// * `x_bucket` - A bucket containing the X tokens to be distributed.
+        /// * `y_bucket` - A bucket containing the Y tokens to be distributed.
+        ///
+        /// # Returns
+        /// The same as `add_liquidity_shape`.
+        ///
+        /// # Panics
+        /// - If `width` is not a positive multiple of the tick spacing.
+        /// - If `num_bins` is zero or larger than `MAX_DISTRIBUTION_BINS`.
+        /// - If any bin is outside the allowed tick range.
+        /// - If no bin receives liquidity.
+        pub fn add_liquidity_distribution(
+            &mut self,
+            shape: LiquidityShape,
+            center: i32,
+            width: u32,
+            num_bins: u32,
+            mut x_bucket: Bucket,
+            mut y_bucket: Bucket,
+        ) -> (Bucket, Bucket, Bucket) {
+            assert!(
+                width > 0 && width % self.tick_spacing == 0,
+                "[Add liquidity distribution]: The bin width needs to be a positive multiple of the tick spacing."
+            );
+            let weights = shape_weights(shape, num_bins);
+
+            let total_width = i64::from(width) * i64::from(num_bins);
+            let left_bound = i64::from(align_tick(center, self.tick_spacing))
+                - total_width / 2 / i64::from(self.tick_spacing)
+                    * i64::from(self.tick_spacing);
+            assert!(
+                left_bound >= i64::from(MIN_TICK)
+                    && left_bound + total_width <= i64::from(MAX_TICK),
+                "[Add liquidity distribution]: The bins exceed the allowed tick range."
+            );
+
+            // Determine for every bin which tokens it receives at the current price.
+            let bins: Vec<(i32, i32, Decimal, bool, bool)> = weights
+                .into_iter()
+                .enumerate()
+                .map(|(index, weight)| {
+                    let bin_left =
+                        (left_bound + index as i64 * i64::from(width)) as i32;
+                    let bin_right = bin_left + width as i32;
+                    let receives_x =
+                        self.price_sqrt < tick_to_price_sqrt(bin_right);
+                    let receives_y =
+                        self.price_sqrt > tick_to_price_sqrt(bin_left);
+                    (bin_left, bin_right, weight, receives_x, receives_y)
+                })
+                .collect();
+            let x_weight_total = bins
+                .iter()
+                .filter(|bin| bin.3)
+                .fold(dec!(0), |total, bin| total + bin.2);
+            let y_weight_total = bins
+                .iter()
+                .filter(|bin| bin.4)
+                .fold(dec!(0), |total, bin| total + bin.2);
+
+            let (x_total, y_total) = (x_bucket.amount(), y_bucket.amount());
+            let mut positions = vec![];
+            for (bin_left, bin_right, weight, receives_x, receives_y) in bins {
+                let x_amount: Decimal = match receives_x {
+                    true => (PreciseDecimal::from(x_total) * weight
+                        / x_weight_total)
+                        .floor_to(self.x_divisibility()),
+                    false => dec!(0),
+                };
+                let y_amount: Decimal = match receives_y {
+                    true => (PreciseDecimal::from(y_total) * weight
+                        / y_weight_total)
+                        .floor_to(self.y_divisibility()),
+                    false => dec!(0),
+                };
+                // Skip bins without liquidity, e.g. if only one of the tokens is provided.
+                if (receives_x && x_amount == dec!(0))
+                    || (receives_y && y_amount == dec!(0))
+                {
+                    continue;
+                }
+                positions.push((
+                    bin_left,
+                    bin_right,
+                    x_bucket.take(x_amount),
+                    y_bucket.take(y_amount),
+                ));
+            }
+            assert!(
+                !positions.is_empty(),
+                "[Add liquidity distribution]: No bin receives liquidity."
+            );
+
+            let (lp_nfts, x_remainder, y_remainder) =
+                self.add_liquidity_shape(positions, None);
+            x_bucket.put(x_remainder);
+            y_bucket.put(y_remainder);
+            (lp_nfts, x_bucket, y_bucket)
+        }
+
+        /// Mints a new liquidity position non-fungible token (LP NFT).
+        ///
+        /// This function is responsible for creating a new LP NFT that represents a liquidity position within the pool.
+        /// It increments the LP counter to ensure a unique identifier for each new position, encapsulates the liquidity details
+        /// in a `LiquidityPosition` struct, and mints the NFT using the pool's LP manager.
+        /// The name, description and image of the NFT are generated from the pair symbol and the price range.
+        ///
+        /// ## Arguments
+        /// - `liquidity`: The amount of liquidity being added.
+        /// - `left_bound`: The lower price bound of the liquidity position.
+        /// - `right_bound`: The upper price bound of the liquidity position.
+        /// - `shape_id`: An optional identifier for a specific liquidity shape, used for more complex liquidity strategies.
+        /// - `added_at`: An optional timestamp of when the position was added, defaults to the current time.
+        /// - `locked_until`: An optional timestamp until which the position can not be removed.
+        /// - `x_fee_checkpoint`: The checkpoint for fees collected in token X up to this point.
+        /// - `y_fee_checkpoint`: The checkpoint for fees collected in token Y up to this point.
+        /// - `seconds_inside_checkpoint`: The checkpoint for total time in seconds this position has been active within the specified price range.
+        ///
+        /// ## Returns
+        /// - `NonFungibleLocalId`: The unique identifier of the newly minted LP NFT.
+        /// - `LiquidityPosition`: The liquidity position data encapsulated in the minted NFT.
+        /// - `Bucket`: A bucket containing the newly minted LP NFT.
+        fn mint_lp_token(
+            &mut self,
+            liquidity: PreciseDecimal,
+            left_bound: i32,
+            right_bound: i32,
+            shape_id: Option<NonFungibleLocalId>,
+            added_at: Option<u64>,
+            locked_until: Option<u64>,
+            x_fee_checkpoint: PreciseDecimal,
+            y_fee_checkpoint: PreciseDecimal,
+            seconds_inside_checkpoint: i64,
+        ) -> (NonFungibleLocalId, LiquidityPosition, Bucket) {
+            // Increment the LP counter to generate a unique position ID.
+            self.lp_counter += 1;
+            let position_id = NonFungibleLocalId::Integer(
+                IntegerNonFungibleLocalId::new(self.lp_counter),
+            );
+
+            let price_left_sqrt = tick_to_price_sqrt(left_bound);
+            let price_right_sqrt = tick_to_price_sqrt(right_bound);
+            let position = LiquidityPosition {
+                name: position_name(
+                    self.pair_symbol.as_deref(),
+                    self.lp_counter,
+                ),
+                description: position_description(
+                    self.pair_symbol.as_deref(),
+                    price_left_sqrt * price_left_sqrt,
+                    price_right_sqrt * price_right_sqrt,
+                ),
+                key_image_url: self.position_image_url(left_bound, right_bound),
+                liquidity,
+                left_bound,
+                right_bound,
+                shape_id,
+                added_at: added_at.unwrap_or_else(Clock::time_in_seconds),
+                locked_until,
+                x_fee_checkpoint,
+                y_fee_checkpoint,
+                x_total_fee_checkpoint: x_fee_checkpoint,
+                y_total_fee_checkpoint: y_fee_checkpoint,
+                seconds_inside_checkpoint,
+            };
+
+            // Mint the LP NFT using the LP manager and the newly created position.
+            let position_bucket = self
+                .lp_manager
+                .mint_non_fungible(&position_id, position.clone());
+
+            if let Some(shape_id) = &position.shape_id {
+                self.insert_shape_position(shape_id, &position_id);
+            }
+
+            (position_id, position, position_bucket)
+        }
+
+        /// Renders the image of a liquidity position for the current price of the pool, see `position_image_url`.
+        ///
+        /// ## Arguments
+        /// - `left_bound`: The lower tick bound of the liquidity position.
+        /// - `right_bound`: The upper tick bound of the liquidity position.
+        ///
+        /// ## Returns
+        /// - `Url`: The SVG data URI of the image.
+        fn position_image_url(&self, left_bound: i32, right_bound: i32) -> Url {
+            let price_left_sqrt = tick_to_price_sqrt(left_bound);
+            let price_right_sqrt = tick_to_price_sqrt(right_bound);
+            position_image_url(
+                self.pair_symbol.as_deref(),
+                price_left_sqrt * price_left_sqrt,
+                price_right_sqrt * price_right_sqrt,
+                self.position_in_range(left_bound, right_bound),
+            )
+        }
+
+        /// Returns whether the current price of the pool is inside the price range of a liquidity position.
+        ///
+        /// ## Arguments
+        /// - `left_bound`: The lower tick bound of the liquidity position.
+        /// - `right_bound`: The upper tick bound of the liquidity position.
+        fn position_in_range(&self, left_bound: i32, right_bound: i32) -> bool {
+            tick_to_price_sqrt(left_bound) <= self.price_sqrt
+                && self.price_sqrt < tick_to_price_sqrt(right_bound)
+        }
 
-        /// Updates the active tick index based on the current price and given bounds.
+        /// Updates the image of a liquidity position if it no longer reflects whether the position is in range.
+        ///
+        /// The image only depends on the in-range status besides the immutable bounds, so it is only rendered and
+        /// stored again if the status changed.
         ///
-        /// Adjusts the `active_tick` of the pool to the nearest tick that is less than or equal to the current pool price.
+        /// ## Arguments
+        /// - `position_id`: The identifier of the liquidity position.
+        /// - `position`: The data of the liquidity position.
+        fn refresh_position_image(
+            &self,
+            position_id: &NonFungibleLocalId,
+            position: &LiquidityPosition,
+        ) {
+            let in_range = self
+                .position_in_range(position.left_bound, position.right_bound);
+            if position_image_in_range(&position.key_image_url) == in_range {
+                return;
+            }
+            self.lp_manager.update_non_fungible_data(
+                position_id,
+                "key_image_url",
+                self.position_image_url(
+                    position.left_bound,
+                    position.right_bound,
+                ),
+            );
+        }
+
+        /// Adds a position to the index of positions belonging to a shape.
         ///
         /// ## Arguments
-        /// - `left_bound`: The lower bound tick index for the update.
-        /// - `right_bound`: The upper bound tick index for the update.
-        /// - `price_left_sqrt`: The square root of the price corresponding to the `left_bound`.
-        /// - `price_right_sqrt`: The square root of the price corresponding to the `right_bound`.
-        fn update_active_tick(
+        /// - `shape_id`: The identifier of the shape.
+        /// - `position_id`: The identifier of the position belonging to the shape.
+        fn insert_shape_position(
             &mut self,
-            left_bound: i32,
-            right_bound: i32,
-            price_left_sqrt: PreciseDecimal,
-            price_right_sqrt: PreciseDecimal,
+            shape_id: &NonFungibleLocalId,
+            position_id: &NonFungibleLocalId,
         ) {
-            // If active tick is None we set it to the integer minimum since every valid left or right bound
-            // is a better fit than the smallest possible active tick
-            let active_tick = self.active_tick.unwrap_or(i32::MIN);
+            let shape_exists = self.shapes.get(shape_id).is_some();
+            match shape_exists {
+                true => {
+                    self.shapes
+                        .get_mut(shape_id)
+                        .unwrap()
+                        .insert(position_id.clone());
+                }
+                false => {
+                    self.shapes.insert(
+                        shape_id.clone(),
+                        IndexSet::from([position_id.clone()]),
+                    );
+                }
+            }
+        }
 
-            // Update the active tick to the right bound if the right bound is closest left of the price.
-            if active_tick < right_bound && price_right_sqrt <= self.price_sqrt
-            {
-                self.active_tick = Some(right_bound);
+        /// Removes a position from the index of positions belonging to a shape.
+        ///
+        /// The shape is removed from the index once its last position is removed.
+        ///
+        /// ## Arguments
+        /// - `shape_id`: The identifier of the shape.
+        /// - `position_id`: The identifier of the position to remove from the shape.
+        fn remove_shape_position(
+            &mut self,
+            shape_id: &NonFungibleLocalId,
+            position_id: &NonFungibleLocalId,
+        ) {
+            let shape_is_empty = match self.shapes.get_mut(shape_id) {
+                Some(mut position_ids) => {
+                    position_ids.shift_remove(position_id);
+                    position_ids.is_empty()
+                }
+                None => false,
+            };
+            if shape_is_empty {
+                self.shapes.remove(shape_id);
             }
-            // Update the active tick to the left bound if the left bound is closest left of the price.
-            else if active_tick < left_bound
-                && price_left_sqrt <= self.price_sqrt
+        }
+
+        /// Burns liquidity position NFTs and removes them from the shape index.
+        ///
+        /// ## Arguments
+        /// - `lp_positions`: A non-fungible bucket containing the liquidity position NFTs to burn.
+        fn burn_lp_tokens(&mut self, lp_positions: NonFungibleBucket) {
+            for position_nft in
+                lp_positions.non_fungibles::<LiquidityPosition>()
             {
-                self.active_tick = Some(left_bound);
+                if let Some(shape_id) = &position_nft.data().shape_id {
+                    self.remove_shape_position(
+                        shape_id,
+                        position_nft.local_id(),
+                    );
+                }
             }
+            self.lp_manager.burn(lp_positions);
         }
 
-        /// Refits the `active_tick` to the highest tick less than or equal to the current `active_tick`.
+        /// Asserts that the given positions form one complete shape and returns its identifier.
         ///
-        /// This method adjusts the `active_tick` to the last valid tick that is not greater than the current `active_tick`.
-        /// It is used to ensure that the `active_tick` remains aligned with the existing ticks after any updates or changes
-        /// that might affect the tick indices. This is especially needed after deleting a tick.
-        fn refit_active_tick(&mut self) {
-            // Set active_tick to the highest tick not greater than the current active_tick
-            self.active_tick = self
-                .ticks
-                .range_back((
-                    Bound::Unbounded,
-                    Bound::Included(self.active_tick.unwrap_or(i32::MIN)),
-                ))
-                .next()
-                .map(|(key, _, _)| key);
+        /// ## Arguments
+        /// - `positions`: The provided liquidity position NFTs.
+        /// - `context`: The context used in the panic messages.
+        ///
+        /// ## Returns
+        /// - `NonFungibleLocalId`: The identifier of the shape.
+        ///
+        /// ## Panics
+        /// - If no positions are provided.
+        /// - If any position does not belong to a shape or the positions belong to different shapes.
+        /// - If positions of the shape are missing, listing the missing position identifiers.
+        fn assert_complete_shape(
+            &self,
+            positions: &[NonFungible<LiquidityPosition>],
+            context: &str,
+        ) -> NonFungibleLocalId {
+            assert!(
+                !positions.is_empty(),
+                "[{}]: At least one position is required.",
+                context
+            );
+            let shape_id = positions[0].data().shape_id.unwrap_or_else(|| {
+                panic!(
+                    "[{}]: The position does not belong to a shape.",
+                    context
+                )
+            });
+            for position in positions {
+                assert!(
+                    position.data().shape_id.as_ref() == Some(&shape_id),
+                    "[{}]: All positions need to belong to the same shape.",
+                    context
+                );
+            }
+
+            let provided_ids: IndexSet<NonFungibleLocalId> = positions
+                .iter()
+                .map(|position| position.local_id().clone())
+                .collect();
+            let missing_ids: Vec<NonFungibleLocalId> = self
+                .shape_positions(shape_id.clone())
+                .into_iter()
+                .filter(|position_id| !provided_ids.contains(position_id))
+                .collect();
+            assert!(
+                missing_ids.is_empty(),
+                "[{}]: The positions {:?} of the shape are missing.",
+                context,
+                missing_ids
+            );
+
+            shape_id
         }
 
         /// Calculates the removable amounts of token X and token Y from the pool without removing liquidity.
@@ -951,9 +2007,44 @@ mod precision_pool {
         /// A tuple consisting of:
         /// * A bucket with the token X from the pool
         /// * A bucket with the token Y from the pool
+        ///
+        /// # Panics
+        /// * If any of the positions is still locked, see `add_liquidity_locked`.
         pub fn remove_liquidity(
             &mut self,
             lp_positions: NonFungibleBucket,
+        ) -> (Bucket, Bucket) {
+            // Synchronize with the registry, so pools without swaps still remit their protocol fees.
+            self.sync_registry();
+            self.remove_liquidity_internal(lp_positions)
+        }
+
+        /// Removes liquidity from the pool without synchronizing with the registry.
+        ///
+        /// Behaves exactly like `remove_liquidity` but never calls the registry, so liquidity providers can exit
+        /// the pool even if the registry is broken, e.g. if its `sync` method panics.
+        ///
+        /// # Arguments
+        /// * `lp_positions`: A non-fungible bucket containing the liquidity position NFTs to be removed.
+        ///
+        /// # Returns
+        /// The same as `remove_liquidity`.
+        ///
+        /// # Panics
+        /// * If any of the positions is still locked, see `add_liquidity_locked`.
+        pub fn remove_liquidity_without_sync(
+            &mut self,
+            lp_positions: NonFungibleBucket,
+        ) -> (Bucket, Bucket) {
+            self.remove_liquidity_internal(lp_positions)
+        }
+
+        /// Removes the liquidity of the given positions and returns the corresponding amounts of token X and token Y.
+        ///
+        /// See `remove_liquidity`, which additionally synchronizes with the registry.
+        fn remove_liquidity_internal(
+            &mut self,
+            lp_positions: NonFungibleBucket,
         ) -> (Bucket, Bucket) {
             // Initialize output buckets for tokens X and Y for all positions.
             let mut x_total_output = Bucket::new(self.x_address());
@@ -962,6 +2053,10 @@ mod precision_pool {
             // Iterate over each NFT in `lp_positions`:
             for nft in lp_positions.non_fungibles::<LiquidityPosition>() {
                 let position = nft.data();
+                assert_position_unlocked(
+                    position.locked_until,
+                    "Remove liquidity",
+                );
                 let price_left_sqrt = tick_to_price_sqrt(position.left_bound);
                 let price_right_sqrt = tick_to_price_sqrt(position.right_bound);
 
@@ -995,23 +2090,15 @@ mod precision_pool {
                     self.y_divisibility(),
                 );
 
-                // Update the ticks and active liquidity of the pool.
-                let left_tick = self.update_or_remove_tick(
-                    position.left_bound,
-                    -position.liquidity,
-                    -position.liquidity,
-                );
-                let right_tick = self.update_or_remove_tick(
-                    position.right_bound,
-                    position.liquidity,
-                    -position.liquidity,
-                );
-                self.update_active_liquidity(
-                    -position.liquidity,
-                    price_left_sqrt,
-                    price_right_sqrt,
-                );
-                self.refit_active_tick();
+                // Update the ticks, active liquidity and active tick of the pool.
+                let (left_tick, right_tick) =
+                    self.pool_liquidity().remove_liquidity(
+                        position.left_bound,
+                        position.right_bound,
+                        price_left_sqrt,
+                        price_right_sqrt,
+                        position.liquidity,
+                    );
 
                 // Remove the specified liquidity amounts from the pool's liquidity vaults.
                 let mut x_output = self.x_liquidity.take(x_amount);
@@ -1068,37 +2155,291 @@ mod precision_pool {
             }
 
             // Burn the NFTs representing the removed liquidity positions.
-            self.lp_manager.burn(lp_positions);
+            self.burn_lp_tokens(lp_positions);
 
             (x_total_output, y_total_output)
         }
 
+        /// Removes liquidity from the pool, failing if the deadline has passed.
+        ///
+        /// Behaves exactly like `remove_liquidity` but rejects the transaction if it is executed after `deadline`.
+        ///
+        /// # Arguments
+        /// * `lp_positions`: A non-fungible bucket containing the liquidity position NFTs to be removed.
+        /// * `deadline`: Optional Unix timestamp in seconds after which the transaction is rejected.
+        ///
+        /// # Returns
+        /// The same as `remove_liquidity`.
+        ///
+        /// # Panics
+        /// - If the current time is past the `deadline`.
+        pub fn remove_liquidity_with_deadline(
+            &mut self,
+            lp_positions: NonFungibleBucket,
+            deadline: Option<u64>,
+        ) -> (Bucket, Bucket) {
+            assert_deadline_not_passed(deadline);
+            self.remove_liquidity(lp_positions)
+        }
+
+        /// Removes liquidity from the pool with slippage protection.
+        ///
+        /// Behaves like `remove_liquidity` but rejects the transaction if the returned amounts are below the given minimums.
+        /// The checked amounts include the auto-claimed fees and therefore correspond to the values reported by
+        /// `removable_liquidity`. If remove liquidity hooks are used, the minimums should account for the minimum
+        /// removable fraction reported there.
+        ///
+        /// # Arguments
+        /// * `lp_positions`: A non-fungible bucket containing the liquidity position NFTs to be removed.
+        /// * `min_x_out`: Minimum total amount of X tokens to receive.
+        /// * `min_y_out`: Minimum total amount of Y tokens to receive.
+        /// * `deadline`: Optional Unix timestamp in seconds after which the transaction is rejected.
+        ///
+        /// # Returns
+        /// The same as `remove_liquidity`.
+        ///
+        /// # Panics
+        /// - If the current time is past the `deadline`.
+        /// - If less than `min_x_out` X tokens or `min_y_out` Y tokens are returned.
+        pub fn remove_liquidity_with_limits(
+            &mut self,
+            lp_positions: NonFungibleBucket,
+            min_x_out: Decimal,
+            min_y_out: Decimal,
+            deadline: Option<u64>,
+        ) -> (Bucket, Bucket) {
+            assert_deadline_not_passed(deadline);
+            let (x_output, y_output) = self.remove_liquidity(lp_positions);
+            assert_min_amount(
+                x_output.amount(),
+                min_x_out,
+                "Remove liquidity",
+                "X",
+            );
+            assert_min_amount(
+                y_output.amount(),
+                min_y_out,
+                "Remove liquidity",
+                "Y",
+            );
+            (x_output, y_output)
+        }
+
+        /// Removes liquidity from the pool and returns the whole value in a single token.
+        ///
+        /// The positions are removed exactly like in `remove_liquidity`, including the auto-claimed fees.
+        /// Afterwards the unwanted token is swapped through this pool, paying the regular input fee and
+        /// executing the swap hooks. Both the `RemoveLiquidityEvent` and the `SwapEvent` are emitted.
+        ///
+        /// # Arguments
+        /// * `lp_positions`: A non-fungible bucket containing the liquidity position NFTs to be removed.
+        /// * `output_address`: The address of the token to receive, either X or Y.
+        /// * `min_output`: Minimum total amount of the output token to receive.
+        ///
+        /// # Returns
+        /// A bucket containing the output token.
+        ///
+        /// # Panics
+        /// - If `output_address` is neither the X nor the Y token address.
+        /// - If the pool has not enough liquidity left to swap the unwanted token completely.
+        /// - If less than `min_output` tokens are returned.
+        pub fn remove_liquidity_to(
+            &mut self,
+            lp_positions: NonFungibleBucket,
+            output_address: ResourceAddress,
+            min_output: Decimal,
+        ) -> Bucket {
+            assert!(
+                output_address == self.x_address()
+                    || output_address == self.y_address(),
+                "[Remove liquidity]: The output address is neither the X nor the Y token address."
+            );
+
+            let (x_output, y_output) = self.remove_liquidity(lp_positions);
+            let (mut output, swap_input) =
+                match output_address == self.x_address() {
+                    true => (x_output, y_output),
+                    false => (y_output, x_output),
+                };
+
+            if !swap_input.is_empty() {
+                let (swap_output, swap_remainder) = self.swap(swap_input);
+                assert!(
+                    swap_remainder.is_empty(),
+                    "[Remove liquidity]: Not enough liquidity to swap into the output token."
+                );
+                output.put(swap_output);
+            }
+
+            assert_min_amount(
+                output.amount(),
+                min_output,
+                "Remove liquidity",
+                "output",
+            );
+            output
+        }
+
+        /// Removes all positions of a shape at once.
+        ///
+        /// Behaves like `remove_liquidity` but ensures that a shape is removed as a whole,
+        /// so that no single positions of the shape are left behind.
+        ///
+        /// # Arguments
+        /// * `lp_positions`: A non-fungible bucket containing all liquidity position NFTs of the shape.
+        ///
+        /// # Returns
+        /// The same as `remove_liquidity`.
+        ///
+        /// # Panics
+        /// - If the positions are not liquidity positions of this pool.
+        /// - If the positions do not all belong to the same shape.
+        /// - If positions of the shape are missing, listing the missing position identifiers.
+        pub fn remove_shape(
+            &mut self,
+            lp_positions: NonFungibleBucket,
+        ) -> (Bucket, Bucket) {
+            assert_eq!(
+                lp_positions.resource_address(),
+                self.lp_manager.address(),
+                "[Remove shape]: The positions are not liquidity positions of this pool."
+            );
+            self.assert_complete_shape(
+                &lp_positions.non_fungibles::<LiquidityPosition>(),
+                "Remove shape",
+            );
+            self.remove_liquidity(lp_positions)
+        }
+
         fn before_swap_state(&self, swap_type: SwapType) -> BeforeSwapState {
             BeforeSwapState {
                 pool_address: self.pool_address,
                 price_sqrt: self.price_sqrt,
                 active_liquidity: self.active_liquidity,
-                swap_type,
+                swap_type: hook_swap_type(swap_type),
                 input_fee_rate: self.input_fee_rate,
                 fee_protocol_share: self.fee_protocol_share,
             }
         }
 
-        /// Executes a swap operation within the pool, handling liquidity adjustments, fee calculations, and tick crossing logic.
-        ///
-        /// # Arguments
-        /// * `input_bucket`: A bucket containing tokens to be swapped.
-        ///
-        /// # Returns
-        /// A tuple containing:
-        /// * A bucket with the resulting tokens after the swap.
-        /// * A bucket with any remaining tokens not used in the swap.
+        /// Executes a swap operation within the pool, handling liquidity adjustments, fee calculations, and tick crossing logic.
+        ///
+        /// # Arguments
+        /// * `input_bucket`: A bucket containing tokens to be swapped.
+        ///
+        /// # Returns
+        /// A tuple containing:
+        /// * A bucket with the resulting tokens after the swap.
+        /// * A bucket with any remaining tokens not used in the swap.
+        ///
+        /// This method orchestrates the swap process, including pre-swap state setup, fee calculations,
+        /// liquidity adjustments, and post-swap state finalization. It leverages various sub-processes
+        /// such as tick processing and fee handling to ensure the swap adheres to the pool's rules and
+        /// configurations.
+        pub fn swap(&mut self, input_bucket: Bucket) -> (Bucket, Bucket) {
+            self.swap_internal(input_bucket, None, None)
+        }
+
+        /// Executes a swap which crosses at most `max_ticks_crossed` ticks.
+        ///
+        /// Behaves like `swap`, but stops cleanly once the limit is reached instead of iterating over an unbounded
+        /// number of ticks, which could exceed the cost unit limit of the transaction. The price never moves onto a
+        /// tick that can not be crossed anymore. The unused input is refunded and `partial_fill` is set in the
+        /// `SwapEvent`. Use `quote_swap` to find out how many ticks a trade would cross.
+        ///
+        /// # Arguments
+        /// * `input_bucket`: A bucket containing tokens to be swapped.
+        /// * `max_ticks_crossed`: The maximum number of ticks the swap is allowed to cross.
+        ///
+        /// # Returns
+        /// The same as `swap`.
+        pub fn swap_with_max_ticks_crossed(
+            &mut self,
+            input_bucket: Bucket,
+            max_ticks_crossed: u32,
+        ) -> (Bucket, Bucket) {
+            self.swap_internal(input_bucket, Some(max_ticks_crossed), None)
+        }
+
+        /// Executes a swap which additionally charges an interface fee for a referrer, e.g. a frontend.
+        ///
+        /// The referrer fee is taken from the input together with the input fee of the pool and accrues to a
+        /// balance of the referrer, which can be paid out via `claim_referrer_fees` or `withdraw_referrer_fees`.
+        /// A referrer fee rate of zero does not create a balance for the referrer. The referrer fee is rounded
+        /// down, charged only on the swapped part of the input and reported in the `SwapEvent`.
+        ///
+        /// # Arguments
+        /// * `input_bucket`: A bucket containing tokens to be swapped.
+        /// * `referrer`: The optional referrer address together with the referrer fee rate applied to the input.
+        ///
+        /// # Returns
+        /// The same as `swap`.
+        ///
+        /// # Panics
+        /// - If the referrer fee rate is negative or exceeds `REFERRER_FEE_RATE_MAX`.
+        pub fn swap_with_referrer(
+            &mut self,
+            input_bucket: Bucket,
+            referrer: Option<(ComponentAddress, Decimal)>,
+        ) -> (Bucket, Bucket) {
+            self.swap_internal(input_bucket, None, referrer)
+        }
+
+        /// Creates the initial state of a swap based on the current pool state.
+        ///
+        /// # Arguments
+        /// * `swap_type` - The type of the swap (`SellX` or `BuyX`).
+        /// * `input_amount` - The gross input amount including fees.
+        /// * `max_ticks_crossed` - The optional maximum number of ticks the swap is allowed to cross.
+        /// * `referrer_fee_rate` - The rate of the referrer fee applied to the `input_amount`.
+        ///
+        /// # Returns
+        /// * `SwapState` - The state before any tick has been processed.
+        fn swap_state(
+            &self,
+            swap_type: SwapType,
+            input_amount: Decimal,
+            max_ticks_crossed: Option<u32>,
+            referrer_fee_rate: Decimal,
+        ) -> SwapState {
+            SwapState::new(
+                &SwapPoolState {
+                    pool_address: self.pool_address,
+                    x_address: self.x_address(),
+                    y_address: self.y_address(),
+                    x_divisibility: self.x_divisibility(),
+                    y_divisibility: self.y_divisibility(),
+                    price_sqrt: self.price_sqrt,
+                    active_tick: self.active_tick,
+                    active_liquidity: self.active_liquidity,
+                    x_lp_fee: self.x_lp_fee,
+                    y_lp_fee: self.y_lp_fee,
+                    input_fee_rate: self.input_fee_rate,
+                    fee_protocol_share: self.fee_protocol_share,
+                    fee_collection_mode: self.fee_collection_mode,
+                    seconds_global: self.seconds_global(),
+                },
+                swap_type,
+                input_amount,
+                max_ticks_crossed,
+                referrer_fee_rate,
+            )
+        }
+
+        /// Executes a swap, optionally limiting the number of crossed ticks and charging a referrer fee.
         ///
-        /// This method orchestrates the swap process, including pre-swap state setup, fee calculations,
-        /// liquidity adjustments, and post-swap state finalization. It leverages various sub-processes
-        /// such as tick processing and fee handling to ensure the swap adheres to the pool's rules and
-        /// configurations.
-        pub fn swap(&mut self, mut input_bucket: Bucket) -> (Bucket, Bucket) {
+        /// See `swap`, `swap_with_max_ticks_crossed` and `swap_with_referrer`.
+        fn swap_internal(
+            &mut self,
+            mut input_bucket: Bucket,
+            max_ticks_crossed: Option<u32>,
+            referrer: Option<(ComponentAddress, Decimal)>,
+        ) -> (Bucket, Bucket) {
+            let referrer_fee_rate = referrer
+                .map(|(_, referrer_fee_rate)| referrer_fee_rate)
+                .unwrap_or(Decimal::ZERO);
+            assert_referrer_fee_rate_is_valid(referrer_fee_rate);
+
             // Synchronize the pool's state with the registry to collect protocol fees and update protocol fee share from time to time.
             self.sync_registry();
             let input_gross_amount = input_bucket.amount();
@@ -1117,51 +2458,13 @@ mod precision_pool {
                 "BeforeSwap",
             );
 
-            let (global_input_fee_lp, global_output_fee_lp) =
-                self.global_fees(swap_type);
-
-            /*
-            The following invariants are valid:
-                input_share + input_fee_rate = 1
-                fee_lp_share + fee_protocol_share = 1
-             */
-            let input_divisibility = self.input_divisibility(swap_type);
-            let (input_amount_net, input_fee_lp, input_fee_protocol) =
-                input_amount_net(
-                    input_bucket.amount(),
-                    self.input_fee_rate,
-                    self.fee_protocol_share,
-                    input_divisibility,
-                );
-
             // Initialize the swap state with the necessary parameters for processing the swap.
-            let mut state = SwapState {
-                pool_address: self.pool_address,
-                input_address: input_bucket.resource_address(),
-                output_address: self.output_address(swap_type),
+            let mut state = self.swap_state(
                 swap_type,
-                output: dec!(0),
-                output_divisibility: self.output_divisibility(swap_type),
-                input: dec!(0),
-                input_divisibility,
-                remainder: input_amount_net,
-                remainder_fee_lp: input_fee_lp,
-                liquidity: self.active_liquidity,
-                active_tick: self.active_tick,
-                price_sqrt: self.price_sqrt,
-                price_sqrt_sell_cache: None,
-                input_fee_rate: self.input_fee_rate,
-                fee_protocol_share: self.fee_protocol_share,
-                fee_lp_share: dec!(1) - self.fee_protocol_share,
-                input_share: dec!(1) - self.input_fee_rate,
-                fee_lp_input: dec!(0),
-                fee_protocol_input: dec!(0),
-                fee_protocol_max: input_fee_protocol,
-                global_input_fee_lp,
-                global_output_fee_lp,
-                global_seconds: self.seconds_global(),
-                crossed_ticks: vec![],
-            };
+                input_bucket.amount(),
+                max_ticks_crossed,
+                referrer_fee_rate,
+            );
 
             // Ensure that the remainder is not empty before proceeding with tick processing.
             assert!(!state.remainder_is_empty());
@@ -1176,33 +2479,62 @@ mod precision_pool {
             */
 
             // Process ticks based on the swap type, adjusting the state accordingly.
+            // The tick bitmap is used to jump to the next initialized tick, so only crossed ticks are loaded.
             match swap_type {
                 SwapType::BuyX => {
-                    self.get_next_ticks(SwapType::BuyX).for_each(
-                        |(&tick_index, tick, _next_tick_index)| {
-                            buy_step(&mut state, tick, tick_index)
-                        },
-                    );
+                    // we DONT want to include the active tick
+                    let mut tick_index = self
+                        .tick_bitmap
+                        .next_initialized_above(self.active_tick);
+                    while let Some(index) = tick_index {
+                        let mut tick = self.ticks.get_mut(&index).unwrap();
+                        if let StepControl::Break =
+                            buy_step(&mut state, &mut tick, index)
+                        {
+                            break;
+                        }
+                        tick_index = self
+                            .tick_bitmap
+                            .next_initialized_above(Some(index));
+                    }
                 }
                 SwapType::SellX => {
-                    self.get_next_ticks(SwapType::SellX).for_each(
-                        |(&_tick_index, tick, next_tick_index)| {
-                            sell_step(&mut state, tick, next_tick_index)
-                        },
-                    );
+                    // we DO want to include the active tick
+                    let mut tick_index = self
+                        .tick_bitmap
+                        .next_initialized_at_or_below(self.active_tick);
+                    while let Some(index) = tick_index {
+                        let next_tick_index = self
+                            .tick_bitmap
+                            .next_initialized_at_or_below(Some(index - 1));
+                        let mut tick = self.ticks.get_mut(&index).unwrap();
+                        if let StepControl::Break =
+                            sell_step(&mut state, &mut tick, next_tick_index)
+                        {
+                            break;
+                        }
+                        tick_index = next_tick_index;
+                    }
                 }
             }
 
-            // Finalize the swap by taking protocol fees and updating the pool's state.
+            // Finalize the swap by taking protocol, referrer and output fees and updating the pool's state.
             state.take_protocol_fees();
+            state.take_referrer_fees();
+            state.take_output_fees();
             self.active_liquidity = state.liquidity;
             self.active_tick = state.active_tick;
             self.price_sqrt = state.price_sqrt;
 
-            let (mut output_bucket, input_bucket) =
-                self.swap_deposit_and_withdraw(state.clone(), input_bucket);
+            let referrer = referrer.map(|(referrer, _)| referrer);
+            let (mut output_bucket, input_bucket) = self
+                .swap_deposit_and_withdraw(
+                    state.clone(),
+                    input_bucket,
+                    referrer,
+                );
 
-            let mut after_state = AfterSwapState::from(state.clone());
+            let mut after_state = after_swap_state(&state);
             (after_state, output_bucket) = self.execute_hooks(
                 HookCall::AfterSwap,
                 (after_state, output_bucket),
@@ -1229,17 +2561,160 @@ mod precision_pool {
                 output_return_amount: output_bucket.amount(),
                 input_fee_lp: state.fee_lp_input,
                 input_fee_protocol: state.fee_protocol_input,
+                input_fee_referrer: state.fee_referrer_input,
+                referrer,
+                output_fee_lp: state.fee_lp_output,
+                output_fee_protocol: state.fee_protocol_output,
                 price_sqrt: self.price_sqrt,
                 active_liquidity: self.active_liquidity,
                 active_tick: self.active_tick,
                 global_x_fee_lp: self.x_lp_fee,
                 global_y_fee_lp: self.y_lp_fee,
                 crossed_ticks: state.crossed_ticks,
+                partial_fill: state.partial_fill,
             });
 
             (output_bucket, input_bucket)
         }
 
+        /// Simulates a swap without changing the state of the pool.
+        ///
+        /// The tick processing is identical to `swap` and `swap_with_max_ticks_crossed`. Swap hooks are not
+        /// executed, so hooks adjusting the input fee rate or the buckets are not reflected in the quote.
+        ///
+        /// # Arguments
+        /// * `input_address`: The address of the token to be swapped.
+        /// * `input_amount`: The gross amount of the token to be swapped, including fees.
+        /// * `max_ticks_crossed`: The optional maximum number of ticks the swap is allowed to cross.
+        ///
+        /// # Returns
+        /// * `SwapQuote` - The expected amounts, the price afterwards and the number of crossed ticks.
+        ///
+        /// # Panics
+        /// - If `input_address` is neither the X nor the Y token address.
+        /// - If `input_amount` is not positive.
+        pub fn quote_swap(
+            &self,
+            input_address: ResourceAddress,
+            input_amount: Decimal,
+            max_ticks_crossed: Option<u32>,
+        ) -> SwapQuote {
+            self.quote_swap_with_referrer(
+                input_address,
+                input_amount,
+                max_ticks_crossed,
+                dec!(0),
+            )
+        }
+
+        /// Simulates a swap with a referrer fee without changing the state of the pool.
+        ///
+        /// Behaves like `quote_swap`, additionally taking the referrer fee like `swap_with_referrer`.
+        ///
+        /// # Arguments
+        /// * `input_address`: The address of the token to be swapped.
+        /// * `input_amount`: The gross amount of the token to be swapped, including fees.
+        /// * `max_ticks_crossed`: The optional maximum number of ticks the swap is allowed to cross.
+        /// * `referrer_fee_rate`: The rate of the referrer fee applied to the `input_amount`.
+        ///
+        /// # Returns
+        /// * `SwapQuote` - The same as `quote_swap` including the referrer fee.
+        ///
+        /// # Panics
+        /// - If `input_address` is neither the X nor the Y token address.
+        /// - If `input_amount` is not positive.
+        /// - If the referrer fee rate is negative or exceeds `REFERRER_FEE_RATE_MAX`.
+        pub fn quote_swap_with_referrer(
+            &self,
+            input_address: ResourceAddress,
+            input_amount: Decimal,
+            max_ticks_crossed: Option<u32>,
+            referrer_fee_rate: Decimal,
+        ) -> SwapQuote {
+            assert_referrer_fee_rate_is_valid(referrer_fee_rate);
+            assert!(
+                input_address == self.x_address()
+                    || input_address == self.y_address(),
+                "[Quote swap]: The input address is neither the X nor the Y token address."
+            );
+            let swap_type = self.swap_type(input_address);
+            let mut state = self.swap_state(
+                swap_type,
+                input_amount,
+                max_ticks_crossed,
+                referrer_fee_rate,
+            );
+            assert!(
+                !state.remainder_is_empty(),
+                "[Quote swap]: The input amount needs to be positive."
+            );
+
+            // Process copies of the ticks, so the pool state stays untouched.
+            match swap_type {
+                SwapType::BuyX => {
+                    let mut tick_index = self
+                        .tick_bitmap
+                        .next_initialized_above(self.active_tick);
+                    while let Some(index) = tick_index {
+                        let mut tick = self.ticks.get(&index).unwrap().clone();
+                        if let StepControl::Break =
+                            buy_step(&mut state, &mut tick, index)
+                        {
+                            break;
+                        }
+                        tick_index = self
+                            .tick_bitmap
+                            .next_initialized_above(Some(index));
+                    }
+                }
+                SwapType::SellX => {
+                    let mut tick_index = self
+                        .tick_bitmap
+                        .next_initialized_at_or_below(self.active_tick);
+                    while let Some(index) = tick_index {
+                        let next_tick_index = self
+                            .tick_bitmap
+                            .next_initialized_at_or_below(Some(index - 1));
+                        let mut tick = self.ticks.get(&index).unwrap().clone();
+                        if let StepControl::Break =
+                            sell_step(&mut state, &mut tick, next_tick_index)
+                        {
+                            break;
+                        }
+                        tick_index = next_tick_index;
+                    }
+                }
+            }
+            state.take_protocol_fees();
+            state.take_referrer_fees();
+            state.take_output_fees();
+
+            state.quote(input_amount)
+        }
+
+        /// Executes a swap, failing if the deadline has passed.
+        ///
+        /// Behaves exactly like `swap` but protects the trader against the transaction being executed
+        /// later than intended at a potentially worse price.
+        ///
+        /// # Arguments
+        /// * `input_bucket`: A bucket containing tokens to be swapped.
+        /// * `deadline`: Optional Unix timestamp in seconds after which the transaction is rejected.
+        ///
+        /// # Returns
+        /// The same as `swap`.
+        ///
+        /// # Panics
+        /// - If the current time is past the `deadline`.
+        pub fn swap_with_deadline(
+            &mut self,
+            input_bucket: Bucket,
+            deadline: Option<u64>,
+        ) -> (Bucket, Bucket) {
+            assert_deadline_not_passed(deadline);
+            self.swap(input_bucket)
+        }
+
         /// Retrieve the tick spacing of this pool
         ///
         /// # Returns
@@ -1282,27 +2757,134 @@ mod precision_pool {
         ///
         /// If the current time is less than `next_sync_time`, the function exits early to throttle the frequency of updates,
         /// which helps in reducing unnecessary computations and state changes.
+        ///
+        /// Besides swaps, the sync is also triggered by `remove_liquidity`, `flash_loan` and `repay_loan`, so pools
+        /// without swaps still remit their protocol fees. `remove_liquidity_without_sync` never synchronizes, so
+        /// liquidity providers can always exit the pool, independent of the registry. Protocol fees can also be
+        /// remitted at any time via `collect_protocol_fees`.
         pub fn sync_registry(&mut self) {
             // Check if the current time exceeds `next_sync_time` to prevent too frequent updates.
             if Clock::time_in_seconds() < self.next_sync_time {
                 return;
             }
+            self.sync_registry_internal();
+        }
+
+        /// Sends the collected protocol fees to the registry immediately, without waiting for `next_sync_time`.
+        ///
+        /// This allows to remit the protocol fees of inactive pools, which would otherwise stay in the pool until
+        /// the next swap after `next_sync_time`. Like `sync_registry` this also updates the protocol fee share and
+        /// the next sync time. The method is permissionless, since the fees can only be sent to the registry.
+        pub fn collect_protocol_fees(&mut self) {
+            self.sync_registry_internal();
+        }
+
+        /// Returns the protocol fees collected by this pool which have not been sent to the registry yet.
+        ///
+        /// # Returns
+        /// - `IndexMap<ResourceAddress, Decimal>` - A map containing the resource addresses and their corresponding protocol fees.
+        pub fn protocol_fees(&self) -> IndexMap<ResourceAddress, Decimal> {
+            IndexMap::from([
+                (self.x_address(), self.x_protocol_fee.amount()),
+                (self.y_address(), self.y_protocol_fee.amount()),
+            ])
+        }
+
+        /// Hands the pool over to a successor registry.
+        ///
+        /// Can only be called by the current registry, which authorizes the migration by calling this method itself.
+        /// The protocol fees collected but not yet sent are remitted to the new registry, which also sets the protocol
+        /// fee share, at the next synchronization. The next sync time is reset, so the next swap synchronizes with the
+        /// new registry immediately. The `registry` metadata is updated and a `RegistryChangedEvent` is emitted.
+        ///
+        /// # Arguments
+        /// * `new_registry_address` - The address of the successor registry.
+        ///
+        /// # Panics
+        /// - If the caller is not the current registry.
+        /// - If `new_registry_address` is the current registry.
+        pub fn migrate_registry(
+            &mut self,
+            new_registry_address: ComponentAddress,
+        ) {
+            let previous_registry_address = self.registry.address();
+            Runtime::assert_access_rule(rule!(require(global_caller(
+                previous_registry_address
+            ))));
+            assert_ne!(
+                new_registry_address, previous_registry_address,
+                "[Migrate registry]: The new registry is the current registry."
+            );
+
+            self.registry = new_registry_address.into();
+            self.next_sync_time = 0;
+            Runtime::global_component()
+                .set_metadata("registry", new_registry_address);
+
+            Runtime::emit_event(RegistryChangedEvent {
+                pool_address: self.pool_address,
+                previous_registry_address,
+                registry_address: new_registry_address,
+            });
+        }
+
+        /// Sends the collected protocol fees to the registry and updates the protocol fee share and the next sync time.
+        ///
+        /// The response of the registry is decoded defensively, so invalid responses do not make the pool untradeable:
+        /// a protocol fee share outside of [0, `FEE_PROTOCOL_SHARE_MAX`] is clamped and a response which cannot be
+        /// decoded is ignored, keeping the protocol fee share unchanged and retrying the sync after
+        /// `REGISTRY_SYNC_RETRY_INTERVAL`. The next sync time is at least `REGISTRY_SYNC_INTERVAL_MIN` in the future,
+        /// so a next sync time in the past does not make every swap call the registry.
+        ///
+        /// A registry whose `sync` panics can not be handled, since failed component calls revert the whole
+        /// transaction: once `next_sync_time` has passed, swaps, flash loans and `remove_liquidity` revert until the
+        /// registry is fixed. The pool has no owner which could replace the registry, so the recovery is up to the
+        /// registry operator, who either fixes the registry or lets it hand the pool over via `migrate_registry`.
+        /// In the meantime liquidity providers can exit via `remove_liquidity_without_sync`.
+        ///
+        /// See `sync_registry` and `collect_protocol_fees`.
+        fn sync_registry_internal(&mut self) {
+            let x_protocol_fee = self.x_protocol_fee.take_all();
+            let y_protocol_fee = self.y_protocol_fee.take_all();
+            let x_amount = x_protocol_fee.amount();
+            let y_amount = y_protocol_fee.amount();
+            let previous_fee_protocol_share = self.fee_protocol_share;
+            let now = Clock::time_in_seconds();
 
             // Calls the `sync` method on the registry component, passing the current pool address and the total protocol fees collected since the last sync.
-            let (fee_protocol_share, next_sync_time) =
-                self.registry
-                    .call::<(ComponentAddress, Bucket, Bucket), (Decimal, u64)>(
-                        "sync",
-                        &(
-                            self.pool_address,
-                            self.x_protocol_fee.take_all(),
-                            self.y_protocol_fee.take_all(),
-                        ),
-                    );
+            let output = self
+                .registry
+                .call::<(ComponentAddress, Bucket, Bucket), ScryptoValue>(
+                    "sync",
+                    &(self.pool_address, x_protocol_fee, y_protocol_fee),
+                );
+            let response = scrypto_encode(&output).ok().and_then(|bytes| {
+                scrypto_decode::<(Decimal, u64)>(&bytes).ok()
+            });
 
             // Updates the pool's state with the new protocol fee share and the next allowed sync time.
-            self.set_fee_protocol_share(fee_protocol_share);
-            self.next_sync_time = next_sync_time;
+            let registry_response_valid = match response {
+                Some((fee_protocol_share, next_sync_time)) => {
+                    self.set_fee_protocol_share(fee_protocol_share);
+                    self.next_sync_time =
+                        next_sync_time.max(now + REGISTRY_SYNC_INTERVAL_MIN);
+                    self.fee_protocol_share == fee_protocol_share
+                }
+                None => {
+                    self.next_sync_time = now + REGISTRY_SYNC_RETRY_INTERVAL;
+                    false
+                }
+            };
+
+            Runtime::emit_event(SyncRegistryEvent {
+                registry_address: self.registry.address(),
+                x_amount,
+                y_amount,
+                previous_fee_protocol_share,
+                fee_protocol_share: self.fee_protocol_share,
+                next_sync_time: self.next_sync_time,
+                registry_response_valid,
+            });
         }
 
         /// Returns the next scheduled synchronization time with the registry.
@@ -1383,12 +2965,18 @@ mod precision_pool {
                 position.right_bound,
             );
 
-            let x_amount = ((new_x_fee_checkpoint - position.x_fee_checkpoint)
-                * position.liquidity)
-                .floor_to(self.x_divisibility());
-            let y_amount = ((new_y_fee_checkpoint - position.y_fee_checkpoint)
-                * position.liquidity)
-                .floor_to(self.y_divisibility());
+            let x_amount = earned_fees(
+                position.liquidity,
+                position.x_fee_checkpoint,
+                new_x_fee_checkpoint,
+                self.x_divisibility(),
+            );
+            let y_amount = earned_fees(
+                position.liquidity,
+                position.y_fee_checkpoint,
+                new_y_fee_checkpoint,
+                self.y_divisibility(),
+            );
 
             (
                 x_amount,
@@ -1429,11 +3017,119 @@ mod precision_pool {
             ])
         }
 
+        /// Returns the referrer fees accrued for the given referrer which have not been claimed yet.
+        ///
+        /// # Arguments
+        /// * `referrer` - The address of the referrer passed to `swap_with_referrer`.
+        ///
+        /// # Returns
+        /// - `IndexMap<ResourceAddress, Decimal>` - A map containing the resource addresses and their corresponding claimable referrer fees.
+        pub fn referrer_fees(
+            &self,
+            referrer: ComponentAddress,
+        ) -> IndexMap<ResourceAddress, Decimal> {
+            let (x_fees, y_fees) = match self.referrer_fees.get(&referrer) {
+                Some(vaults) => (vaults.0.amount(), vaults.1.amount()),
+                None => (dec!(0), dec!(0)),
+            };
+            IndexMap::from([
+                (self.x_address(), x_fees),
+                (self.y_address(), y_fees),
+            ])
+        }
+
+        /// Pays out the accrued referrer fees to the referrer.
+        ///
+        /// The fees are always deposited into the referrer account via `try_deposit_batch_or_abort`, therefore
+        /// anyone can trigger the payout. Only accounts are paid out this way, other referrer components withdraw
+        /// their fees via `withdraw_referrer_fees` instead.
+        ///
+        /// # Arguments
+        /// * `referrer` - The address of the referrer passed to `swap_with_referrer`.
+        ///
+        /// # Panics
+        /// - If the `referrer` is not an account.
+        /// - If no referrer fees have been accrued for the `referrer`.
+        /// - If the referrer rejects the deposit.
+        pub fn claim_referrer_fees(&mut self, referrer: ComponentAddress) {
+            assert!(
+                referrer
+                    .as_node_id()
+                    .entity_type()
+                    .is_some_and(|entity_type| entity_type.is_global_account()),
+                "[Claim referrer fees]: Referrer is not an account, use withdraw_referrer_fees instead."
+            );
+            let (x_fees, y_fees) =
+                self.take_all_referrer_fees(referrer, "Claim referrer fees");
+            let mut referrer_account: Global<Account> = referrer.into();
+            referrer_account
+                .try_deposit_batch_or_abort(vec![x_fees, y_fees], None);
+        }
+
+        /// Withdraws the accrued referrer fees and returns them to the caller.
+        ///
+        /// Only the referrer itself can withdraw its fees: either the referrer component calls this method, or the
+        /// caller presents the owner badge of the referrer account.
+        ///
+        /// # Arguments
+        /// * `referrer` - The address of the referrer passed to `swap_with_referrer`.
+        ///
+        /// # Returns
+        /// A tuple containing:
+        /// * A bucket with the referrer fees in token X.
+        /// * A bucket with the referrer fees in token Y.
+        ///
+        /// # Panics
+        /// - If the caller is neither the referrer nor presents the owner badge of the referrer account.
+        /// - If no referrer fees have been accrued for the `referrer`.
+        pub fn withdraw_referrer_fees(
+            &mut self,
+            referrer: ComponentAddress,
+        ) -> (Bucket, Bucket) {
+            Runtime::assert_access_rule(referrer_owner_rule(referrer));
+            self.take_all_referrer_fees(referrer, "Withdraw referrer fees")
+        }
+
+        /// Takes all accrued referrer fees of the referrer out of its vaults and emits a `ClaimReferrerFeesEvent`.
+        ///
+        /// # Arguments
+        /// * `referrer` - The address of the referrer passed to `swap_with_referrer`.
+        /// * `context` - Name of the operation used as prefix in the error message.
+        ///
+        /// # Panics
+        /// If no referrer fees have been accrued for the `referrer`.
+        fn take_all_referrer_fees(
+            &mut self,
+            referrer: ComponentAddress,
+            context: &str,
+        ) -> (Bucket, Bucket) {
+            let (x_fees, y_fees) = {
+                let mut vaults = self
+                    .referrer_fees
+                    .get_mut(&referrer)
+                    .unwrap_or_else(|| {
+                        panic!(
+                            "[{}]: No referrer fees accrued for this referrer.",
+                            context
+                        )
+                    });
+                (vaults.0.take_all(), vaults.1.take_all())
+            };
+
+            Runtime::emit_event(ClaimReferrerFeesEvent {
+                referrer,
+                x_amount: x_fees.amount(),
+                y_amount: y_fees.amount(),
+            });
+            (x_fees, y_fees)
+        }
+
         /// Claims the accumulated fees for a given liquidity position.
         ///
         /// This method calculates and distributes the fees accrued in a specific liquidity position represented by an NFT.
         /// It updates the fee checkpoints for both x and y tokens based on the current state of the pool and the position's bounds.
         /// The method ensures that the fee distribution is accurate by considering the fees outside the position's range and the current active tick.
+        /// The image of the position is refreshed to reflect the current price of the pool.
         ///
         /// # Arguments
         /// * `position_nft` - A reference to the non-fungible token representing the liquidity position.
@@ -1469,6 +3165,7 @@ mod precision_pool {
                 "y_fee_checkpoint",
                 new_y_fee_checkpoint,
             );
+            self.refresh_position_image(position_id, &position);
 
             Runtime::emit_event(ClaimFeesEvent {
                 position_id: position_nft.local_id().clone(),
@@ -1510,6 +3207,182 @@ mod precision_pool {
             (x_fees, y_fees)
         }
 
+        /// Claims the accumulated fees for all positions of a shape.
+        ///
+        /// Behaves like `claim_fees` but ensures that the proof contains every position of the shape,
+        /// so that the fees of a shape are always claimed as a whole.
+        ///
+        /// # Arguments
+        /// * `shape_proof` - NonFungibleProof containing all liquidity positions of the shape.
+        ///
+        /// # Returns
+        /// The same as `claim_fees`.
+        ///
+        /// # Panics
+        /// - If the positions do not all belong to the same shape.
+        /// - If positions of the shape are missing, listing the missing position identifiers.
+        pub fn claim_shape_fees(
+            &mut self,
+            shape_proof: NonFungibleProof,
+        ) -> (Bucket, Bucket) {
+            let position_nfts = shape_proof
+                .check(self.lp_manager.address())
+                .non_fungibles::<LiquidityPosition>();
+            self.assert_complete_shape(&position_nfts, "Claim shape fees");
+
+            let mut x_fees = Bucket::new(self.x_address());
+            let mut y_fees = Bucket::new(self.y_address());
+            for position_nft in &position_nfts {
+                let (x_claimed, y_claimed) =
+                    self.claim_fees_internal(position_nft);
+                x_fees.put(x_claimed);
+                y_fees.put(y_claimed);
+            }
+            (x_fees, y_fees)
+        }
+
+        /// Reinvests the accumulated fees of a liquidity position into the same position.
+        ///
+        /// The claimable fees are claimed and as much of them as possible is added as liquidity to the range
+        /// of the position. Instead of minting a new LP NFT, the liquidity of the existing position is increased.
+        /// The total fee checkpoints are adjusted such that `total_fees` keeps reporting the same amount for the
+        /// position. The add liquidity hooks are executed like for `add_liquidity`, with the identifier of the position
+        /// in their state. Since no position is minted, the after add liquidity hooks receive an empty position bucket,
+        /// which they have to return empty.
+        ///
+        /// # Arguments
+        /// * `position_proof` - A non-fungible proof of the liquidity position whose fees are compounded.
+        ///
+        /// # Returns
+        /// A tuple containing two `Bucket`s:
+        /// * The first `Bucket` contains the x token fees that could not be added as liquidity.
+        /// * The second `Bucket` contains the y token fees that could not be added as liquidity.
+        ///
+        /// # Panics
+        /// - If the proof does not contain exactly one liquidity position of this pool.
+        /// - If the hooks took more tokens than the allowed limit or left the liquidity to be added at zero.
+        /// - If the after add liquidity hooks return a non-empty position bucket.
+        pub fn compound_fees(
+            &mut self,
+            position_proof: NonFungibleProof,
+        ) -> (Bucket, Bucket) {
+            let position_nft = position_proof
+                .check(self.lp_manager.address())
+                .non_fungible::<LiquidityPosition>();
+            let position_id = position_nft.local_id().clone();
+
+            // Claim the fees first, this updates the fee checkpoints of the position to the current ones.
+            let (mut x_fees, mut y_fees) =
+                self.claim_fees_internal(&position_nft);
+            let position: LiquidityPosition =
+                self.lp_manager.get_non_fungible_data(&position_id);
+
+            let price_left_sqrt = tick_to_price_sqrt(position.left_bound);
+            let price_right_sqrt = tick_to_price_sqrt(position.right_bound);
+            let addable_liquidity = |x_amount, y_amount| {
+                addable_amounts(
+                    x_amount,
+                    self.x_divisibility(),
+                    y_amount,
+                    self.y_divisibility(),
+                    self.price_sqrt,
+                    price_left_sqrt,
+                    price_right_sqrt,
+                )
+            };
+
+            // Fees too small to add any liquidity are returned without executing hooks, since nothing is added.
+            let (liquidity, _, _) =
+                addable_liquidity(x_fees.amount(), y_fees.amount());
+            if liquidity <= pdec!(0) {
+                return (x_fees, y_fees);
+            }
+
+            let position_type = LiquidityPositionType {
+                left_bound: position.left_bound,
+                right_bound: position.right_bound,
+                position_id: Some(position_id.clone()),
+                shape_id: position.shape_id.clone(),
+            };
+            let (x_provided, y_provided) = (x_fees.amount(), y_fees.amount());
+            (x_fees, y_fees) = self.execute_before_add_liquidity_hooks(
+                position_type.clone(),
+                x_fees,
+                y_fees,
+            );
+            let (liquidity, x_amount, y_amount) =
+                addable_liquidity(x_fees.amount(), y_fees.amount());
+            assert_ne!(
+                liquidity,
+                pdec!(0),
+                "[Compound fees]: Allowed liquidity is zero."
+            );
+
+            self.x_liquidity.put(x_fees.take(x_amount));
+            self.y_liquidity.put(y_fees.take(y_amount));
+
+            // The ticks of the position exist already, so only their liquidity and the active liquidity change.
+            let (left_tick, right_tick) = self.pool_liquidity().add_liquidity(
+                position.left_bound,
+                position.right_bound,
+                price_left_sqrt,
+                price_right_sqrt,
+                liquidity,
+            );
+
+            // The fee checkpoints are equal to the current ones after claiming, so the added liquidity does not
+            // earn past fees. The total fee checkpoints are moved such that the total fees stay the same.
+            let new_liquidity = position.liquidity + liquidity;
+            let x_total_fee_checkpoint = position.x_fee_checkpoint
+                - (position.x_fee_checkpoint - position.x_total_fee_checkpoint)
+                    * position.liquidity
+                    / new_liquidity;
+            let y_total_fee_checkpoint = position.y_fee_checkpoint
+                - (position.y_fee_checkpoint - position.y_total_fee_checkpoint)
+                    * position.liquidity
+                    / new_liquidity;
+            self.lp_manager.update_non_fungible_data(
+                &position_id,
+                "liquidity",
+                new_liquidity,
+            );
+            self.lp_manager.update_non_fungible_data(
+                &position_id,
+                "x_total_fee_checkpoint",
+                x_total_fee_checkpoint,
+            );
+            self.lp_manager.update_non_fungible_data(
+                &position_id,
+                "y_total_fee_checkpoint",
+                y_total_fee_checkpoint,
+            );
+
+            // The position stays with its owner, so the hooks receive an empty position bucket.
+            self.execute_after_add_liquidity_hooks(
+                position_type,
+                x_amount,
+                y_amount,
+                liquidity,
+                Bucket::new(self.lp_manager.address()),
+            )
+            .drop_empty();
+
+            Runtime::emit_event(AddLiquidityEvent {
+                position_id: position_id.clone(),
+                position: self.lp_manager.get_non_fungible_data(&position_id),
+                left_tick,
+                right_tick,
+                x_amount,
+                y_amount,
+                x_gross_amount: x_provided - x_fees.amount(), // can be negative if the before add liquidity hook puts x into the bucket
+                y_gross_amount: y_provided - y_fees.amount(), // can be negative if the before add liquidity hook puts y into the bucket
+                active_liquidity: self.active_liquidity,
+                active_tick: self.active_tick,
+            });
+
+            (x_fees, y_fees)
+        }
+
         /// Calculates the number of seconds a liquidity position has been active being in range.
         ///
         /// # Arguments
@@ -1546,6 +3419,21 @@ mod precision_pool {
             (seconds_inside_now - lp_position.seconds_inside_checkpoint) as u64
         }
 
+        /// Returns the time until which a liquidity position is locked.
+        ///
+        /// # Arguments
+        /// * `nft_id` - The identifier for the non-fungible token representing the liquidity position.
+        ///
+        /// # Returns
+        /// * `Option<u64>` - The Unix timestamp in seconds until which the position can not be removed,
+        ///   `None` if the position was never locked or its lock has already ended.
+        pub fn locked_until(&self, nft_id: NonFungibleLocalId) -> Option<u64> {
+            self.lp_manager
+                .get_non_fungible_data::<LiquidityPosition>(&nft_id)
+                .locked_until
+                .filter(|locked_until| *locked_until > Clock::time_in_seconds())
+        }
+
         /// Calculates the total fees accrued for a given liquidity position in both `x` and `y` tokens.
         ///
         /// This method computes the fees by determining the fee checkpoints for both `x` and `y` tokens
@@ -1628,6 +3516,43 @@ mod precision_pool {
             ])
         }
 
+        /// Returns the identifiers of all existing positions belonging to a shape.
+        ///
+        /// # Arguments
+        /// * `shape_id` - The identifier of the shape.
+        ///
+        /// # Returns
+        /// - `IndexSet<NonFungibleLocalId>` - The position identifiers in the order they were minted,
+        ///   empty if the shape does not exist.
+        pub fn shape_positions(
+            &self,
+            shape_id: NonFungibleLocalId,
+        ) -> IndexSet<NonFungibleLocalId> {
+            self.shapes
+                .get(&shape_id)
+                .map(|position_ids| position_ids.clone())
+                .unwrap_or_default()
+        }
+
+        /// Returns the aggregated value of all positions belonging to a shape.
+        ///
+        /// The value is the sum of the removable token amounts and the claimable fees of all positions,
+        /// see `removable_liquidity`.
+        ///
+        /// # Arguments
+        /// * `shape_id` - The identifier of the shape.
+        ///
+        /// # Returns
+        /// - `IndexMap<ResourceAddress, Decimal>` - A map containing the resource addresses and their corresponding amounts.
+        pub fn shape_value(
+            &self,
+            shape_id: NonFungibleLocalId,
+        ) -> IndexMap<ResourceAddress, Decimal> {
+            let position_ids =
+                self.shape_positions(shape_id).into_iter().collect();
+            self.removable_liquidity(position_ids).0
+        }
+
         /// Executes a swap, handling deposits and withdrawals based on the swap type.
         ///
         /// This function first aggregates the input tokens with liquidity provider fees and then deposits
@@ -1646,6 +3571,7 @@ mod precision_pool {
             &mut self,
             state: SwapState,
             mut input_bucket: Bucket,
+            referrer: Option<ComponentAddress>,
         ) -> (Bucket, Bucket) {
             // Take input net and fee from the input bucket which afterwards gets returned as remainder.
             let input_net = input_bucket.take(state.input);
@@ -1654,27 +3580,42 @@ mod precision_pool {
             self.deposit_protocol_fees(
                 input_bucket.take(state.fee_protocol_input),
             );
+            // Accrue referrer fees to the balance of the referrer.
+            if let Some(referrer) = referrer {
+                self.deposit_referrer_fees(
+                    referrer,
+                    input_bucket.take(state.fee_referrer_input),
+                );
+            }
+
+            // Withdraw the output including the output fees, which are zero if the fee is taken from the input.
+            let output_gross =
+                state.output + state.fee_lp_output + state.fee_protocol_output;
+            let mut output = match state.swap_type {
+                SwapType::BuyX => self.x_liquidity.take(output_gross),
+                SwapType::SellX => self.y_liquidity.take(output_gross),
+            };
+            let output_fee_lp = output.take(state.fee_lp_output);
+            self.deposit_protocol_fees(output.take(state.fee_protocol_output));
 
             match state.swap_type {
                 SwapType::BuyX => {
-                    // Withdraw the specified output amount from the `x` liquidity vault.
-                    let output = self.x_liquidity.take(state.output);
-
-                    // Deposit the input net and fee tokens into the vaults and update the global fee count.
+                    // Deposit the input net and fee tokens into the vaults and update the global fee counts.
                     self.y_liquidity.put(input_net);
                     self.y_fees.put(fee_lp);
+                    self.x_fees.put(output_fee_lp);
                     self.y_lp_fee = state.global_input_fee_lp;
+                    self.x_lp_fee = state.global_output_fee_lp;
 
                     (output, input_bucket)
                 }
                 SwapType::SellX => {
-                    // Withdraw the specified output amount from the `y` liquidity vault.
-                    let output = self.y_liquidity.take(state.output);
-
-                    // Deposit the input net and fee tokens into the vaults and update the global fee count.
+                    // Deposit the input net and fee tokens into the vaults and update the global fee counts.
                     self.x_liquidity.put(input_net);
                     self.x_fees.put(fee_lp);
+                    self.y_fees.put(output_fee_lp);
                     self.x_lp_fee = state.global_input_fee_lp;
+                    self.y_lp_fee = state.global_output_fee_lp;
 
                     (output, input_bucket)
                 }
@@ -1695,20 +3636,6 @@ mod precision_pool {
             SwapType::BuyX
         }
 
-        /// Returns the output token address based on the swap type.
-        ///
-        /// # Arguments
-        /// * `swap_type` - The type of the swap (BuyX or SellX).
-        ///
-        /// # Returns
-        /// * `ResourceAddress` - The address of the output token.
-        fn output_address(&self, swap_type: SwapType) -> ResourceAddress {
-            match swap_type {
-                SwapType::BuyX => self.x_address(),
-                SwapType::SellX => self.y_address(),
-            }
-        }
-
         /// Retrieves the divisibility of the input token based on the swap type.
         ///
         /// # Arguments
@@ -1723,41 +3650,6 @@ mod precision_pool {
             }
         }
 
-        /// Retrieves the divisibility of the output token based on the swap type.
-        ///
-        /// # Arguments
-        /// * `swap_type` - The type of the swap (BuyX or SellX).
-        ///
-        /// # Returns
-        /// * `u8` - The divisibility of the output token.
-        fn output_divisibility(&self, swap_type: SwapType) -> u8 {
-            match swap_type {
-                SwapType::BuyX => self.x_divisibility(),
-                SwapType::SellX => self.y_divisibility(),
-            }
-        }
-
-        /// Provides the global fees associated with the swap type.
-        ///
-        /// This method returns a tuple of liquidity provider fees for both tokens involved in the swap.
-        /// For BuyX, it returns the fees for token `y` (input) and token `x` (output).
-        /// For SellX, it returns the fees for token `x` (input) and token `y` (output).
-        ///
-        /// # Arguments
-        /// * `swap_type` - The type of the swap (BuyX or SellX).
-        ///
-        /// # Returns
-        /// * `(PreciseDecimal, PreciseDecimal)` - The global liquidity provider fees for the input and output tokens.
-        fn global_fees(
-            &self,
-            swap_type: SwapType,
-        ) -> (PreciseDecimal, PreciseDecimal) {
-            match swap_type {
-                SwapType::BuyX => (self.y_lp_fee, self.x_lp_fee),
-                SwapType::SellX => (self.x_lp_fee, self.y_lp_fee),
-            }
-        }
-
         /// This should not be used in isolation to judge the merit of a trade,
         /// but be used in combination with the liquidity distribution
         /// along the different price intervals.
@@ -1789,6 +3681,14 @@ mod precision_pool {
             self.fee_protocol_share
         }
 
+        /// Retrieves the token in which the swap fees of this pool are collected.
+        ///
+        /// # Returns
+        /// * The fee collection mode set at instantiation
+        pub fn fee_collection_mode(&self) -> FeeCollectionMode {
+            self.fee_collection_mode
+        }
+
         /// Retrieve the flash loan fee rate.
         /// This fee is set during pool instantiation and is immutable.
         ///
@@ -1874,128 +3774,28 @@ mod precision_pool {
         ///
         /// # Returns
         /// * `u64` - The number of seconds elapsed since the pool's instantiation.
-        fn seconds_global(&mut self) -> u64 {
+        fn seconds_global(&self) -> u64 {
             Clock::time_in_seconds() - self.instantiated_at
         }
 
-        /// Updates an existing tick or inserts a new one based on the provided parameters.
-        ///
-        /// This method is crucial for maintaining the state of liquidity at different price levels (ticks) within the pool.
-        /// It adjusts the liquidity by adding the `delta_liquidity` to the current liquidity of the tick.
-        /// If the tick does not exist, it creates a new tick with the given parameters.
-        ///
-        /// # Arguments
-        /// * `tick_index` - The index of the tick to update or insert.
-        /// * `delta_liquidity` - The change in liquidity to apply to the tick.
-        /// * `total_liquidity` - The new total liquidity after applying the change.
-        ///
-        /// # Returns
-        /// * `Tick` - The updated or newly created tick.
-        ///
-        /// # Panics
-        /// Panics if the resulting total liquidity exceeds the maximum allowed liquidity per tick.
-        fn update_or_insert_tick(
-            &mut self,
-            tick_index: i32,
-            delta_liquidity: PreciseDecimal,
-            total_liquidity: PreciseDecimal,
-        ) -> Tick {
-            if let Some(mut tick) = self.ticks.get_mut(&tick_index) {
-                (*tick).delta_liquidity += delta_liquidity;
-                (*tick).total_liquidity += total_liquidity;
-                assert!(
-                    tick.total_liquidity <= self.max_liquidity_per_tick,
-                    "Cannot add more total liquidity to the tick than allowed by max_liquidity_per_tick"
-                );
-                return (*tick).clone();
-            }
-
-            let price_sqrt = tick_to_price_sqrt(tick_index);
-            let (x_fee_outside, y_fee_outside, seconds_outside) =
-                if self.price_sqrt < price_sqrt {
-                    (pdec!(0), pdec!(0), 0)
-                } else {
-                    (self.x_lp_fee, self.y_lp_fee, self.seconds_global())
-                };
-
-            let tick = Tick {
-                index: tick_index,
-                delta_liquidity,
-                total_liquidity,
-                price_sqrt,
-                x_fee_outside,
-                y_fee_outside,
-                seconds_outside,
-            };
-
-            assert!(
-                tick.total_liquidity <= self.max_liquidity_per_tick,
-                "Cannot add more total liquidity to the tick than allowed by max_liquidity_per_tick"
-            );
-
-            self.ticks.insert(tick_index, tick.clone());
-            tick
-        }
-
-        /// Updates or removes a tick based on the liquidity changes.
-        ///
-        /// This method adjusts the liquidity of a tick and removes the tick if its total liquidity reaches zero.
-        /// It is essential for correctly managing the removal of liquidity from the pool and ensuring that empty ticks are cleaned up.
-        ///
-        /// # Arguments
-        /// * `tick_index` - The index of the tick to update or remove.
-        /// * `delta_liquidity` - The change in liquidity to apply.
-        /// * `total_liquidity` - The new total liquidity after applying the change.
-        ///
-        /// # Returns
-        /// * `Tick` - The updated tick, or the removed tick if its total liquidity is zero.
-        fn update_or_remove_tick(
-            &mut self,
-            tick_index: i32,
-            delta_liquidity: PreciseDecimal,
-            total_liquidity: PreciseDecimal,
-        ) -> Tick {
-            let tick = self.update_or_insert_tick(
-                tick_index,
-                delta_liquidity,
-                total_liquidity,
-            );
-            if tick.total_liquidity == pdec!(0) {
-                return self.ticks.remove(&tick_index).unwrap();
-            }
-            tick
-        }
-
-        /// Retrieves an iterator over the ticks for a given swap type.
-        ///
-        /// This method provides access to the ticks that are relevant for a particular swap operation.
-        /// Depending on the swap type, it either includes (in case of `SellX`) or excludes (in case of `BuyX`)
-        /// the active tick in the range.
-        ///
-        /// # Arguments
-        /// * `swap_type` - The type of swap operation (`SellX` or `BuyX`).
+        /// Provides the part of the pool's state which is changed by adding and removing liquidity.
         ///
         /// # Returns
-        /// * `NodeIteratorMut<'_, i32, Tick>` - An iterator over the ticks within the specified range.
-        fn get_next_ticks(
-            &mut self,
-            swap_type: SwapType,
-        ) -> NodeIteratorMut<'_, i32, Tick> {
-            match swap_type {
-                SwapType::SellX => {
-                    // we DO want to include the active tick
-                    self.ticks.range_back_mut((
-                        Bound::Unbounded,
-                        Bound::Included(self.active_tick.unwrap_or(i32::MIN)),
-                    ))
-                }
-                SwapType::BuyX => {
-                    // we DONT want to include the active tick
-                    self.ticks.range_mut((
-                        Bound::Excluded(self.active_tick.unwrap_or(i32::MIN)),
-                        Bound::Unbounded,
-                    ))
-                }
+        /// * `PoolLiquidity` - The ticks, active liquidity and active tick of the pool.
+        fn pool_liquidity(&mut self) -> PoolLiquidity<'_, PoolTicks<'_>> {
+            let seconds_global = self.seconds_global();
+            PoolLiquidity {
+                ticks: PoolTicks {
+                    ticks: &mut self.ticks,
+                    tick_bitmap: &mut self.tick_bitmap,
+                },
+                active_liquidity: &mut self.active_liquidity,
+                active_tick: &mut self.active_tick,
+                price_sqrt: self.price_sqrt,
+                x_lp_fee: self.x_lp_fee,
+                y_lp_fee: self.y_lp_fee,
+                seconds_global,
+                max_liquidity_per_tick: self.max_liquidity_per_tick,
             }
         }
 
@@ -2018,6 +3818,9 @@ mod precision_pool {
             address: ResourceAddress,
             loan_amount: Decimal,
         ) -> (Bucket, Bucket) {
+            // Synchronize with the registry to remit the protocol fees from time to time.
+            self.sync_registry();
+
             // Determines the divisibility of the token to ensure the loan amount respects the token's smallest unit.
             let divisibility = ResourceManager::from_address(address)
                 .resource_type()
@@ -2118,6 +3921,9 @@ mod precision_pool {
             // Burn the loan terms NFT to officially close the loan.
             self.flash_manager.burn(loan_terms);
 
+            // Synchronize with the registry to remit the flash loan fees from time to time, now that the fee is deposited.
+            self.sync_registry();
+
             // Return any excess tokens to the caller.
             loan_repayment
         }
@@ -2137,6 +3943,40 @@ mod precision_pool {
             }
         }
 
+        /// Deposits referrer fees into the vaults of the given referrer, creating them on the first deposit.
+        /// Empty fees are dropped without creating vaults, so swaps without referrer fees can not grow the map of
+        /// referrer balances.
+        ///
+        /// # Arguments
+        /// * `referrer`: The address of the referrer the fees are accrued for.
+        /// * `referrer_fees`: A `Bucket` containing the referrer fees taken from the swap input.
+        fn deposit_referrer_fees(
+            &mut self,
+            referrer: ComponentAddress,
+            referrer_fees: Bucket,
+        ) {
+            if referrer_fees.is_empty() {
+                referrer_fees.drop_empty();
+                return;
+            }
+            if self.referrer_fees.get(&referrer).is_none() {
+                self.referrer_fees.insert(
+                    referrer,
+                    (
+                        Vault::new(self.x_address()),
+                        Vault::new(self.y_address()),
+                    ),
+                );
+            }
+            let x_address = self.x_address();
+            let mut vaults = self.referrer_fees.get_mut(&referrer).unwrap();
+            if referrer_fees.resource_address() == x_address {
+                vaults.0.put(referrer_fees);
+            } else {
+                vaults.1.put(referrer_fees);
+            }
+        }
+
         /// Executes predefined hooks based on the lifecycle event of the pool.
         ///
         /// This method applies custom logic at different stages of the pool's lifecycle,
@@ -2206,14 +4046,30 @@ mod precision_pool {
                 .map(|hook| hook.to_owned())
         }
 
+        /// Returns the symbols of the pool tokens joined by a slash, e.g. `XRD/USDC`.
+        ///
+        /// # Arguments
+        /// * `x_address` - The resource address of the first asset in the pool.
+        /// * `y_address` - The resource address of the second asset in the pool.
+        ///
+        /// # Returns
+        /// The pair symbol or `None` if any of the tokens has no symbol.
+        fn pair_symbol(
+            x_address: ResourceAddress,
+            y_address: ResourceAddress,
+        ) -> Option<String> {
+            token_symbol(x_address)
+                .zip(token_symbol(y_address))
+                .map(|(x, y)| format!("{}/{}", x, y))
+        }
+
         /// Generates names and descriptions for the pool and LP tokens.
         ///
         /// This function constructs the names and descriptions for the pool and its associated LP tokens
-        /// based on the symbols of the provided resource addresses.
+        /// based on the pair symbol of the pool.
         ///
         /// # Arguments
-        /// * `x_address` - The resource address of the first asset in the pool.
-        /// * `y_address` - The resource address of the second asset in the pool.
+        /// * `pair_symbol` - The symbols of the pool tokens, see `pair_symbol`.
         ///
         /// # Returns
         /// A tuple containing:
@@ -2221,15 +4077,9 @@ mod precision_pool {
         /// - `lp_name`: The name of the LP token.
         /// - `lp_description`: The description of the LP token.
         fn names_and_lp_description(
-            x_address: ResourceAddress,
-            y_address: ResourceAddress,
+            pair_symbol: Option<String>,
         ) -> (String, String, String) {
-            let x_symbol = token_symbol(x_address);
-            let y_symbol = token_symbol(y_address);
-            let (pool_name, lp_name, lp_description) = match x_symbol
-                .zip(y_symbol)
-                .map(|(x, y)| format!("{}/{}", x, y))
-            {
+            let (pool_name, lp_name, lp_description) = match pair_symbol {
                 Some(pair_symbol) => (
                     format!("Ociswap Precision Pool {}", pair_symbol)
                         .to_owned(),
@@ -2390,91 +4240,253 @@ mod precision_pool {
         pub fn last_observation_index(&self) -> Option<u16> {
             self.oracle.last_observation_index()
         }
+
+        /// Exports the complete state of the pool, except the ticks, in a versioned format.
+        ///
+        /// The snapshot allows analytics to read the pool state at any point in time instead of rebuilding it
+        /// by replaying all events since the `InstantiateEvent`. The ticks are exported separately via
+        /// `snapshot_ticks`, since their number is unbounded.
+        ///
+        /// # Returns
+        /// * `PoolSnapshot` - The versioned snapshot of the pool state, see `PoolSnapshotV1` for the fields.
+        pub fn snapshot(&self) -> PoolSnapshot {
+            PoolSnapshot::V1(PoolSnapshotV1 {
+                pool_address: self.pool_address,
+                x_address: self.x_address(),
+                y_address: self.y_address(),
+                lp_address: self.lp_address(),
+                flash_loan_address: self.flash_loan_address(),
+                x_divisibility: self.x_divisibility(),
+                y_divisibility: self.y_divisibility(),
+                tick_spacing: self.tick_spacing,
+                max_liquidity_per_tick: self.max_liquidity_per_tick,
+                price_sqrt: self.price_sqrt,
+                active_tick: self.active_tick,
+                active_liquidity: self.active_liquidity,
+                x_lp_fee: self.x_lp_fee,
+                y_lp_fee: self.y_lp_fee,
+                x_liquidity: self.x_liquidity.amount(),
+                y_liquidity: self.y_liquidity.amount(),
+                x_fees: self.x_fees.amount(),
+                y_fees: self.y_fees.amount(),
+                x_protocol_fee: self.x_protocol_fee.amount(),
+                y_protocol_fee: self.y_protocol_fee.amount(),
+                input_fee_rate: self.input_fee_rate,
+                fee_protocol_share: self.fee_protocol_share,
+                fee_collection_mode: self.fee_collection_mode,
+                flash_loan_fee_rate: self.flash_loan_fee_rate,
+                registry_address: self.registry.address(),
+                next_sync_time: self.next_sync_time,
+                hooks: self
+                    .hooks
+                    .iter()
+                    .map(|((package_address, blueprint_name), hook)| {
+                        (
+                            *package_address,
+                            blueprint_name.clone(),
+                            hook.address(),
+                        )
+                    })
+                    .collect(),
+                lp_counter: self.lp_counter,
+                instantiated_at: self.instantiated_at,
+                observations_limit: self.oracle.observations_limit(),
+                observations_stored: self.oracle.observations_stored(),
+                oldest_observation_at: self.oracle.oldest_observation_at(),
+                last_observation_index: self.oracle.last_observation_index(),
+            })
+        }
+
+        /// Exports the initialized ticks of the pool in ascending order, one page at a time.
+        ///
+        /// To read all ticks, start with `cursor` set to `None` and pass the returned `next_cursor` until it is `None`.
+        ///
+        /// # Arguments
+        /// * `cursor` - The tick index after which the page starts, or `None` to start at the lowest tick.
+        /// * `limit` - The maximum number of ticks in the page, at most `SNAPSHOT_TICKS_LIMIT_MAX`.
+        ///
+        /// # Returns
+        /// * `TicksSnapshot` - The versioned page of ticks, see `TicksSnapshotV1` for the fields.
+        ///
+        /// # Panics
+        /// If `limit` is zero or exceeds `SNAPSHOT_TICKS_LIMIT_MAX`.
+        pub fn snapshot_ticks(
+            &self,
+            cursor: Option<i32>,
+            limit: u32,
+        ) -> TicksSnapshot {
+            assert!(
+                limit > 0 && limit <= SNAPSHOT_TICKS_LIMIT_MAX,
+                "[Snapshot ticks]: The limit needs to be between 1 and {}.",
+                SNAPSHOT_TICKS_LIMIT_MAX
+            );
+            let start = cursor.map_or(Bound::Unbounded, Bound::Excluded);
+            let mut ticks: Vec<TickSnapshotV1> = self
+                .ticks
+                .range((start, Bound::Unbounded))
+                .take(limit as usize + 1)
+                .map(|(_, tick, _)| TickSnapshotV1 {
+                    index: tick.index,
+                    delta_liquidity: tick.delta_liquidity,
+                    total_liquidity: tick.total_liquidity,
+                    price_sqrt: tick.price_sqrt,
+                    x_fee_outside: tick.x_fee_outside,
+                    y_fee_outside: tick.y_fee_outside,
+                    seconds_outside: tick.seconds_outside,
+                })
+                .collect();
+            // The additional tick only signals that another page follows.
+            let next_cursor = if ticks.len() > limit as usize {
+                ticks.truncate(limit as usize);
+                ticks.last().map(|tick| tick.index)
+            } else {
+                None
+            };
+            TicksSnapshot::V1(TicksSnapshotV1 { ticks, next_cursor })
+        }
+
+        /// Recomputes what the pool owes to all live positions and compares it with its internal state.
+        ///
+        /// Sums the removable amounts and claimable fees over all positions which have not been burned and
+        /// recomputes the liquidity of every tick from the positions referencing it. The costs grow with the
+        /// number of positions ever minted, so the method is meant for tests and transaction previews.
+        ///
+        /// # Returns
+        /// * `PoolAudit` - The recomputed values next to the vault balances, see `is_solvent` and `is_consistent`.
+        pub fn audit(&self) -> PoolAudit {
+            let mut audit = PoolAudit {
+                positions: 0,
+                x_removable: dec!(0),
+                y_removable: dec!(0),
+                x_claimable_fees: dec!(0),
+                y_claimable_fees: dec!(0),
+                x_liquidity: self.x_liquidity.amount(),
+                y_liquidity: self.y_liquidity.amount(),
+                x_fees: self.x_fees.amount(),
+                y_fees: self.y_fees.amount(),
+                active_liquidity: self.active_liquidity,
+                active_liquidity_expected: pdec!(0),
+                inconsistent_ticks: vec![],
+            };
+
+            // Expected delta and total liquidity per tick derived from the positions.
+            let mut expected_ticks: IndexMap<
+                i32,
+                (PreciseDecimal, PreciseDecimal),
+            > = index_map_new();
+            for id in 1..=self.lp_counter {
+                let position_id = NonFungibleLocalId::integer(id);
+                if !self.lp_manager.non_fungible_exists(&position_id) {
+                    continue;
+                }
+                let position: LiquidityPosition =
+                    self.lp_manager.get_non_fungible_data(&position_id);
+                audit.positions += 1;
+
+                let (x_amount, y_amount) = removable_amounts(
+                    position.liquidity,
+                    self.price_sqrt,
+                    tick_to_price_sqrt(position.left_bound),
+                    tick_to_price_sqrt(position.right_bound),
+                    self.x_divisibility(),
+                    self.y_divisibility(),
+                );
+                let (x_fees, y_fees, _, _) =
+                    self.claimable_fees_internal(&position);
+                audit.x_removable += x_amount;
+                audit.y_removable += y_amount;
+                audit.x_claimable_fees += x_fees;
+                audit.y_claimable_fees += y_fees;
+
+                let left_tick = expected_ticks
+                    .entry(position.left_bound)
+                    .or_insert((pdec!(0), pdec!(0)));
+                left_tick.0 += position.liquidity;
+                left_tick.1 += position.liquidity;
+                let right_tick = expected_ticks
+                    .entry(position.right_bound)
+                    .or_insert((pdec!(0), pdec!(0)));
+                right_tick.0 -= position.liquidity;
+                right_tick.1 += position.liquidity;
+            }
+
+            let active_tick = self.active_tick.unwrap_or(i32::MIN);
+            for (index, tick, _) in
+                self.ticks.range((Bound::Unbounded, Bound::Unbounded))
+            {
+                let expected = expected_ticks.shift_remove(&index);
+                if expected
+                    != Some((tick.delta_liquidity, tick.total_liquidity))
+                {
+                    audit.inconsistent_ticks.push(index);
+                }
+                if index <= active_tick {
+                    audit.active_liquidity_expected += tick.delta_liquidity;
+                }
+            }
+            // Ticks referenced by positions but missing in the pool.
+            audit.inconsistent_ticks.extend(expected_ticks.keys());
+
+            audit
+        }
     }
 }
 
 #[derive(ScryptoSbor, NonFungibleData, Clone)]
 pub struct LiquidityPosition {
+    pub name: String,
+    pub description: String,
+    #[mutable]
+    pub key_image_url: Url,
+    #[mutable]
     pub liquidity: PreciseDecimal,
     pub left_bound: i32,
     pub right_bound: i32,
     pub shape_id: Option<NonFungibleLocalId>,
     pub added_at: u64,
+    pub locked_until: Option<u64>,
     #[mutable]
     x_fee_checkpoint: PreciseDecimal,
     #[mutable]
     y_fee_checkpoint: PreciseDecimal,
+    #[mutable]
     x_total_fee_checkpoint: PreciseDecimal,
+    #[mutable]
     y_total_fee_checkpoint: PreciseDecimal,
     seconds_inside_checkpoint: i64,
 }
 
-#[derive(ScryptoSbor, Clone, Debug)]
-pub struct Tick {
-    pub index: i32,
-    pub delta_liquidity: PreciseDecimal,
-    pub total_liquidity: PreciseDecimal,
-    pub price_sqrt: PreciseDecimal,
-    pub x_fee_outside: PreciseDecimal,
-    pub y_fee_outside: PreciseDecimal,
-    pub seconds_outside: u64,
-}
-
-#[derive(ScryptoSbor, Clone, Debug)]
-pub struct TickOutside {
-    pub index: i32,
-    pub x_fee: PreciseDecimal,
-    pub y_fee: PreciseDecimal,
-    pub seconds: u64,
+/// Result of `audit`, comparing the obligations of the pool towards all live positions with its state.
+#[derive(ScryptoSbor, Clone, Debug, PartialEq)]
+pub struct PoolAudit {
+    pub positions: u64,
+    pub x_removable: Decimal,
+    pub y_removable: Decimal,
+    pub x_claimable_fees: Decimal,
+    pub y_claimable_fees: Decimal,
+    pub x_liquidity: Decimal,
+    pub y_liquidity: Decimal,
+    pub x_fees: Decimal,
+    pub y_fees: Decimal,
+    pub active_liquidity: PreciseDecimal,
+    pub active_liquidity_expected: PreciseDecimal,
+    pub inconsistent_ticks: Vec<i32>,
 }
 
-impl Tick {
-    /// Returns the fees accumulated outside the current tick for a given swap type.
-    ///
-    /// Returns accumulated fees outside the current tick's range based on the swap type.
-    /// The returned tuple contains fees for the bought and sold tokens respectively.
-    ///
-    /// # Arguments
-    /// * `swap_type` - The type of swap (BuyX or SellX) which determines the direction of trade.
-    ///
-    /// # Returns
-    /// A tuple containing the fee amounts for the tokens involved in the swap outside the current tick.
-    pub fn fee_outside(
-        &self,
-        swap_type: SwapType,
-    ) -> (PreciseDecimal, PreciseDecimal) {
-        match swap_type {
-            SwapType::BuyX => (self.y_fee_outside, self.x_fee_outside),
-            SwapType::SellX => (self.x_fee_outside, self.y_fee_outside),
-        }
+impl PoolAudit {
+    /// Whether the liquidity and fee vaults cover the removable amounts and claimable fees of all positions.
+    pub fn is_solvent(&self) -> bool {
+        self.x_removable <= self.x_liquidity
+            && self.y_removable <= self.y_liquidity
+            && self.x_claimable_fees <= self.x_fees
+            && self.y_claimable_fees <= self.y_fees
     }
 
-    /// Updates the tick's outside fee and time values based on global accumulations.
-    ///
-    /// Updates the tick's outside fee values and time by subtracting the stored values from the global accumulations.
-    ///
-    /// # Arguments
-    /// * `fee_x_global` - The global accumulated fee for token X.
-    /// * `fee_y_global` - The global accumulated fee for token Y.
-    /// * `seconds_global` - The global accumulated seconds outside this tick.
-    ///
-    /// # Returns
-    /// A `TickOutside` instance containing the updated outside values for this tick.
-    pub fn update_outside_values(
-        &mut self,
-        fee_x_global: PreciseDecimal,
-        fee_y_global: PreciseDecimal,
-        seconds_global: u64,
-    ) -> TickOutside {
-        self.x_fee_outside = fee_x_global - self.x_fee_outside;
-        self.y_fee_outside = fee_y_global - self.y_fee_outside;
-        self.seconds_outside = seconds_global - self.seconds_outside;
-        TickOutside {
-            index: self.index,
-            x_fee: self.x_fee_outside,
-            y_fee: self.y_fee_outside,
-            seconds: self.seconds_outside,
-        }
+    /// Whether the ticks match the positions and the active liquidity equals the sum of `delta_liquidity`
+    /// of all ticks up to the active tick.
+    pub fn is_consistent(&self) -> bool {
+        self.inconsistent_ticks.is_empty()
+            && self.active_liquidity == self.active_liquidity_expected
     }
 }
 
@@ -2487,6 +4499,7 @@ struct InstantiateEvent {
     price_sqrt: PreciseDecimal,
     tick_spacing: u32,
     input_fee_rate: Decimal,
+    fee_collection_mode: FeeCollectionMode,
     flash_loan_address: ResourceAddress,
     flash_loan_fee_rate: Decimal,
     registry_address: ComponentAddress,
@@ -2530,6 +4543,13 @@ struct ClaimFeesEvent {
     y_amount: Decimal,
 }
 
+#[derive(ScryptoSbor, ScryptoEvent)]
+struct ClaimReferrerFeesEvent {
+    referrer: ComponentAddress,
+    x_amount: Decimal,
+    y_amount: Decimal,
+}
+
 #[derive(ScryptoSbor, ScryptoEvent)]
 struct SwapEvent {
     input_address: ResourceAddress,
@@ -2537,6 +4557,10 @@ struct SwapEvent {
     input_gross_amount: Decimal,
     input_fee_lp: Decimal,
     input_fee_protocol: Decimal,
+    input_fee_referrer: Decimal,
+    referrer: Option<ComponentAddress>,
+    output_fee_lp: Decimal,
+    output_fee_protocol: Decimal,
     output_address: ResourceAddress,
     output_amount: Decimal,
     output_return_amount: Decimal,
@@ -2546,6 +4570,25 @@ struct SwapEvent {
     global_x_fee_lp: PreciseDecimal,
     global_y_fee_lp: PreciseDecimal,
     crossed_ticks: Vec<TickOutside>,
+    partial_fill: bool,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent)]
+struct SyncRegistryEvent {
+    registry_address: ComponentAddress,
+    x_amount: Decimal,
+    y_amount: Decimal,
+    previous_fee_protocol_share: Decimal,
+    fee_protocol_share: Decimal,
+    next_sync_time: u64,
+    registry_response_valid: bool,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent)]
+struct RegistryChangedEvent {
+    pool_address: ComponentAddress,
+    previous_registry_address: ComponentAddress,
+    registry_address: ComponentAddress,
 }
 
 #[derive(ScryptoSbor, ScryptoEvent)]
@@ -2555,9 +4598,70 @@ struct FlashLoanEvent {
     fee: Decimal,
 }
 
+#[derive(ScryptoSbor, ScryptoEvent)]
+struct MergePositionsEvent {
+    position_ids: Vec<NonFungibleLocalId>,
+    new_position_id: NonFungibleLocalId,
+    liquidity: PreciseDecimal,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent)]
+struct SplitPositionEvent {
+    position_id: NonFungibleLocalId,
+    remaining_liquidity: PreciseDecimal,
+    new_position_ids: Vec<NonFungibleLocalId>,
+    new_liquidities: Vec<PreciseDecimal>,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent)]
+struct RepositionEvent {
+    old_position_id: NonFungibleLocalId,
+    new_position_id: NonFungibleLocalId,
+    old_left_bound: i32,
+    old_right_bound: i32,
+    new_left_bound: i32,
+    new_right_bound: i32,
+    x_remainder: Decimal,
+    y_remainder: Decimal,
+}
+
 #[derive(ScryptoSbor, NonFungibleData)]
 pub struct FlashLoan {
     pub address: ResourceAddress,
     pub due_amount: Decimal,
     pub fee: Decimal,
 }
+
+/// The ticks of a pool stored in its `AvlTree` together with its `TickBitmap`, see `TickStorage`.
+///
+/// Keeps the bitmap in sync whenever a tick is initialized or removed.
+struct PoolTicks<'a> {
+    ticks: &'a mut AvlTree<i32, Tick>,
+    tick_bitmap: &'a mut TickBitmap,
+}
+
+impl TickStorage for PoolTicks<'_> {
+    fn update_tick(
+        &mut self,
+        index: i32,
+        update: impl FnOnce(&mut Tick),
+    ) -> Option<Tick> {
+        let mut tick = self.ticks.get_mut(&index)?;
+        update(&mut *tick);
+        Some((*tick).clone())
+    }
+
+    fn insert_tick(&mut self, tick: Tick) {
+        self.tick_bitmap.set(tick.index);
+        self.ticks.insert(tick.index, tick);
+    }
+
+    fn remove_tick(&mut self, index: i32) {
+        self.tick_bitmap.clear(index);
+        self.ticks.remove(&index);
+    }
+
+    fn tick_at_or_below(&self, index: i32) -> Option<i32> {
+        self.tick_bitmap.next_initialized_at_or_below(Some(index))
+    }
+}
@@ -0,0 +1,231 @@
+use scrypto::prelude::*;
+
+/// Number of significant digits the prices of a position are displayed with.
+const PRICE_SIGNIFICANT_DIGITS: usize = 6;
+
+/// Status text of a position whose price range contains the current price of the pool.
+const STATUS_IN_RANGE: &str = "In range";
+
+/// Returns the human-readable name of a liquidity position, e.g. `Ociswap LP XRD/USDC #12`.
+///
+/// # Arguments
+/// * `pair_symbol` - The symbols of the pool tokens, e.g. `XRD/USDC`, `None` if a token has no symbol
+/// * `position_number` - The integer identifier of the position
+pub fn position_name(pair_symbol: Option<&str>, position_number: u64) -> String {
+    match pair_symbol {
+        Some(pair_symbol) => format!("Ociswap LP {} #{}", pair_symbol, position_number),
+        None => format!("Ociswap LP #{}", position_number),
+    }
+}
+
+/// Returns the human-readable description of a liquidity position including its price range.
+///
+/// # Arguments
+/// * `pair_symbol` - The symbols of the pool tokens, `None` if a token has no symbol
+/// * `price_left` - The lower price (not square root) of the position
+/// * `price_right` - The upper price (not square root) of the position
+pub fn position_description(
+    pair_symbol: Option<&str>,
+    price_left: PreciseDecimal,
+    price_right: PreciseDecimal,
+) -> String {
+    let pool_name = match pair_symbol {
+        Some(pair_symbol) => format!("Ociswap Precision Pool {}", pair_symbol),
+        None => "Ociswap Precision Pool".to_owned(),
+    };
+    format!(
+        "Liquidity position of the {} between the prices {} and {}.",
+        pool_name,
+        format_price(price_left),
+        format_price(price_right)
+    )
+}
+
+/// Renders the image of a liquidity position as SVG data URI.
+///
+/// The image shows the pair symbols, the price range and whether the price of the pool was inside the range
+/// at the time of rendering. Since the image is stored per position, the markup is kept minimal: the styles
+/// are shared via a stylesheet and only the dynamic values are rendered as text.
+///
+/// # Arguments
+/// * `pair_symbol` - The symbols of the pool tokens, `None` if a token has no symbol
+/// * `price_left` - The lower price (not square root) of the position
+/// * `price_right` - The upper price (not square root) of the position
+/// * `in_range` - Whether the current price of the pool is inside the price range of the position
+pub fn position_image_url(
+    pair_symbol: Option<&str>,
+    price_left: PreciseDecimal,
+    price_right: PreciseDecimal,
+    in_range: bool,
+) -> Url {
+    let (status, status_color) = match in_range {
+        true => (STATUS_IN_RANGE, "#22c55e"),
+        false => ("Out of range", "#f59e0b"),
+    };
+    let svg = format!(
+        "<svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 400 400'>\
+         <style>text{{font:24px monospace;fill:#fff}}.l{{font-size:16px;fill:#94a3b8}}</style>\
+         <rect width='400' height='400' rx='32' fill='#0f172a'/>\
+         <text x='32' y='72'>{}</text>\
+         <text x='32' y='176' class='l'>Min price</text>\
+         <text x='32' y='208'>{}</text>\
+         <text x='32' y='256' class='l'>Max price</text>\
+         <text x='32' y='288'>{}</text>\
+         <text x='32' y='352' style='fill:{}'>{}</text>\
+         </svg>",
+        escape_xml(pair_symbol.unwrap_or("LP Position")),
+        format_price(price_left),
+        format_price(price_right),
+        status_color,
+        status
+    );
+    Url::of(format!("data:image/svg+xml,{}", percent_encode(&svg)))
+}
+
+/// Returns whether the image of a position was rendered with the price of the pool inside its range.
+///
+/// Allows to only render and store a new image if the in-range status of a position changed.
+///
+/// # Arguments
+/// * `url` - The image of the position rendered by `position_image_url`
+pub fn position_image_in_range(url: &Url) -> bool {
+    // Symbols are escaped, so the status text enclosed in tags can only stem from the status itself.
+    url.as_str()
+        .contains(&percent_encode(&format!(">{}<", STATUS_IN_RANGE)))
+}
+
+/// Formats a price truncated to `PRICE_SIGNIFICANT_DIGITS` significant digits.
+///
+/// Integer digits are never truncated, e.g. `1234567.89` is displayed as `1234567`.
+///
+/// # Arguments
+/// * `price` - The price (not square root) to format
+pub fn format_price(price: PreciseDecimal) -> String {
+    let price = price.to_string();
+    let (integer, fraction) = price.split_once('.').unwrap_or((&price, ""));
+    let fraction_digits = match integer {
+        "0" => {
+            let leading_zeros = fraction.len() - fraction.trim_start_matches('0').len();
+            leading_zeros + PRICE_SIGNIFICANT_DIGITS
+        }
+        _ => PRICE_SIGNIFICANT_DIGITS.saturating_sub(integer.len()),
+    };
+    let fraction = fraction[..fraction.len().min(fraction_digits)].trim_end_matches('0');
+    match fraction.is_empty() {
+        true => integer.to_owned(),
+        false => format!("{}.{}", integer, fraction),
+    }
+}
+
+/// Escapes the characters with a special meaning in XML, since token symbols are arbitrary metadata.
+fn escape_xml(text: &str) -> String {
+    text.chars()
+        .map(|character| match character {
+            '&' => "&amp;".to_owned(),
+            '<' => "&lt;".to_owned(),
+            '>' => "&gt;".to_owned(),
+            '\'' => "&apos;".to_owned(),
+            '"' => "&quot;".to_owned(),
+            _ => character.to_string(),
+        })
+        .collect()
+}
+
+/// Percent-encodes all bytes which are not safe to use unencoded in a data URI.
+fn percent_encode(text: &str) -> String {
+    text.bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z'
+            | b'a'..=b'z'
+            | b'0'..=b'9'
+            | b'-'
+            | b'_'
+            | b'.'
+            | b'~'
+            | b'\''
+            | b'='
+            | b'/'
+            | b':'
+            | b','
+            | b';'
+            | b'('
+            | b')' => (byte as char).to_string(),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_price() {
+        assert_eq!(format_price(pdec!(1)), "1");
+        assert_eq!(format_price(pdec!("1.5")), "1.5");
+        assert_eq!(format_price(pdec!("1.23456789")), "1.23456");
+        assert_eq!(format_price(pdec!("1234567.89")), "1234567");
+        assert_eq!(format_price(pdec!("0.000123456789")), "0.000123456");
+        assert_eq!(format_price(pdec!("0.1000001")), "0.1");
+    }
+
+    #[test]
+    fn test_position_name() {
+        assert_eq!(
+            position_name(Some("XRD/USDC"), 12),
+            "Ociswap LP XRD/USDC #12"
+        );
+        assert_eq!(position_name(None, 1), "Ociswap LP #1");
+    }
+
+    #[test]
+    fn test_position_description() {
+        assert_eq!(
+            position_description(Some("XRD/USDC"), pdec!("0.5"), pdec!(2)),
+            "Liquidity position of the Ociswap Precision Pool XRD/USDC between the prices 0.5 and 2."
+        );
+    }
+
+    #[test]
+    fn test_position_image_url() {
+        let url = position_image_url(Some("XRD/USDC"), pdec!("0.5"), pdec!(2), true);
+        let url = url.as_str();
+
+        assert!(url.starts_with("data:image/svg+xml,%3Csvg"));
+        assert!(url.contains("XRD/USDC"));
+        assert!(url.contains("In%20range"));
+        assert!(!url.contains('<') && !url.contains('#') && !url.contains(' '));
+        assert!(url.len() < 700);
+    }
+
+    #[test]
+    fn test_position_image_in_range() {
+        let in_range = position_image_url(Some("XRD/USDC"), pdec!("0.5"), pdec!(2), true);
+        let out_of_range = position_image_url(Some("XRD/USDC"), pdec!("0.5"), pdec!(2), false);
+
+        assert!(position_image_in_range(&in_range));
+        assert!(!position_image_in_range(&out_of_range));
+    }
+
+    #[test]
+    fn test_position_image_in_range_ignores_symbols() {
+        let url = position_image_url(Some(">In range<"), pdec!("0.5"), pdec!(2), false);
+
+        assert!(!position_image_in_range(&url));
+    }
+
+    #[test]
+    fn test_position_image_url_out_of_range() {
+        let url = position_image_url(None, pdec!("0.5"), pdec!(2), false);
+
+        assert!(url.as_str().contains("Out%20of%20range"));
+        assert!(url.as_str().contains("LP%20Position"));
+    }
+
+    #[test]
+    fn test_position_image_url_escapes_symbols() {
+        let url = position_image_url(Some("<A>/B&C"), pdec!(1), pdec!(2), true);
+
+        assert!(url.as_str().contains("%26lt;A%26gt;/B%26amp;C"));
+    }
+}
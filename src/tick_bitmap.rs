@@ -0,0 +1,170 @@
+use scrypto::prelude::*;
+use scrypto_avltree::AvlTree;
+use std::ops::Bound;
+
+const WORD_BITS: i32 = u64::BITS as i32;
+
+/// Word-packed bitmap of the initialized ticks of a pool.
+///
+/// Every bit represents one tick on the grid of the pool, i.e. the bit index is `tick / tick_spacing`.
+/// The bits are packed into words of 64 bits and only words containing at least one initialized tick are stored.
+/// Finding the next initialized tick therefore reads at most the current word and the next non-empty word,
+/// independent of how many empty ticks lie in between.
+#[derive(ScryptoSbor)]
+pub struct TickBitmap {
+    tick_spacing: u32,
+    words: AvlTree<i32, u64>,
+}
+
+impl TickBitmap {
+    /// Creates an empty bitmap for the given tick spacing.
+    pub fn new(tick_spacing: u32) -> Self {
+        Self {
+            tick_spacing,
+            words: AvlTree::new(),
+        }
+    }
+
+    /// Returns whether the given tick is marked as initialized.
+    pub fn is_initialized(&self, tick: i32) -> bool {
+        let (word_index, bit) = position(tick, self.tick_spacing);
+        self.word(word_index) & (1 << bit) != 0
+    }
+
+    /// Marks the given tick as initialized.
+    pub fn set(&mut self, tick: i32) {
+        let (word_index, bit) = position(tick, self.tick_spacing);
+        if let Some(mut word) = self.words.get_mut(&word_index) {
+            *word |= 1 << bit;
+            return;
+        }
+        self.words.insert(word_index, 1 << bit);
+    }
+
+    /// Marks the given tick as not initialized, removing the word once it is empty.
+    pub fn clear(&mut self, tick: i32) {
+        let (word_index, bit) = position(tick, self.tick_spacing);
+        let word = self.word(word_index) & !(1 << bit);
+        if word == 0 {
+            self.words.remove(&word_index);
+            return;
+        }
+        if let Some(mut stored_word) = self.words.get_mut(&word_index) {
+            *stored_word = word;
+        }
+    }
+
+    /// Returns the lowest initialized tick strictly greater than `tick`, or the lowest initialized tick if `tick` is `None`.
+    pub fn next_initialized_above(&self, tick: Option<i32>) -> Option<i32> {
+        if let Some(tick) = tick {
+            let (word_index, bit) = position(tick, self.tick_spacing);
+            if let Some(bit) = lowest_bit_above(self.word(word_index), bit) {
+                return Some(self.tick(word_index, bit));
+            }
+            return self
+                .words
+                .range((Bound::Excluded(word_index), Bound::Unbounded))
+                .next()
+                .map(|(word_index, word, _)| self.tick(word_index, word.trailing_zeros()));
+        }
+        self.words
+            .range((Bound::Unbounded, Bound::Unbounded))
+            .next()
+            .map(|(word_index, word, _)| self.tick(word_index, word.trailing_zeros()))
+    }
+
+    /// Returns the highest initialized tick less than or equal to `tick`, or `None` if `tick` is `None`.
+    pub fn next_initialized_at_or_below(&self, tick: Option<i32>) -> Option<i32> {
+        let (word_index, bit) = position(tick?, self.tick_spacing);
+        if let Some(bit) = highest_bit_at_or_below(self.word(word_index), bit) {
+            return Some(self.tick(word_index, bit));
+        }
+        self.words
+            .range_back((Bound::Unbounded, Bound::Excluded(word_index)))
+            .next()
+            .map(|(word_index, word, _)| self.tick(word_index, highest_bit(word)))
+    }
+
+    fn word(&self, word_index: i32) -> u64 {
+        self.words.get(&word_index).map(|word| *word).unwrap_or(0)
+    }
+
+    fn tick(&self, word_index: i32, bit: u32) -> i32 {
+        (word_index * WORD_BITS + bit as i32) * self.tick_spacing as i32
+    }
+}
+
+/// Calculates the word index and the bit within the word of a tick.
+///
+/// Ticks between two grid ticks are mapped to the grid tick below, so searches starting from them behave as expected.
+fn position(tick: i32, tick_spacing: u32) -> (i32, u32) {
+    let compressed = tick.div_euclid(tick_spacing as i32);
+    (
+        compressed.div_euclid(WORD_BITS),
+        compressed.rem_euclid(WORD_BITS) as u32,
+    )
+}
+
+/// Returns the lowest set bit of `word` strictly above `bit`.
+fn lowest_bit_above(word: u64, bit: u32) -> Option<u32> {
+    let masked = word.checked_shr(bit + 1).unwrap_or(0);
+    match masked {
+        0 => None,
+        _ => Some(bit + 1 + masked.trailing_zeros()),
+    }
+}
+
+/// Returns the highest set bit of `word` less than or equal to `bit`.
+fn highest_bit_at_or_below(word: u64, bit: u32) -> Option<u32> {
+    let masked = word & (u64::MAX >> (WORD_BITS as u32 - 1 - bit));
+    match masked {
+        0 => None,
+        _ => Some(highest_bit(masked)),
+    }
+}
+
+fn highest_bit(word: u64) -> u32 {
+    WORD_BITS as u32 - 1 - word.leading_zeros()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case(0, 1, (0, 0))]
+    #[test_case(63, 1, (0, 63))]
+    #[test_case(64, 1, (1, 0))]
+    #[test_case(-1, 1, (-1, 63))]
+    #[test_case(-64, 1, (-1, 0))]
+    #[test_case(-65, 1, (-2, 63))]
+    #[test_case(30, 10, (0, 3))]
+    #[test_case(-30, 10, (-1, 61))]
+    #[test_case(-25, 10, (-1, 61))]
+    #[test_case(887272, 1, (13863, 40))]
+    #[test_case(-887272, 1, (-13864, 24))]
+    fn test_position(tick: i32, tick_spacing: u32, expected: (i32, u32)) {
+        assert_eq!(position(tick, tick_spacing), expected);
+    }
+
+    #[test_case(0b1010, 0, Some(1))]
+    #[test_case(0b1010, 1, Some(3))]
+    #[test_case(0b1010, 3, None)]
+    #[test_case(u64::MAX, 62, Some(63))]
+    #[test_case(u64::MAX, 63, None)]
+    #[test_case(0, 0, None)]
+    fn test_lowest_bit_above(word: u64, bit: u32, expected: Option<u32>) {
+        assert_eq!(lowest_bit_above(word, bit), expected);
+    }
+
+    #[test_case(0b1010, 0, None)]
+    #[test_case(0b1010, 1, Some(1))]
+    #[test_case(0b1010, 2, Some(1))]
+    #[test_case(0b1010, 63, Some(3))]
+    #[test_case(1 << 63, 63, Some(63))]
+    #[test_case(1 << 63, 62, None)]
+    #[test_case(0, 63, None)]
+    fn test_highest_bit_at_or_below(word: u64, bit: u32, expected: Option<u32>) {
+        assert_eq!(highest_bit_at_or_below(word, bit), expected);
+    }
+}
@@ -1,4 +1,6 @@
 use crate::constants::*;
+use crate::pool_math::SwapType;
+use common::time::*;
 use common::utils::assert_fee_rate_within_bounds;
 use scrypto::prelude::*;
 
@@ -17,6 +19,22 @@ pub fn sort_buckets(a_bucket: Bucket, b_bucket: Bucket) -> (Bucket, Bucket) {
     (b_bucket, a_bucket)
 }
 
+/// Returns the access rule proving to be the referrer: either the referrer component is the caller or the
+/// owner badge of the referrer account is present.
+///
+/// # Arguments
+/// * `referrer`: Address of the referrer
+///
+/// # Returns
+/// The access rule to assert before paying out the referrer fees to the caller
+pub fn referrer_owner_rule(referrer: ComponentAddress) -> AccessRule {
+    let owner_badge = NonFungibleGlobalId::new(
+        ACCOUNT_OWNER_BADGE,
+        NonFungibleLocalId::bytes(referrer.as_node_id().0).unwrap(),
+    );
+    rule!(require(global_caller(referrer)) || require(owner_badge))
+}
+
 pub fn assert_input_fee_rate_is_valid(input_fee_rate: Decimal) {
     assert_fee_rate_within_bounds(input_fee_rate, INPUT_FEE_RATE_MAX, "input fee rate");
 }
@@ -29,6 +47,14 @@ pub fn assert_flash_loan_fee_rate_is_valid(flash_loan_fee_rate: Decimal) {
     );
 }
 
+pub fn assert_referrer_fee_rate_is_valid(referrer_fee_rate: Decimal) {
+    assert_fee_rate_within_bounds(
+        referrer_fee_rate,
+        REFERRER_FEE_RATE_MAX,
+        "referrer fee rate",
+    );
+}
+
 pub fn assert_hooks_bucket_output(
     input_amount: Decimal,
     output_amount: Decimal,
@@ -40,3 +66,129 @@ pub fn assert_hooks_bucket_output(
         hook_type_name
     );
 }
+
+/// Asserts that an optional transaction deadline has not passed yet.
+///
+/// # Arguments
+/// * `deadline`: Optional Unix timestamp in seconds, `None` disables the check
+///
+/// # Panics
+/// If the current time is after the `deadline`
+pub fn assert_deadline_not_passed(deadline: Option<u64>) {
+    if let Some(deadline) = deadline {
+        assert!(
+            Clock::time_in_seconds() <= deadline,
+            "[Deadline]: The transaction deadline has passed."
+        );
+    }
+}
+
+/// Asserts that a liquidity position is not time-locked anymore.
+///
+/// # Arguments
+/// * `locked_until`: Optional Unix timestamp in seconds until which the position is locked, `None` if it was never locked
+/// * `context`: Name of the operation used as prefix in the error message, e.g. "Remove liquidity"
+///
+/// # Panics
+/// If the current time is before `locked_until`
+pub fn assert_position_unlocked(locked_until: Option<u64>, context: &str) {
+    if let Some(locked_until) = locked_until {
+        assert!(
+            Clock::time_in_seconds() >= locked_until,
+            "[{}]: The position is locked until {}.",
+            context,
+            locked_until
+        );
+    }
+}
+
+/// Asserts that an amount is at least the minimum amount accepted by the user.
+///
+/// # Arguments
+/// * `amount`: Actual amount
+/// * `min_amount`: Minimum amount accepted by the user
+/// * `context`: Name of the operation used as prefix in the error message, e.g. "Add liquidity"
+/// * `token_name`: Name of the token used in the error message, e.g. "X"
+///
+/// # Panics
+/// If `amount` is smaller than `min_amount`
+pub fn assert_min_amount(amount: Decimal, min_amount: Decimal, context: &str, token_name: &str) {
+    assert!(
+        amount >= min_amount,
+        "[{}]: The {} amount {} is lower than the minimum amount {}.",
+        context,
+        token_name,
+        amount,
+        min_amount
+    );
+}
+
+/// Asserts that the current price deviates at most `max_deviation` (relative) from the expected price.
+///
+/// # Arguments
+/// * `price_sqrt`: Current square root price of the pool
+/// * `expected_price`: Price (not square root) the user expects the pool to be at
+/// * `max_deviation`: Maximum relative deviation, e.g. `dec!("0.01")` for 1%
+///
+/// # Panics
+/// * If `expected_price` is not positive or `max_deviation` is negative
+/// * If the current price deviates more than `max_deviation` from `expected_price`
+pub fn assert_price_deviation(
+    price_sqrt: PreciseDecimal,
+    expected_price: Decimal,
+    max_deviation: Decimal,
+) {
+    assert!(
+        expected_price > Decimal::ZERO,
+        "[Price deviation]: The expected price needs to be positive."
+    );
+    assert!(
+        max_deviation >= Decimal::ZERO,
+        "[Price deviation]: The maximum deviation can't be negative."
+    );
+    let expected_price = PreciseDecimal::from(expected_price);
+    let price = price_sqrt * price_sqrt;
+    let deviation = (price - expected_price).checked_abs().unwrap() / expected_price;
+    assert!(
+        deviation <= PreciseDecimal::from(max_deviation),
+        "[Price deviation]: The current price {} deviates more than {} from the expected price {}.",
+        price,
+        max_deviation,
+        expected_price
+    );
+}
+
+/// Asserts that the output of a swap deviates at most `max_slippage` (relative) from the output at the expected price.
+///
+/// The expected price has to be provided by the caller, since the price of the pool before the swap can already
+/// be manipulated within the same transaction.
+///
+/// # Arguments
+/// * `swap_type`: Type of the swap
+/// * `expected_price`: Price (not square root) the user expects the swap to be executed at
+/// * `input_amount`: Amount of the input token used by the swap
+/// * `output_amount`: Amount of the output token returned by the swap
+/// * `max_slippage`: Maximum relative deviation, e.g. `dec!("0.01")` for 1%
+///
+/// # Panics
+/// If the output is lower than the expected output reduced by `max_slippage`
+pub fn assert_swap_slippage(
+    swap_type: SwapType,
+    expected_price: Decimal,
+    input_amount: Decimal,
+    output_amount: Decimal,
+    max_slippage: Decimal,
+) {
+    let price = PreciseDecimal::from(expected_price);
+    let expected_output = match swap_type {
+        SwapType::SellX => PreciseDecimal::from(input_amount) * price,
+        SwapType::BuyX => PreciseDecimal::from(input_amount) / price,
+    };
+    assert!(
+        PreciseDecimal::from(output_amount) >= expected_output * (Decimal::ONE - max_slippage),
+        "[Swap slippage]: The swap output {} is lower than the expected output {} minus the allowed slippage {}.",
+        output_amount,
+        expected_output,
+        max_slippage
+    );
+}
@@ -0,0 +1,206 @@
+use radix_engine::transaction::TransactionReceipt;
+use std::fs;
+use std::path::PathBuf;
+
+/// Environment variable overriding the directory the benchmark reports are written to.
+pub const BENCHMARK_REPORT_DIR_VAR: &str = "PRECISION_POOL_BENCHMARK_DIR";
+
+/// Default directory of the benchmark reports, relative to the workspace root.
+pub const BENCHMARK_REPORT_DIR_DEFAULT: &str = "target/benchmarks";
+
+/// Environment variable pointing to a directory with reports recorded on a baseline commit.
+///
+/// If set, every report is compared against the baseline report of the same name and the comparison is written
+/// to `<name>.comparison.json` next to the report.
+pub const BENCHMARK_BASELINE_DIR_VAR: &str = "PRECISION_POOL_BENCHMARK_BASELINE_DIR";
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CostMeasurement {
+    pub name: String,
+    pub execution_cost_units: u32,
+    pub finalization_cost_units: u32,
+}
+
+/// Execution cost units of a measurement before (baseline) and after a change.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CostComparison {
+    pub name: String,
+    pub baseline_execution_cost_units: u32,
+    pub execution_cost_units: u32,
+}
+
+impl CostComparison {
+    /// Difference of the execution cost units to the baseline, negative if the change is cheaper.
+    pub fn delta(&self) -> i64 {
+        self.execution_cost_units as i64 - self.baseline_execution_cost_units as i64
+    }
+}
+
+/// Collects the cost units of committed transactions and writes them as a JSON report.
+///
+/// Every report is written to its own file `<name>.json`, which allows the benchmarks to run in parallel and
+/// the reports of two commits to be compared with standard tooling to detect cost regressions.
+#[derive(Clone, Debug)]
+pub struct BenchmarkReport {
+    pub name: String,
+    pub measurements: Vec<CostMeasurement>,
+}
+
+impl BenchmarkReport {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            measurements: vec![],
+        }
+    }
+
+    /// Records the cost units consumed by a successfully committed transaction.
+    pub fn record(&mut self, name: &str, receipt: &TransactionReceipt) -> &CostMeasurement {
+        receipt.expect_commit_success();
+        self.measurements.push(CostMeasurement {
+            name: name.to_string(),
+            execution_cost_units: receipt.fee_summary.total_execution_cost_units_consumed,
+            finalization_cost_units: receipt.fee_summary.total_finalization_cost_units_consumed,
+        });
+        self.measurements.last().unwrap()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&CostMeasurement> {
+        self.measurements
+            .iter()
+            .find(|measurement| measurement.name == name)
+    }
+
+    /// Compares the measurements with the measurements of the same name in `baseline`.
+    ///
+    /// Measurements missing in the baseline are skipped.
+    pub fn compare(&self, baseline: &BenchmarkReport) -> Vec<CostComparison> {
+        self.measurements
+            .iter()
+            .filter_map(|measurement| {
+                baseline
+                    .get(&measurement.name)
+                    .map(|baseline_measurement| CostComparison {
+                        name: measurement.name.clone(),
+                        baseline_execution_cost_units: baseline_measurement.execution_cost_units,
+                        execution_cost_units: measurement.execution_cost_units,
+                    })
+            })
+            .collect()
+    }
+
+    /// Parses a report previously written by `to_json`.
+    pub fn from_json(json: &str) -> Option<Self> {
+        let name = json_string_value(json.lines().find(|line| line.contains("\"name\""))?, "name")?;
+        let measurements = json
+            .lines()
+            .filter(|line| line.contains("\"execution_cost_units\""))
+            .map(|line| {
+                Some(CostMeasurement {
+                    name: json_string_value(line, "name")?,
+                    execution_cost_units: json_number_value(line, "execution_cost_units")?,
+                    finalization_cost_units: json_number_value(line, "finalization_cost_units")?,
+                })
+            })
+            .collect::<Option<Vec<CostMeasurement>>>()?;
+        Some(Self { name, measurements })
+    }
+
+    pub fn to_json(&self) -> String {
+        let measurements: Vec<String> = self
+            .measurements
+            .iter()
+            .map(|measurement| {
+                format!(
+                    "    {{\"name\": \"{}\", \"execution_cost_units\": {}, \"finalization_cost_units\": {}}}",
+                    measurement.name,
+                    measurement.execution_cost_units,
+                    measurement.finalization_cost_units
+                )
+            })
+            .collect();
+        format!(
+            "{{\n  \"name\": \"{}\",\n  \"measurements\": [\n{}\n  ]\n}}\n",
+            self.name,
+            measurements.join(",\n")
+        )
+    }
+
+    /// Writes the report to `<PRECISION_POOL_BENCHMARK_DIR>/<name>.json` and returns the path of the report.
+    ///
+    /// If `PRECISION_POOL_BENCHMARK_BASELINE_DIR` contains a report of the same name, the comparison with it is
+    /// written to `<name>.comparison.json` and printed.
+    pub fn write(&self) -> PathBuf {
+        let directory = match std::env::var(BENCHMARK_REPORT_DIR_VAR) {
+            Ok(directory) => PathBuf::from(directory),
+            Err(_) => PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+                .join("..")
+                .join(BENCHMARK_REPORT_DIR_DEFAULT),
+        };
+        fs::create_dir_all(&directory).expect("[Benchmark]: Unable to create report directory.");
+        let path = directory.join(format!("{}.json", self.name));
+        fs::write(&path, self.to_json()).expect("[Benchmark]: Unable to write report.");
+        if let Some(baseline) = self.read_baseline() {
+            let comparisons = self.compare(&baseline);
+            for comparison in &comparisons {
+                println!(
+                    "{}: {} -> {} execution cost units ({:+})",
+                    comparison.name,
+                    comparison.baseline_execution_cost_units,
+                    comparison.execution_cost_units,
+                    comparison.delta()
+                );
+            }
+            fs::write(
+                directory.join(format!("{}.comparison.json", self.name)),
+                comparisons_to_json(&self.name, &comparisons),
+            )
+            .expect("[Benchmark]: Unable to write comparison.");
+        }
+        path
+    }
+
+    fn read_baseline(&self) -> Option<BenchmarkReport> {
+        let directory = PathBuf::from(std::env::var(BENCHMARK_BASELINE_DIR_VAR).ok()?);
+        let json = fs::read_to_string(directory.join(format!("{}.json", self.name))).ok()?;
+        BenchmarkReport::from_json(&json)
+    }
+}
+
+fn comparisons_to_json(name: &str, comparisons: &[CostComparison]) -> String {
+    let comparisons: Vec<String> = comparisons
+        .iter()
+        .map(|comparison| {
+            format!(
+                "    {{\"name\": \"{}\", \"baseline_execution_cost_units\": {}, \"execution_cost_units\": {}, \"delta\": {}}}",
+                comparison.name,
+                comparison.baseline_execution_cost_units,
+                comparison.execution_cost_units,
+                comparison.delta()
+            )
+        })
+        .collect();
+    format!(
+        "{{\n  \"name\": \"{}\",\n  \"comparisons\": [\n{}\n  ]\n}}\n",
+        name,
+        comparisons.join(",\n")
+    )
+}
+
+fn json_value<'a>(line: &'a str, key: &str) -> Option<&'a str> {
+    let start = line.find(&format!("\"{}\": ", key))? + key.len() + 4;
+    Some(&line[start..])
+}
+
+fn json_string_value(line: &str, key: &str) -> Option<String> {
+    let value = json_value(line, key)?.strip_prefix('"')?;
+    Some(value[..value.find('"')?].to_string())
+}
+
+fn json_number_value(line: &str, key: &str) -> Option<u32> {
+    let value = json_value(line, key)?;
+    let end = value
+        .find(|character: char| !character.is_ascii_digit())
+        .unwrap_or(value.len());
+    value[..end].parse().ok()
+}
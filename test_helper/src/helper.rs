@@ -2,6 +2,7 @@ use crate::constants::*;
 use common::math::AttoDecimal;
 use common::pools::SwapType;
 use precision_pool::pool;
+use precision_pool::pool_math::{LiquidityShape, TickRounding};
 use pretty_assertions::assert_eq;
 use radix_engine::system::system_modules::execution_trace::{
     ResourceSpecifier, ResourceSpecifier::Amount, ResourceSpecifier::Ids,
@@ -38,6 +39,10 @@ pub struct PoolTestHelper {
     pub lp_address: Option<ResourceAddress>,
 
     pub price_sqrt: Option<PreciseDecimal>,
+
+    // Audits the pool after every successful operation of the `*_success` helpers, see `assert_invariants`.
+    // Enabled for all tests by setting the environment variable `PRECISION_POOL_CHECK_INVARIANTS`.
+    pub check_invariants: bool,
 }
 
 impl PoolTestHelper {
@@ -49,6 +54,17 @@ impl PoolTestHelper {
         Self::new_internal(false)
     }
 
+    pub fn new_with_test_hook() -> PoolTestHelper {
+        let packages: HashMap<&str, &str> = vec![
+            ("registry", "registry"),
+            ("precision_pool", "."),
+            ("test_hook", "test_hook"),
+        ]
+        .into_iter()
+        .collect();
+        Self::new_with_packages(packages, true)
+    }
+
     fn new_internal(instantiate_registry: bool) -> PoolTestHelper {
         let packages: HashMap<&str, &str> = vec![("registry", "registry"), ("precision_pool", ".")]
             .into_iter()
@@ -67,6 +83,8 @@ impl PoolTestHelper {
             lp_address: None,
 
             price_sqrt: None,
+
+            check_invariants: std::env::var("PRECISION_POOL_CHECK_INVARIANTS").is_ok(),
         };
 
         if instantiate_registry {
@@ -280,6 +298,190 @@ impl PoolTestHelper {
         self
     }
 
+    pub fn add_liquidity_with_deadline(
+        &mut self,
+        left_bound: i32,
+        right_bound: i32,
+        x_amount: Decimal,
+        y_amount: Decimal,
+        deadline: Option<u64>,
+    ) -> &mut PoolTestHelper {
+        let account = self.registry.env.account;
+        let pool_address = self.pool_address.unwrap();
+        let (x_address, y_address) = (self.x_address(), self.y_address());
+        let manifest_builder = mem::replace(
+            &mut self.registry.env.manifest_builder,
+            ManifestBuilder::new(),
+        );
+        self.registry.env.manifest_builder = manifest_builder
+            .withdraw_from_account(account, x_address, x_amount)
+            .withdraw_from_account(account, y_address, y_amount)
+            .take_from_worktop(x_address, x_amount, self.registry.name("x_bucket"))
+            .take_from_worktop(y_address, y_amount, self.registry.name("y_bucket"))
+            .with_name_lookup(|builder, lookup| {
+                let x_bucket = lookup.bucket(self.registry.name("x_bucket"));
+                let y_bucket = lookup.bucket(self.registry.name("y_bucket"));
+                builder.call_method(
+                    pool_address,
+                    "add_liquidity_with_deadline",
+                    manifest_args!(left_bound, right_bound, x_bucket, y_bucket, deadline),
+                )
+            });
+        self.registry
+            .env
+            .new_instruction("add_liquidity_with_deadline", 5, 4);
+        self
+    }
+
+    pub fn add_liquidity_locked(
+        &mut self,
+        left_bound: i32,
+        right_bound: i32,
+        x_amount: Decimal,
+        y_amount: Decimal,
+        locked_until: u64,
+    ) -> &mut PoolTestHelper {
+        let account = self.registry.env.account;
+        let pool_address = self.pool_address.unwrap();
+        let (x_address, y_address) = (self.x_address(), self.y_address());
+        let manifest_builder = mem::replace(
+            &mut self.registry.env.manifest_builder,
+            ManifestBuilder::new(),
+        );
+        self.registry.env.manifest_builder = manifest_builder
+            .withdraw_from_account(account, x_address, x_amount)
+            .withdraw_from_account(account, y_address, y_amount)
+            .take_from_worktop(x_address, x_amount, self.registry.name("x_bucket"))
+            .take_from_worktop(y_address, y_amount, self.registry.name("y_bucket"))
+            .with_name_lookup(|builder, lookup| {
+                let x_bucket = lookup.bucket(self.registry.name("x_bucket"));
+                let y_bucket = lookup.bucket(self.registry.name("y_bucket"));
+                builder.call_method(
+                    pool_address,
+                    "add_liquidity_locked",
+                    manifest_args!(left_bound, right_bound, x_bucket, y_bucket, locked_until),
+                )
+            });
+        self.registry
+            .env
+            .new_instruction("add_liquidity_locked", 5, 4);
+        self
+    }
+
+    pub fn add_liquidity_with_limits(
+        &mut self,
+        left_bound: i32,
+        right_bound: i32,
+        x_amount: Decimal,
+        y_amount: Decimal,
+        min_x: Decimal,
+        min_y: Decimal,
+        max_price_deviation: Option<(Decimal, Decimal)>,
+    ) -> &mut PoolTestHelper {
+        let account = self.registry.env.account;
+        let pool_address = self.pool_address.unwrap();
+        let (x_address, y_address) = (self.x_address(), self.y_address());
+        let manifest_builder = mem::replace(
+            &mut self.registry.env.manifest_builder,
+            ManifestBuilder::new(),
+        );
+        self.registry.env.manifest_builder = manifest_builder
+            .withdraw_from_account(account, x_address, x_amount)
+            .withdraw_from_account(account, y_address, y_amount)
+            .take_from_worktop(x_address, x_amount, self.registry.name("x_bucket"))
+            .take_from_worktop(y_address, y_amount, self.registry.name("y_bucket"))
+            .with_name_lookup(|builder, lookup| {
+                let x_bucket = lookup.bucket(self.registry.name("x_bucket"));
+                let y_bucket = lookup.bucket(self.registry.name("y_bucket"));
+                builder.call_method(
+                    pool_address,
+                    "add_liquidity_with_limits",
+                    manifest_args!(
+                        left_bound,
+                        right_bound,
+                        x_bucket,
+                        y_bucket,
+                        min_x,
+                        min_y,
+                        max_price_deviation,
+                        None::<u64>
+                    ),
+                )
+            });
+        self.registry
+            .env
+            .new_instruction("add_liquidity_with_limits", 5, 4);
+        self
+    }
+
+    pub fn add_liquidity_by_price(
+        &mut self,
+        price_left: Decimal,
+        price_right: Decimal,
+        rounding: TickRounding,
+        x_amount: Decimal,
+        y_amount: Decimal,
+    ) -> &mut PoolTestHelper {
+        let account = self.registry.env.account;
+        let pool_address = self.pool_address.unwrap();
+        let (x_address, y_address) = (self.x_address(), self.y_address());
+        let manifest_builder = mem::replace(
+            &mut self.registry.env.manifest_builder,
+            ManifestBuilder::new(),
+        );
+        self.registry.env.manifest_builder = manifest_builder
+            .withdraw_from_account(account, x_address, x_amount)
+            .withdraw_from_account(account, y_address, y_amount)
+            .take_from_worktop(x_address, x_amount, self.registry.name("x_bucket"))
+            .take_from_worktop(y_address, y_amount, self.registry.name("y_bucket"))
+            .with_name_lookup(|builder, lookup| {
+                let x_bucket = lookup.bucket(self.registry.name("x_bucket"));
+                let y_bucket = lookup.bucket(self.registry.name("y_bucket"));
+                builder.call_method(
+                    pool_address,
+                    "add_liquidity_by_price",
+                    manifest_args!(price_left, price_right, rounding, x_bucket, y_bucket),
+                )
+            });
+        self.registry
+            .env
+            .new_instruction("add_liquidity_by_price", 5, 4);
+        self
+    }
+
+    pub fn zap_in(
+        &mut self,
+        left_bound: i32,
+        right_bound: i32,
+        input_address: ResourceAddress,
+        input_amount: Decimal,
+        max_price_deviation: (Decimal, Decimal),
+    ) -> &mut PoolTestHelper {
+        let account = self.registry.env.account;
+        let pool_address = self.pool_address.unwrap();
+        let manifest_builder = mem::replace(
+            &mut self.registry.env.manifest_builder,
+            ManifestBuilder::new(),
+        );
+        self.registry.env.manifest_builder = manifest_builder
+            .withdraw_from_account(account, input_address, input_amount)
+            .take_from_worktop(
+                input_address,
+                input_amount,
+                self.registry.name("input_bucket"),
+            )
+            .with_name_lookup(|builder, lookup| {
+                let input_bucket = lookup.bucket(self.registry.name("input_bucket"));
+                builder.call_method(
+                    pool_address,
+                    "zap_in",
+                    manifest_args!(left_bound, right_bound, input_bucket, max_price_deviation),
+                )
+            });
+        self.registry.env.new_instruction("zap_in", 3, 2);
+        self
+    }
+
     pub fn add_liquidity_shape(
         &mut self,
         left_bound: i32,
@@ -348,6 +550,110 @@ impl PoolTestHelper {
         self
     }
 
+    pub fn add_liquidity_shape_positions(
+        &mut self,
+        positions: &[ShapePosition],
+        shape_proof: Option<IndexSet<NonFungibleLocalId>>,
+    ) -> &mut PoolTestHelper {
+        let account = self.registry.env.account;
+        let pool_address = self.pool_address.unwrap();
+        let mut manifest_builder = mem::replace(
+            &mut self.registry.env.manifest_builder,
+            ManifestBuilder::new(),
+        );
+        for (index, position) in positions.iter().enumerate() {
+            manifest_builder = manifest_builder
+                .withdraw_from_account(account, position.x_address, position.x_amount)
+                .withdraw_from_account(account, position.y_address, position.y_amount)
+                .take_from_worktop(
+                    position.x_address,
+                    position.x_amount,
+                    self.registry.name(&format!("x_bucket_{}", index)),
+                )
+                .take_from_worktop(
+                    position.y_address,
+                    position.y_amount,
+                    self.registry.name(&format!("y_bucket_{}", index)),
+                );
+        }
+
+        if let Some(proof) = shape_proof.clone() {
+            manifest_builder = manifest_builder
+                .create_proof_from_account_of_non_fungibles(
+                    account,
+                    self.lp_address.unwrap(),
+                    proof,
+                )
+                .pop_from_auth_zone(self.registry.name("shape_proof"));
+        }
+        self.registry.env.manifest_builder =
+            manifest_builder.with_name_lookup(|builder, lookup| {
+                let position_buckets: Vec<(i32, i32, ManifestBucket, ManifestBucket)> = positions
+                    .iter()
+                    .enumerate()
+                    .map(|(index, position)| {
+                        (
+                            position.left_bound,
+                            position.right_bound,
+                            lookup.bucket(self.registry.name(&format!("x_bucket_{}", index))),
+                            lookup.bucket(self.registry.name(&format!("y_bucket_{}", index))),
+                        )
+                    })
+                    .collect();
+                let shape_proof = match shape_proof.clone() {
+                    Some(_) => Some(lookup.proof(self.registry.name("shape_proof"))),
+                    None => None,
+                };
+                builder.call_method(
+                    pool_address,
+                    "add_liquidity_shape",
+                    manifest_args!(position_buckets, shape_proof),
+                )
+            });
+        let proof_instructions = if shape_proof.is_some() { 2 } else { 0 };
+        let instructions = positions.len() * 4 + proof_instructions;
+        self.registry
+            .env
+            .new_instruction("add_liquidity_shape", instructions + 1, instructions);
+        self
+    }
+
+    pub fn add_liquidity_distribution(
+        &mut self,
+        shape: LiquidityShape,
+        center: i32,
+        width: u32,
+        num_bins: u32,
+        x_amount: Decimal,
+        y_amount: Decimal,
+    ) -> &mut PoolTestHelper {
+        let account = self.registry.env.account;
+        let pool_address = self.pool_address.unwrap();
+        let (x_address, y_address) = (self.x_address(), self.y_address());
+        let manifest_builder = mem::replace(
+            &mut self.registry.env.manifest_builder,
+            ManifestBuilder::new(),
+        );
+        self.registry.env.manifest_builder = manifest_builder
+            .withdraw_from_account(account, x_address, x_amount)
+            .withdraw_from_account(account, y_address, y_amount)
+            .take_from_worktop(x_address, x_amount, self.registry.name("x_bucket"))
+            .take_from_worktop(y_address, y_amount, self.registry.name("y_bucket"))
+            .with_name_lookup(|builder, lookup| {
+                let x_bucket = lookup.bucket(self.registry.name("x_bucket"));
+                let y_bucket = lookup.bucket(self.registry.name("y_bucket"));
+                builder.call_method(
+                    pool_address,
+                    "add_liquidity_distribution",
+                    manifest_args!(shape, center, width, num_bins, x_bucket, y_bucket),
+                )
+            });
+        self.registry
+            .env
+            .new_instruction("add_liquidity_distribution", 5, 4);
+        self
+    }
+
     pub fn remove_liquidity(
         &mut self,
         lp_positions: IndexSet<NonFungibleLocalId>,
@@ -374,56 +680,525 @@ impl PoolTestHelper {
         self
     }
 
-    pub fn removable_liquidity(
+    pub fn remove_liquidity_without_sync(
         &mut self,
-        lp_position_ids: IndexSet<NonFungibleLocalId>,
+        lp_positions: IndexSet<NonFungibleLocalId>,
     ) -> &mut PoolTestHelper {
+        let account = self.registry.env.account;
         let pool_address = self.pool_address.unwrap();
+        let lp_address = self.lp_address.unwrap();
         let manifest_builder = mem::replace(
             &mut self.registry.env.manifest_builder,
             ManifestBuilder::new(),
         );
-        let lp_position_ids: Vec<NonFungibleLocalId> = lp_position_ids.into_iter().collect();
-        self.registry.env.manifest_builder = manifest_builder.call_method(
-            pool_address,
-            "removable_liquidity",
-            manifest_args!(lp_position_ids),
-        );
+        self.registry.env.manifest_builder = manifest_builder
+            .withdraw_non_fungibles_from_account(account, lp_address, lp_positions.clone())
+            .take_non_fungibles_from_worktop(
+                lp_address,
+                lp_positions,
+                self.registry.name("lp_bucket"),
+            )
+            .with_name_lookup(|builder, lookup| {
+                let lp_bucket = lookup.bucket(self.registry.name("lp_bucket"));
+                builder.call_method(
+                    pool_address,
+                    "remove_liquidity_without_sync",
+                    manifest_args!(lp_bucket),
+                )
+            });
         self.registry
             .env
-            .new_instruction("removable_liquidity", 1, 0);
+            .new_instruction("remove_liquidity_without_sync", 3, 2);
         self
     }
 
-    pub fn swap(
+    pub fn remove_shape(
         &mut self,
-        input_address: ResourceAddress,
-        input_amount: Decimal,
+        lp_positions: IndexSet<NonFungibleLocalId>,
+    ) -> &mut PoolTestHelper {
+        let account = self.registry.env.account;
+        let pool_address = self.pool_address.unwrap();
+        let lp_address = self.lp_address.unwrap();
+        let manifest_builder = mem::replace(
+            &mut self.registry.env.manifest_builder,
+            ManifestBuilder::new(),
+        );
+        self.registry.env.manifest_builder = manifest_builder
+            .withdraw_non_fungibles_from_account(account, lp_address, lp_positions.clone())
+            .take_non_fungibles_from_worktop(
+                lp_address,
+                lp_positions,
+                self.registry.name("lp_bucket"),
+            )
+            .with_name_lookup(|builder, lookup| {
+                let lp_bucket = lookup.bucket(self.registry.name("lp_bucket"));
+                builder.call_method(pool_address, "remove_shape", manifest_args!(lp_bucket))
+            });
+        self.registry.env.new_instruction("remove_shape", 3, 2);
+        self
+    }
+
+    pub fn remove_liquidity_with_deadline(
+        &mut self,
+        lp_positions: IndexSet<NonFungibleLocalId>,
+        deadline: Option<u64>,
+    ) -> &mut PoolTestHelper {
+        let account = self.registry.env.account;
+        let pool_address = self.pool_address.unwrap();
+        let lp_address = self.lp_address.unwrap();
+        let manifest_builder = mem::replace(
+            &mut self.registry.env.manifest_builder,
+            ManifestBuilder::new(),
+        );
+        self.registry.env.manifest_builder = manifest_builder
+            .withdraw_non_fungibles_from_account(account, lp_address, lp_positions.clone())
+            .take_non_fungibles_from_worktop(
+                lp_address,
+                lp_positions,
+                self.registry.name("lp_bucket"),
+            )
+            .with_name_lookup(|builder, lookup| {
+                let lp_bucket = lookup.bucket(self.registry.name("lp_bucket"));
+                builder.call_method(
+                    pool_address,
+                    "remove_liquidity_with_deadline",
+                    manifest_args!(lp_bucket, deadline),
+                )
+            });
+        self.registry
+            .env
+            .new_instruction("remove_liquidity_with_deadline", 3, 2);
+        self
+    }
+
+    pub fn remove_liquidity_with_limits(
+        &mut self,
+        lp_positions: IndexSet<NonFungibleLocalId>,
+        min_x_out: Decimal,
+        min_y_out: Decimal,
+    ) -> &mut PoolTestHelper {
+        let account = self.registry.env.account;
+        let pool_address = self.pool_address.unwrap();
+        let lp_address = self.lp_address.unwrap();
+        let manifest_builder = mem::replace(
+            &mut self.registry.env.manifest_builder,
+            ManifestBuilder::new(),
+        );
+        self.registry.env.manifest_builder = manifest_builder
+            .withdraw_non_fungibles_from_account(account, lp_address, lp_positions.clone())
+            .take_non_fungibles_from_worktop(
+                lp_address,
+                lp_positions,
+                self.registry.name("lp_bucket"),
+            )
+            .with_name_lookup(|builder, lookup| {
+                let lp_bucket = lookup.bucket(self.registry.name("lp_bucket"));
+                builder.call_method(
+                    pool_address,
+                    "remove_liquidity_with_limits",
+                    manifest_args!(lp_bucket, min_x_out, min_y_out, None::<u64>),
+                )
+            });
+        self.registry
+            .env
+            .new_instruction("remove_liquidity_with_limits", 3, 2);
+        self
+    }
+
+    pub fn remove_liquidity_to(
+        &mut self,
+        lp_positions: IndexSet<NonFungibleLocalId>,
+        output_address: ResourceAddress,
+        min_output: Decimal,
+    ) -> &mut PoolTestHelper {
+        let account = self.registry.env.account;
+        let pool_address = self.pool_address.unwrap();
+        let lp_address = self.lp_address.unwrap();
+        let manifest_builder = mem::replace(
+            &mut self.registry.env.manifest_builder,
+            ManifestBuilder::new(),
+        );
+        self.registry.env.manifest_builder = manifest_builder
+            .withdraw_non_fungibles_from_account(account, lp_address, lp_positions.clone())
+            .take_non_fungibles_from_worktop(
+                lp_address,
+                lp_positions,
+                self.registry.name("lp_bucket"),
+            )
+            .with_name_lookup(|builder, lookup| {
+                let lp_bucket = lookup.bucket(self.registry.name("lp_bucket"));
+                builder.call_method(
+                    pool_address,
+                    "remove_liquidity_to",
+                    manifest_args!(lp_bucket, output_address, min_output),
+                )
+            });
+        self.registry
+            .env
+            .new_instruction("remove_liquidity_to", 3, 2);
+        self
+    }
+
+    pub fn reposition(
+        &mut self,
+        lp_positions: IndexSet<NonFungibleLocalId>,
+        left_bound: i32,
+        right_bound: i32,
+        max_price_deviation: (Decimal, Decimal),
+    ) -> &mut PoolTestHelper {
+        let account = self.registry.env.account;
+        let pool_address = self.pool_address.unwrap();
+        let lp_address = self.lp_address.unwrap();
+        let manifest_builder = mem::replace(
+            &mut self.registry.env.manifest_builder,
+            ManifestBuilder::new(),
+        );
+        self.registry.env.manifest_builder = manifest_builder
+            .withdraw_non_fungibles_from_account(account, lp_address, lp_positions.clone())
+            .take_non_fungibles_from_worktop(
+                lp_address,
+                lp_positions,
+                self.registry.name("lp_bucket"),
+            )
+            .with_name_lookup(|builder, lookup| {
+                let lp_bucket = lookup.bucket(self.registry.name("lp_bucket"));
+                builder.call_method(
+                    pool_address,
+                    "reposition",
+                    manifest_args!(lp_bucket, left_bound, right_bound, max_price_deviation),
+                )
+            });
+        self.registry.env.new_instruction("reposition", 3, 2);
+        self
+    }
+
+    pub fn merge_positions(
+        &mut self,
+        lp_positions: IndexSet<NonFungibleLocalId>,
+    ) -> &mut PoolTestHelper {
+        let account = self.registry.env.account;
+        let pool_address = self.pool_address.unwrap();
+        let lp_address = self.lp_address.unwrap();
+        let manifest_builder = mem::replace(
+            &mut self.registry.env.manifest_builder,
+            ManifestBuilder::new(),
+        );
+        self.registry.env.manifest_builder = manifest_builder
+            .withdraw_non_fungibles_from_account(account, lp_address, lp_positions.clone())
+            .take_non_fungibles_from_worktop(
+                lp_address,
+                lp_positions,
+                self.registry.name("lp_bucket"),
+            )
+            .with_name_lookup(|builder, lookup| {
+                let lp_bucket = lookup.bucket(self.registry.name("lp_bucket"));
+                builder.call_method(pool_address, "merge_positions", manifest_args!(lp_bucket))
+            });
+        self.registry.env.new_instruction("merge_positions", 3, 2);
+        self
+    }
+
+    pub fn split_position(
+        &mut self,
+        lp_positions: IndexSet<NonFungibleLocalId>,
+        fractions: Vec<Decimal>,
+    ) -> &mut PoolTestHelper {
+        let account = self.registry.env.account;
+        let pool_address = self.pool_address.unwrap();
+        let manifest_builder = mem::replace(
+            &mut self.registry.env.manifest_builder,
+            ManifestBuilder::new(),
+        );
+        self.registry.env.manifest_builder = manifest_builder
+            .create_proof_from_account_of_non_fungibles(
+                account,
+                self.lp_address.unwrap(),
+                lp_positions,
+            )
+            .pop_from_auth_zone(self.registry.name("lp_proof"))
+            .with_name_lookup(|builder, lookup| {
+                let lp_proof = lookup.proof(self.registry.name("lp_proof"));
+                builder.call_method(
+                    pool_address,
+                    "split_position",
+                    manifest_args!(lp_proof, fractions),
+                )
+            });
+        self.registry.env.new_instruction("split_position", 3, 2);
+        self
+    }
+
+    pub fn removable_liquidity(
+        &mut self,
+        lp_position_ids: IndexSet<NonFungibleLocalId>,
+    ) -> &mut PoolTestHelper {
+        let pool_address = self.pool_address.unwrap();
+        let manifest_builder = mem::replace(
+            &mut self.registry.env.manifest_builder,
+            ManifestBuilder::new(),
+        );
+        let lp_position_ids: Vec<NonFungibleLocalId> = lp_position_ids.into_iter().collect();
+        self.registry.env.manifest_builder = manifest_builder.call_method(
+            pool_address,
+            "removable_liquidity",
+            manifest_args!(lp_position_ids),
+        );
+        self.registry
+            .env
+            .new_instruction("removable_liquidity", 1, 0);
+        self
+    }
+
+    pub fn swap(
+        &mut self,
+        input_address: ResourceAddress,
+        input_amount: Decimal,
+    ) -> &mut PoolTestHelper {
+        let manifest_builder = mem::replace(
+            &mut self.registry.env.manifest_builder,
+            ManifestBuilder::new(),
+        );
+        self.registry.env.manifest_builder = manifest_builder
+            .withdraw_from_account(self.registry.env.account, input_address, input_amount)
+            .take_from_worktop(
+                input_address,
+                input_amount,
+                self.registry.name("input_bucket"),
+            )
+            .with_name_lookup(|builder, lookup| {
+                let input_bucket = lookup.bucket(self.registry.name("input_bucket"));
+                builder.call_method(
+                    self.pool_address.unwrap(),
+                    "swap",
+                    manifest_args!(input_bucket),
+                )
+            });
+        self.registry.env.new_instruction("swap", 3, 2);
+        self
+    }
+
+    pub fn swap_with_max_ticks_crossed(
+        &mut self,
+        input_address: ResourceAddress,
+        input_amount: Decimal,
+        max_ticks_crossed: u32,
+    ) -> &mut PoolTestHelper {
+        let manifest_builder = mem::replace(
+            &mut self.registry.env.manifest_builder,
+            ManifestBuilder::new(),
+        );
+        self.registry.env.manifest_builder = manifest_builder
+            .withdraw_from_account(self.registry.env.account, input_address, input_amount)
+            .take_from_worktop(
+                input_address,
+                input_amount,
+                self.registry.name("input_bucket"),
+            )
+            .with_name_lookup(|builder, lookup| {
+                let input_bucket = lookup.bucket(self.registry.name("input_bucket"));
+                builder.call_method(
+                    self.pool_address.unwrap(),
+                    "swap_with_max_ticks_crossed",
+                    manifest_args!(input_bucket, max_ticks_crossed),
+                )
+            });
+        self.registry
+            .env
+            .new_instruction("swap_with_max_ticks_crossed", 3, 2);
+        self
+    }
+
+    pub fn quote_swap(
+        &mut self,
+        input_address: ResourceAddress,
+        input_amount: Decimal,
+        max_ticks_crossed: Option<u32>,
+    ) -> &mut PoolTestHelper {
+        let pool_address = self.pool_address.unwrap();
+        let manifest_builder = mem::replace(
+            &mut self.registry.env.manifest_builder,
+            ManifestBuilder::new(),
+        );
+        self.registry.env.manifest_builder = manifest_builder.call_method(
+            pool_address,
+            "quote_swap",
+            manifest_args!(input_address, input_amount, max_ticks_crossed),
+        );
+        self.registry.env.new_instruction("quote_swap", 1, 0);
+        self
+    }
+
+    pub fn swap_with_deadline(
+        &mut self,
+        input_address: ResourceAddress,
+        input_amount: Decimal,
+        deadline: Option<u64>,
+    ) -> &mut PoolTestHelper {
+        let manifest_builder = mem::replace(
+            &mut self.registry.env.manifest_builder,
+            ManifestBuilder::new(),
+        );
+        self.registry.env.manifest_builder = manifest_builder
+            .withdraw_from_account(self.registry.env.account, input_address, input_amount)
+            .take_from_worktop(
+                input_address,
+                input_amount,
+                self.registry.name("input_bucket"),
+            )
+            .with_name_lookup(|builder, lookup| {
+                let input_bucket = lookup.bucket(self.registry.name("input_bucket"));
+                builder.call_method(
+                    self.pool_address.unwrap(),
+                    "swap_with_deadline",
+                    manifest_args!(input_bucket, deadline),
+                )
+            });
+        self.registry
+            .env
+            .new_instruction("swap_with_deadline", 3, 2);
+        self
+    }
+
+    pub fn swap_with_referrer(
+        &mut self,
+        input_address: ResourceAddress,
+        input_amount: Decimal,
+        referrer: Option<(ComponentAddress, Decimal)>,
+    ) -> &mut PoolTestHelper {
+        let manifest_builder = mem::take(&mut self.registry.env.manifest_builder);
+        self.registry.env.manifest_builder = manifest_builder
+            .withdraw_from_account(self.registry.env.account, input_address, input_amount)
+            .take_from_worktop(
+                input_address,
+                input_amount,
+                self.registry.name("input_bucket"),
+            )
+            .with_name_lookup(|builder, lookup| {
+                let input_bucket = lookup.bucket(self.registry.name("input_bucket"));
+                builder.call_method(
+                    self.pool_address.unwrap(),
+                    "swap_with_referrer",
+                    manifest_args!(input_bucket, referrer),
+                )
+            });
+        self.registry
+            .env
+            .new_instruction("swap_with_referrer", 3, 2);
+        self
+    }
+
+    pub fn quote_swap_with_referrer(
+        &mut self,
+        input_address: ResourceAddress,
+        input_amount: Decimal,
+        max_ticks_crossed: Option<u32>,
+        referrer_fee_rate: Decimal,
+    ) -> &mut PoolTestHelper {
+        let manifest_builder = mem::take(&mut self.registry.env.manifest_builder);
+        self.registry.env.manifest_builder = manifest_builder.call_method(
+            self.pool_address.unwrap(),
+            "quote_swap_with_referrer",
+            manifest_args!(
+                input_address,
+                input_amount,
+                max_ticks_crossed,
+                referrer_fee_rate
+            ),
+        );
+        self.registry
+            .env
+            .new_instruction("quote_swap_with_referrer", 1, 0);
+        self
+    }
+
+    pub fn referrer_fees(&mut self, referrer: ComponentAddress) -> &mut PoolTestHelper {
+        let manifest_builder = mem::take(&mut self.registry.env.manifest_builder);
+        self.registry.env.manifest_builder = manifest_builder.call_method(
+            self.pool_address.unwrap(),
+            "referrer_fees",
+            manifest_args!(referrer),
+        );
+        self.registry.env.new_instruction("referrer_fees", 1, 0);
+        self
+    }
+
+    pub fn claim_referrer_fees(&mut self, referrer: ComponentAddress) -> &mut PoolTestHelper {
+        let manifest_builder = mem::take(&mut self.registry.env.manifest_builder);
+        self.registry.env.manifest_builder = manifest_builder.call_method(
+            self.pool_address.unwrap(),
+            "claim_referrer_fees",
+            manifest_args!(referrer),
+        );
+        self.registry
+            .env
+            .new_instruction("claim_referrer_fees", 1, 0);
+        self
+    }
+
+    pub fn withdraw_referrer_fees(&mut self, referrer: ComponentAddress) -> &mut PoolTestHelper {
+        let manifest_builder = mem::take(&mut self.registry.env.manifest_builder);
+        self.registry.env.manifest_builder = manifest_builder.call_method(
+            self.pool_address.unwrap(),
+            "withdraw_referrer_fees",
+            manifest_args!(referrer),
+        );
+        self.registry
+            .env
+            .new_instruction("withdraw_referrer_fees", 1, 0);
+        self
+    }
+
+    pub fn claim_fees(
+        &mut self,
+        lp_positions: IndexSet<NonFungibleLocalId>,
+    ) -> &mut PoolTestHelper {
+        let account = self.registry.env.account;
+        let pool_address = self.pool_address.unwrap();
+        let manifest_builder = mem::replace(
+            &mut self.registry.env.manifest_builder,
+            ManifestBuilder::new(),
+        );
+        self.registry.env.manifest_builder = manifest_builder
+            .create_proof_from_account_of_non_fungibles(
+                account,
+                self.lp_address.unwrap(),
+                lp_positions,
+            )
+            .pop_from_auth_zone(self.registry.name("lp_proof"))
+            .with_name_lookup(|builder, lookup| {
+                let lp_proofs = lookup.proof(self.registry.name("lp_proof"));
+                builder.call_method(pool_address, "claim_fees", manifest_args!(lp_proofs))
+            });
+        self.registry.env.new_instruction("claim_fees", 3, 2);
+        self
+    }
+
+    pub fn claim_shape_fees(
+        &mut self,
+        lp_positions: IndexSet<NonFungibleLocalId>,
     ) -> &mut PoolTestHelper {
+        let account = self.registry.env.account;
+        let pool_address = self.pool_address.unwrap();
         let manifest_builder = mem::replace(
             &mut self.registry.env.manifest_builder,
             ManifestBuilder::new(),
         );
         self.registry.env.manifest_builder = manifest_builder
-            .withdraw_from_account(self.registry.env.account, input_address, input_amount)
-            .take_from_worktop(
-                input_address,
-                input_amount,
-                self.registry.name("input_bucket"),
+            .create_proof_from_account_of_non_fungibles(
+                account,
+                self.lp_address.unwrap(),
+                lp_positions,
             )
+            .pop_from_auth_zone(self.registry.name("lp_proof"))
             .with_name_lookup(|builder, lookup| {
-                let input_bucket = lookup.bucket(self.registry.name("input_bucket"));
-                builder.call_method(
-                    self.pool_address.unwrap(),
-                    "swap",
-                    manifest_args!(input_bucket),
-                )
+                let lp_proofs = lookup.proof(self.registry.name("lp_proof"));
+                builder.call_method(pool_address, "claim_shape_fees", manifest_args!(lp_proofs))
             });
-        self.registry.env.new_instruction("swap", 3, 2);
+        self.registry.env.new_instruction("claim_shape_fees", 3, 2);
         self
     }
 
-    pub fn claim_fees(
+    pub fn compound_fees(
         &mut self,
         lp_positions: IndexSet<NonFungibleLocalId>,
     ) -> &mut PoolTestHelper {
@@ -441,10 +1216,10 @@ impl PoolTestHelper {
             )
             .pop_from_auth_zone(self.registry.name("lp_proof"))
             .with_name_lookup(|builder, lookup| {
-                let lp_proofs = lookup.proof(self.registry.name("lp_proof"));
-                builder.call_method(pool_address, "claim_fees", manifest_args!(lp_proofs))
+                let lp_proof = lookup.proof(self.registry.name("lp_proof"));
+                builder.call_method(pool_address, "compound_fees", manifest_args!(lp_proof))
             });
-        self.registry.env.new_instruction("claim_fees", 3, 2);
+        self.registry.env.new_instruction("compound_fees", 3, 2);
         self
     }
 
@@ -486,6 +1261,30 @@ impl PoolTestHelper {
         self
     }
 
+    pub fn shape_positions(&mut self, shape_id: NonFungibleLocalId) -> &mut PoolTestHelper {
+        let pool_address = self.pool_address.unwrap();
+        let manifest_builder = mem::replace(
+            &mut self.registry.env.manifest_builder,
+            ManifestBuilder::new(),
+        );
+        self.registry.env.manifest_builder =
+            manifest_builder.call_method(pool_address, "shape_positions", manifest_args!(shape_id));
+        self.registry.env.new_instruction("shape_positions", 1, 0);
+        self
+    }
+
+    pub fn shape_value(&mut self, shape_id: NonFungibleLocalId) -> &mut PoolTestHelper {
+        let pool_address = self.pool_address.unwrap();
+        let manifest_builder = mem::replace(
+            &mut self.registry.env.manifest_builder,
+            ManifestBuilder::new(),
+        );
+        self.registry.env.manifest_builder =
+            manifest_builder.call_method(pool_address, "shape_value", manifest_args!(shape_id));
+        self.registry.env.new_instruction("shape_value", 1, 0);
+        self
+    }
+
     pub fn seconds_in_position(&mut self, nft_id: NonFungibleLocalId) -> &mut PoolTestHelper {
         let manifest_builder = mem::replace(
             &mut self.registry.env.manifest_builder,
@@ -502,6 +1301,20 @@ impl PoolTestHelper {
         self
     }
 
+    pub fn locked_until(&mut self, nft_id: NonFungibleLocalId) -> &mut PoolTestHelper {
+        let manifest_builder = mem::replace(
+            &mut self.registry.env.manifest_builder,
+            ManifestBuilder::new(),
+        );
+        self.registry.env.manifest_builder = manifest_builder.call_method(
+            self.pool_address.unwrap(),
+            "locked_until",
+            manifest_args!(nft_id),
+        );
+        self.registry.env.new_instruction("locked_until", 1, 0);
+        self
+    }
+
     pub fn seconds_in_position_batch_success(
         &mut self,
         seconds_inside_tests: &Vec<SecondsInsideTest>,
@@ -699,10 +1512,22 @@ impl PoolTestHelper {
         self.getter("fee_protocol_rate")
     }
 
+    pub fn fee_protocol_share(&mut self) -> &mut PoolTestHelper {
+        self.getter("fee_protocol_share")
+    }
+
+    pub fn pool_registry(&mut self) -> &mut PoolTestHelper {
+        self.getter("registry")
+    }
+
     pub fn flash_loan_fee_rate(&mut self) -> &mut PoolTestHelper {
         self.getter("flash_loan_fee_rate")
     }
 
+    pub fn fee_collection_mode(&mut self) -> &mut PoolTestHelper {
+        self.getter("fee_collection_mode")
+    }
+
     pub fn lp_address(&mut self) -> &mut PoolTestHelper {
         self.getter("lp_address")
     }
@@ -830,6 +1655,45 @@ impl PoolTestHelper {
         self
     }
 
+    pub fn instantiate_default_with_fee_collection_mode(
+        &mut self,
+        price_sqrt: PreciseDecimal,
+        input_fee_rate: Decimal,
+        fee_collection_mode: pool::FeeCollectionMode,
+        verbose: bool,
+    ) -> &mut PoolTestHelper {
+        self.set_whitelist_registry();
+        let package_address = self.registry.env.package_address("precision_pool");
+        let manifest_builder = mem::take(&mut self.registry.env.manifest_builder);
+        self.registry.env.manifest_builder = manifest_builder.call_function(
+            package_address,
+            "PrecisionPool",
+            "instantiate_with_fee_collection_mode",
+            manifest_args!(
+                self.x_address(),
+                self.y_address(),
+                price_sqrt,
+                1u32,
+                input_fee_rate,
+                dec!(0),
+                self.registry.registry_address.unwrap(),
+                Vec::<(ComponentAddress, ManifestBucket)>::new(),
+                self.registry.env.dapp_definition,
+                fee_collection_mode
+            ),
+        );
+        self.registry
+            .env
+            .new_instruction("instantiate_with_fee_collection_mode", 1, 0);
+        let receipt = self.registry.execute_expect_success(verbose);
+        let (pool_address, lp_address): (ComponentAddress, ResourceAddress) =
+            receipt.outputs("instantiate_with_fee_collection_mode")[0];
+        self.pool_address = Some(pool_address);
+        self.lp_address = Some(lp_address);
+        self.price_sqrt = Some(price_sqrt);
+        self
+    }
+
     pub fn new_default_with_positions(
         price_sqrt: PreciseDecimal,
         positions: &[LiquidityPosition],
@@ -881,6 +1745,30 @@ impl PoolTestHelper {
         self
     }
 
+    pub fn collect_protocol_fees(&mut self) -> &mut PoolTestHelper {
+        let manifest_builder = mem::take(&mut self.registry.env.manifest_builder);
+        self.registry.env.manifest_builder = manifest_builder.call_method(
+            self.pool_address.unwrap(),
+            "collect_protocol_fees",
+            manifest_args!(),
+        );
+        self.registry
+            .env
+            .new_instruction("collect_protocol_fees", 1, 0);
+        self
+    }
+
+    pub fn protocol_fees(&mut self) -> &mut PoolTestHelper {
+        let manifest_builder = mem::take(&mut self.registry.env.manifest_builder);
+        self.registry.env.manifest_builder = manifest_builder.call_method(
+            self.pool_address.unwrap(),
+            "protocol_fees",
+            manifest_args!(),
+        );
+        self.registry.env.new_instruction("protocol_fees", 1, 0);
+        self
+    }
+
     pub fn next_sync_time(&mut self) -> &mut PoolTestHelper {
         let manifest_builder = mem::take(&mut self.registry.env.manifest_builder);
         self.registry.env.manifest_builder = manifest_builder.call_method(
@@ -903,6 +1791,18 @@ impl PoolTestHelper {
         self
     }
 
+    pub fn active_tick_success(&mut self) -> Option<i32> {
+        let receipt = self.active_tick().registry.execute_expect_success(false);
+        receipt.outputs::<Option<i32>>("active_tick")[0]
+    }
+
+    pub fn position_data(&mut self, nft_id: NonFungibleLocalId) -> pool::LiquidityPosition {
+        self.registry
+            .env
+            .test_runner
+            .get_non_fungible_data(self.lp_address.unwrap(), nft_id)
+    }
+
     pub fn a_address(&self) -> ResourceAddress {
         self.registry.env.a_address
     }
@@ -980,6 +1880,7 @@ impl PoolTestHelper {
             x_input,
             y_input
         );
+        self.check_invariants_if_enabled();
 
         output_buckets
     }
@@ -1016,6 +1917,7 @@ impl PoolTestHelper {
             output_address,
             output_amount
         );
+        self.check_invariants_if_enabled();
     }
 
     pub fn swap_failure(&mut self, swap_type: SwapType, input_amount: Decimal) {
@@ -1047,6 +1949,7 @@ impl PoolTestHelper {
             x_output_expected,
             y_output_expected
         );
+        self.check_invariants_if_enabled();
     }
 
     pub fn removable_liquidity_success(
@@ -1100,6 +2003,7 @@ impl PoolTestHelper {
             x_fee_expected,
             y_fee_expected
         );
+        self.check_invariants_if_enabled();
     }
 
     pub fn claimable_fees_success(
@@ -1151,6 +2055,14 @@ impl PoolTestHelper {
         );
     }
 
+    /// Returns the amounts of two fungible output buckets, e.g. the output and remainder of a swap.
+    pub fn amounts(output_buckets: &[ResourceSpecifier]) -> (Decimal, Decimal) {
+        match output_buckets {
+            [Amount(_, first), Amount(_, second)] => (*first, *second),
+            _ => panic!("Expected two fungible output buckets."),
+        }
+    }
+
     pub fn input_address(&self, swap_type: SwapType) -> ResourceAddress {
         match swap_type {
             SwapType::SellX => self.x_address(),
@@ -1213,6 +2125,55 @@ impl PoolTestHelper {
         self
     }
 
+    pub fn snapshot(&mut self) -> &mut PoolTestHelper {
+        let manifest_builder = mem::take(&mut self.registry.env.manifest_builder);
+        self.registry.env.manifest_builder =
+            manifest_builder.call_method(self.pool_address.unwrap(), "snapshot", manifest_args!());
+        self.registry.env.new_instruction("snapshot", 1, 0);
+        self
+    }
+
+    pub fn snapshot_ticks(&mut self, cursor: Option<i32>, limit: u32) -> &mut PoolTestHelper {
+        let manifest_builder = mem::take(&mut self.registry.env.manifest_builder);
+        self.registry.env.manifest_builder = manifest_builder.call_method(
+            self.pool_address.unwrap(),
+            "snapshot_ticks",
+            manifest_args!(cursor, limit),
+        );
+        self.registry.env.new_instruction("snapshot_ticks", 1, 0);
+        self
+    }
+
+    pub fn audit(&mut self) -> &mut PoolTestHelper {
+        let manifest_builder = mem::take(&mut self.registry.env.manifest_builder);
+        self.registry.env.manifest_builder =
+            manifest_builder.call_method(self.pool_address.unwrap(), "audit", manifest_args!());
+        self.registry.env.new_instruction("audit", 1, 0);
+        self
+    }
+
+    pub fn assert_invariants(&mut self) -> pool::PoolAudit {
+        let receipt = self.audit().registry.execute_expect_success(false);
+        let audit: pool::PoolAudit = receipt.outputs("audit")[0].clone();
+        assert!(
+            audit.is_solvent(),
+            "The vaults do not cover the removable amounts and claimable fees: {:?}",
+            audit
+        );
+        assert!(
+            audit.is_consistent(),
+            "The ticks or the active liquidity do not match the positions: {:?}",
+            audit
+        );
+        audit
+    }
+
+    fn check_invariants_if_enabled(&mut self) {
+        if self.check_invariants && self.pool_address.is_some() {
+            self.assert_invariants();
+        }
+    }
+
     pub fn oldest_observation_at(&mut self) -> &mut PoolTestHelper {
         let manifest_builder = mem::take(&mut self.registry.env.manifest_builder);
         self.registry.env.manifest_builder = manifest_builder.call_method(
@@ -1254,6 +2215,220 @@ impl PoolTestHelper {
         self.lock_metadata("hook_packages")
     }
 
+    /// Instantiates a `TestHook` executing the given `calls`, requires a helper created with `new_with_test_hook`.
+    pub fn instantiate_test_hook(
+        &mut self,
+        calls: Vec<HookCall>,
+    ) -> (ComponentAddress, ResourceAddress) {
+        let package_address = self.registry.env.package_address("test_hook");
+        let manifest_builder = mem::take(&mut self.registry.env.manifest_builder);
+        self.registry.env.manifest_builder = manifest_builder.call_function(
+            package_address,
+            "TestHook",
+            "instantiate",
+            manifest_args!(calls, TestAccess::new(), self.x_address(), self.y_address()),
+        );
+        self.registry.env.new_instruction("instantiate", 1, 0);
+
+        let receipt = self.registry.execute_expect_success(false);
+
+        let new_resource_ads = receipt
+            .execution_receipt
+            .expect_commit_success()
+            .new_resource_addresses();
+
+        let outputs: Vec<(ComponentAddress, Bucket)> = receipt.outputs("instantiate");
+
+        (outputs[0].0, new_resource_ads[0])
+    }
+
+    /// Instantiates a `TestRegistry` answering every sync with the given response, requires a helper created with
+    /// `new_with_test_hook`.
+    pub fn instantiate_test_registry(
+        &mut self,
+        fee_protocol_share: Decimal,
+        next_sync_time: u64,
+    ) -> ComponentAddress {
+        let package_address = self.registry.env.package_address("test_hook");
+        let manifest_builder = mem::take(&mut self.registry.env.manifest_builder);
+        self.registry.env.manifest_builder = manifest_builder.call_function(
+            package_address,
+            "TestRegistry",
+            "instantiate",
+            manifest_args!(
+                self.x_address(),
+                self.y_address(),
+                fee_protocol_share,
+                next_sync_time
+            ),
+        );
+        self.registry.env.new_instruction("instantiate", 1, 0);
+        let receipt = self.registry.execute_expect_success(false);
+        receipt.outputs("instantiate")[0]
+    }
+
+    /// Converts the ticks to price square roots with the given `TickMathBenchmark` function, requires a helper
+    /// created with `new_with_test_hook`.
+    pub fn tick_to_price_sqrt_benchmark(
+        &mut self,
+        function_name: &str,
+        ticks: Vec<i32>,
+    ) -> &mut PoolTestHelper {
+        let package_address = self.registry.env.package_address("test_hook");
+        let manifest_builder = mem::take(&mut self.registry.env.manifest_builder);
+        self.registry.env.manifest_builder = manifest_builder.call_function(
+            package_address,
+            "TickMathBenchmark",
+            function_name,
+            manifest_args!(ticks),
+        );
+        self.registry.env.new_instruction(function_name, 1, 0);
+        self
+    }
+
+    /// Instantiates a `TestReferrer`, requires a helper created with `new_with_test_hook`.
+    pub fn instantiate_test_referrer(&mut self) -> ComponentAddress {
+        let package_address = self.registry.env.package_address("test_hook");
+        let manifest_builder = mem::take(&mut self.registry.env.manifest_builder);
+        self.registry.env.manifest_builder = manifest_builder.call_function(
+            package_address,
+            "TestReferrer",
+            "instantiate",
+            manifest_args!(),
+        );
+        self.registry.env.new_instruction("instantiate", 1, 0);
+        let receipt = self.registry.execute_expect_success(false);
+        receipt.outputs("instantiate")[0]
+    }
+
+    pub fn test_referrer_withdraw_referrer_fees(
+        &mut self,
+        referrer_address: ComponentAddress,
+    ) -> &mut PoolTestHelper {
+        let manifest_builder = mem::take(&mut self.registry.env.manifest_builder);
+        self.registry.env.manifest_builder = manifest_builder.call_method(
+            referrer_address,
+            "withdraw_referrer_fees",
+            manifest_args!(self.pool_address.unwrap()),
+        );
+        self.registry
+            .env
+            .new_instruction("withdraw_referrer_fees", 1, 0);
+        self
+    }
+
+    /// Instantiates a `TestRegistryInvalid` answering every sync with an undecodable response, requires a helper
+    /// created with `new_with_test_hook`.
+    pub fn instantiate_test_registry_invalid(&mut self) -> ComponentAddress {
+        let package_address = self.registry.env.package_address("test_hook");
+        let manifest_builder = mem::take(&mut self.registry.env.manifest_builder);
+        self.registry.env.manifest_builder = manifest_builder.call_function(
+            package_address,
+            "TestRegistryInvalid",
+            "instantiate",
+            manifest_args!(self.x_address(), self.y_address()),
+        );
+        self.registry.env.new_instruction("instantiate", 1, 0);
+        let receipt = self.registry.execute_expect_success(false);
+        receipt.outputs("instantiate")[0]
+    }
+
+    pub fn set_test_registry_response(
+        &mut self,
+        registry_address: ComponentAddress,
+        fee_protocol_share: Decimal,
+        next_sync_time: u64,
+    ) -> &mut PoolTestHelper {
+        let manifest_builder = mem::take(&mut self.registry.env.manifest_builder);
+        self.registry.env.manifest_builder = manifest_builder.call_method(
+            registry_address,
+            "set_response",
+            manifest_args!(fee_protocol_share, next_sync_time),
+        );
+        self.registry.env.new_instruction("set_response", 1, 0);
+        self
+    }
+
+    pub fn set_test_registry_sync_panics(
+        &mut self,
+        registry_address: ComponentAddress,
+        sync_panics: bool,
+    ) -> &mut PoolTestHelper {
+        let manifest_builder = mem::take(&mut self.registry.env.manifest_builder);
+        self.registry.env.manifest_builder = manifest_builder.call_method(
+            registry_address,
+            "set_sync_panics",
+            manifest_args!(sync_panics),
+        );
+        self.registry.env.new_instruction("set_sync_panics", 1, 0);
+        self
+    }
+
+    pub fn test_registry_migrate_pool(
+        &mut self,
+        registry_address: ComponentAddress,
+        new_registry_address: ComponentAddress,
+    ) -> &mut PoolTestHelper {
+        let manifest_builder = mem::take(&mut self.registry.env.manifest_builder);
+        self.registry.env.manifest_builder = manifest_builder.call_method(
+            registry_address,
+            "migrate_pool",
+            manifest_args!(self.pool_address.unwrap(), new_registry_address),
+        );
+        self.registry.env.new_instruction("migrate_pool", 1, 0);
+        self
+    }
+
+    pub fn migrate_registry(
+        &mut self,
+        new_registry_address: ComponentAddress,
+    ) -> &mut PoolTestHelper {
+        let manifest_builder = mem::take(&mut self.registry.env.manifest_builder);
+        self.registry.env.manifest_builder = manifest_builder.call_method(
+            self.pool_address.unwrap(),
+            "migrate_registry",
+            manifest_args!(new_registry_address),
+        );
+        self.registry.env.new_instruction("migrate_registry", 1, 0);
+        self
+    }
+
+    pub fn test_registry_fees(
+        &mut self,
+        registry_address: ComponentAddress,
+    ) -> &mut PoolTestHelper {
+        let manifest_builder = mem::take(&mut self.registry.env.manifest_builder);
+        self.registry.env.manifest_builder =
+            manifest_builder.call_method(registry_address, "fees", manifest_args!());
+        self.registry.env.new_instruction("fees", 1, 0);
+        self
+    }
+
+    pub fn instantiate_default_with_registry(
+        &mut self,
+        price_sqrt: PreciseDecimal,
+        input_fee_rate: Decimal,
+        registry_address: ComponentAddress,
+        verbose: bool,
+    ) -> &mut PoolTestHelper {
+        self.instantiate(
+            self.x_address(),
+            self.y_address(),
+            price_sqrt,
+            input_fee_rate,
+            dec!(0),
+            registry_address,
+            vec![],
+        );
+        let receipt = self.registry.execute_expect_success(verbose);
+        let (pool_address, lp_address): (ComponentAddress, ResourceAddress) =
+            receipt.outputs("instantiate")[0];
+        self.pool_address = Some(pool_address);
+        self.lp_address = Some(lp_address);
+        self.price_sqrt = Some(price_sqrt);
+        self
+    }
+
     pub fn set_whitelist_packages(
         &mut self,
         metadata_key: &str,
@@ -1665,14 +2840,7 @@ pub fn swap_with_hook_action_test(
     after_swap_fee_rate: Option<Decimal>,
     expect_success: bool,
 ) {
-    let packages: HashMap<&str, &str> = vec![
-        ("registry", "registry"),
-        ("precision_pool", "."),
-        ("test_hook", "test_hook"),
-    ]
-    .into_iter()
-    .collect();
-    let mut helper = PoolTestHelper::new_with_packages(packages, true);
+    let mut helper = PoolTestHelper::new_with_test_hook();
 
     helper.set_whitelist_hook("test_hook");
 
@@ -1737,51 +2905,13 @@ pub fn removable_liquidity_with_remove_hook(
     y_output_expected: Decimal,
     minimum_removable_fraction_expected: Decimal,
 ) {
-    let packages: HashMap<&str, &str> = vec![
-        ("registry", "registry"),
-        ("precision_pool", "."),
-        ("test_hook", "test_hook"),
-    ]
-    .into_iter()
-    .collect();
-    let mut helper = PoolTestHelper::new_with_packages(packages, true);
+    let mut helper = PoolTestHelper::new_with_test_hook();
 
     helper.set_whitelist_registry();
     helper.set_whitelist_hook("test_hook");
     helper.registry.execute_expect_success(false);
 
-    let package_address = helper.registry.env.package_address("test_hook");
-    let manifest_builder = mem::replace(
-        &mut helper.registry.env.manifest_builder,
-        ManifestBuilder::new(),
-    );
-    let calls = vec![HookCall::AfterRemoveLiquidity];
-    helper.registry.env.manifest_builder = manifest_builder.call_function(
-        package_address,
-        "TestHook",
-        "instantiate",
-        manifest_args!(
-            calls,
-            TestAccess::new(),
-            helper.x_address(),
-            helper.y_address()
-        ),
-    );
-    helper.registry.env.new_instruction("instantiate", 1, 0);
-
-    let receipt = helper.registry.execute_expect_success(false);
-
-    let new_resource_ads = receipt
-        .execution_receipt
-        .expect_commit_success()
-        .new_resource_addresses();
-
-    let outputs: Vec<(ComponentAddress, Bucket)> = receipt.outputs("instantiate");
-
-    let hook_address = outputs[0].0;
-    let hook_badge_address = new_resource_ads[0];
-
-    let hook_infos = vec![(hook_address, hook_badge_address)];
+    let hook_infos = vec![helper.instantiate_test_hook(vec![HookCall::AfterRemoveLiquidity])];
 
     helper.instantiate_default_with_hooks(pdec!(1), hook_infos, false);
     helper
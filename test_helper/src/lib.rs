@@ -1,5 +1,9 @@
+pub mod benchmark;
 pub mod constants;
 pub mod helper;
+pub mod specification;
 
+pub use benchmark::*;
 pub use constants::*;
 pub use helper::*;
+pub use specification::*;
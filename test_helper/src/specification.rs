@@ -0,0 +1,382 @@
+//! Rust port of the reference model in `tests/specification_concentrated_pool`.
+//!
+//! The model follows the formulas of the specification (`add_liquidity.py`, `swap.py`, `remove_liquidity.py`
+//! and `claim_fee.py`) instead of the implementation: liquidity is tracked per position, ticks are derived from
+//! the positions for every swap and LP fees are distributed to the positions active during a swap step.
+//! The only difference is the arithmetic: the specification uses 500 digits while the port uses `PreciseDecimal`.
+//! Therefore, results are compared within `SPEC_RELATIVE_TOLERANCE` and `SPEC_ABSOLUTE_TOLERANCE`.
+use scrypto::prelude::*;
+use std::cmp::{max, min};
+use std::collections::BTreeMap;
+
+/// Relative deviation allowed between the pool and the specification model.
+pub const SPEC_RELATIVE_TOLERANCE: Decimal = dec!("0.0000000001");
+/// Absolute deviation allowed between the pool and the specification model, covering the rounding of each step to
+/// the token divisibility.
+pub const SPEC_ABSOLUTE_TOLERANCE: Decimal = dec!("0.000000000000001");
+
+// Two units of the token divisibility (18), deducted before calculating the liquidity of a position.
+const ATTO_TWO_DECIMAL: Decimal = dec!("0.000000000000000002");
+
+pub fn spec_tick_to_price_sqrt(tick: i32) -> PreciseDecimal {
+    pdec!("1.000049998750062496094023416993798697")
+        .checked_powi(tick as i64)
+        .unwrap()
+}
+
+fn floor_to_decimal(value: PreciseDecimal) -> Decimal {
+    Decimal::try_from(
+        value
+            .checked_round(18, RoundingMode::ToNegativeInfinity)
+            .unwrap(),
+    )
+    .unwrap()
+}
+
+fn ceil_to_decimal(value: PreciseDecimal) -> Decimal {
+    Decimal::try_from(
+        value
+            .checked_round(18, RoundingMode::ToPositiveInfinity)
+            .unwrap(),
+    )
+    .unwrap()
+}
+
+/// Asserts that a value of the pool matches the specification model within the rounding bounds.
+pub fn assert_within_spec_bounds(actual: Decimal, expected: Decimal, context: &str) {
+    let tolerance = max(
+        SPEC_ABSOLUTE_TOLERANCE,
+        expected.checked_abs().unwrap() * SPEC_RELATIVE_TOLERANCE,
+    );
+    assert!(
+        (actual - expected).checked_abs().unwrap() <= tolerance,
+        "[{}]: The pool returned {} but the specification model {}.",
+        context,
+        actual,
+        expected
+    );
+}
+
+#[derive(Clone, Debug)]
+pub struct SpecPosition {
+    pub liquidity: PreciseDecimal,
+    pub left_bound: i32,
+    pub right_bound: i32,
+    pub x_fee: PreciseDecimal,
+    pub y_fee: PreciseDecimal,
+}
+
+impl SpecPosition {
+    // See `position_is_active` in `swap.py`.
+    fn is_active(&self, price_a_sqrt: PreciseDecimal, price_b_sqrt: PreciseDecimal) -> bool {
+        let price_mid_sqrt = (price_a_sqrt + price_b_sqrt) / pdec!(2);
+        spec_tick_to_price_sqrt(self.left_bound) <= price_mid_sqrt
+            && price_mid_sqrt <= spec_tick_to_price_sqrt(self.right_bound)
+    }
+}
+
+/// State of the specification model: the price, the fee rates and all positions ever added.
+///
+/// Positions are identified by their index, which equals the LP counter of the pool minus one.
+/// Removed positions are kept as `None`.
+#[derive(Clone, Debug)]
+pub struct SpecPool {
+    pub price_sqrt: PreciseDecimal,
+    pub input_fee_rate: Decimal,
+    pub fee_protocol_share: Decimal,
+    pub positions: Vec<Option<SpecPosition>>,
+}
+
+impl SpecPool {
+    pub fn new(price_sqrt: PreciseDecimal, input_fee_rate: Decimal) -> Self {
+        Self {
+            price_sqrt,
+            input_fee_rate,
+            fee_protocol_share: dec!(0),
+            positions: vec![],
+        }
+    }
+
+    /// Indices of the positions which have not been removed.
+    pub fn active_positions(&self) -> Vec<usize> {
+        self.positions
+            .iter()
+            .enumerate()
+            .filter_map(|(index, position)| position.as_ref().map(|_| index))
+            .collect()
+    }
+
+    // See `calc_amounts_for_liquidity` in `add_liquidity.py`.
+    fn amounts_for_liquidity(
+        &self,
+        liquidity: PreciseDecimal,
+        left_bound: i32,
+        right_bound: i32,
+    ) -> (PreciseDecimal, PreciseDecimal) {
+        let price_left_sqrt = spec_tick_to_price_sqrt(left_bound);
+        let price_right_sqrt = spec_tick_to_price_sqrt(right_bound);
+        if self.price_sqrt < price_left_sqrt {
+            return (
+                liquidity * (pdec!(1) / price_left_sqrt - pdec!(1) / price_right_sqrt),
+                pdec!(0),
+            );
+        }
+        if self.price_sqrt < price_right_sqrt {
+            return (
+                liquidity * (pdec!(1) / self.price_sqrt - pdec!(1) / price_right_sqrt),
+                liquidity * (self.price_sqrt - price_left_sqrt),
+            );
+        }
+        (pdec!(0), liquidity * (price_right_sqrt - price_left_sqrt))
+    }
+
+    /// Adds a position, see `calc_allowed_inputs` in `add_liquidity.py`.
+    ///
+    /// # Returns
+    /// The index of the position and the added amounts of token X and token Y.
+    pub fn add_liquidity(
+        &mut self,
+        left_bound: i32,
+        right_bound: i32,
+        x_amount: Decimal,
+        y_amount: Decimal,
+    ) -> (usize, Decimal, Decimal) {
+        let x_safe = PreciseDecimal::from(max(x_amount - ATTO_TWO_DECIMAL, dec!(0)));
+        let y_safe = PreciseDecimal::from(max(y_amount - ATTO_TWO_DECIMAL, dec!(0)));
+        let price_left_sqrt = spec_tick_to_price_sqrt(left_bound);
+        let price_right_sqrt = spec_tick_to_price_sqrt(right_bound);
+
+        let (x_liquidity, y_liquidity) = if self.price_sqrt < price_left_sqrt {
+            (
+                Some(x_safe / (pdec!(1) / price_left_sqrt - pdec!(1) / price_right_sqrt)),
+                None,
+            )
+        } else if self.price_sqrt < price_right_sqrt {
+            (
+                Some(x_safe / (pdec!(1) / self.price_sqrt - pdec!(1) / price_right_sqrt)),
+                (self.price_sqrt != price_left_sqrt)
+                    .then(|| y_safe / (self.price_sqrt - price_left_sqrt)),
+            )
+        } else {
+            (None, Some(y_safe / (price_right_sqrt - price_left_sqrt)))
+        };
+        let liquidity = match (x_liquidity, y_liquidity) {
+            (Some(x_liquidity), Some(y_liquidity)) => min(x_liquidity, y_liquidity),
+            (Some(liquidity), None) | (None, Some(liquidity)) => liquidity,
+            (None, None) => unreachable!(),
+        };
+
+        let (x_allowed, y_allowed) = self.amounts_for_liquidity(liquidity, left_bound, right_bound);
+        let x_allowed = ceil_to_decimal(x_allowed + PreciseDecimal::from(Decimal::ATTO));
+        let y_allowed = ceil_to_decimal(y_allowed + PreciseDecimal::from(Decimal::ATTO));
+        // Dust handling
+        let x_allowed = if x_amount - x_allowed <= ATTO_TWO_DECIMAL {
+            x_amount
+        } else {
+            x_allowed
+        };
+        let y_allowed = if y_amount - y_allowed <= ATTO_TWO_DECIMAL {
+            y_amount
+        } else {
+            y_allowed
+        };
+
+        self.positions.push(Some(SpecPosition {
+            liquidity,
+            left_bound,
+            right_bound,
+            x_fee: pdec!(0),
+            y_fee: pdec!(0),
+        }));
+        (self.positions.len() - 1, x_allowed, y_allowed)
+    }
+
+    // See `calculate_ticks` in `swap.py`.
+    fn ticks(&self) -> BTreeMap<i32, PreciseDecimal> {
+        let mut ticks = BTreeMap::new();
+        for position in self.positions.iter().flatten() {
+            *ticks.entry(position.left_bound).or_insert(pdec!(0)) += position.liquidity;
+            *ticks.entry(position.right_bound).or_insert(pdec!(0)) -= position.liquidity;
+        }
+        ticks
+    }
+
+    // See `Fees.add_fees` in `swap.py`, only the LP fees are tracked.
+    fn add_fees(
+        &mut self,
+        price_a_sqrt: PreciseDecimal,
+        price_b_sqrt: PreciseDecimal,
+        fee_lp: PreciseDecimal,
+        input_is_x: bool,
+    ) {
+        let total_liquidity: PreciseDecimal = self
+            .positions
+            .iter()
+            .flatten()
+            .filter(|position| position.is_active(price_a_sqrt, price_b_sqrt))
+            .fold(pdec!(0), |total, position| total + position.liquidity);
+        for position in self.positions.iter_mut().flatten() {
+            if !position.is_active(price_a_sqrt, price_b_sqrt) {
+                continue;
+            }
+            let fee = position.liquidity / total_liquidity * fee_lp;
+            match input_is_x {
+                true => position.x_fee += fee,
+                false => position.y_fee += fee,
+            }
+        }
+    }
+
+    /// Swaps `input_amount` of token X or Y, see `calculate_swap` in `swap.py`.
+    ///
+    /// # Returns
+    /// The output amount and the remaining input amount.
+    pub fn swap(&mut self, input_amount: Decimal, input_is_x: bool) -> (Decimal, Decimal) {
+        let ticks = self.ticks();
+        let input_fee_rate = PreciseDecimal::from(self.input_fee_rate);
+        let fee_lp_share = pdec!(1) - PreciseDecimal::from(self.fee_protocol_share);
+        let mut next_ticks: Vec<(i32, PreciseDecimal)> = ticks
+            .keys()
+            .map(|tick| (*tick, spec_tick_to_price_sqrt(*tick)))
+            .filter(|(_, price_tick_sqrt)| match input_is_x {
+                true => *price_tick_sqrt <= self.price_sqrt,
+                false => self.price_sqrt < *price_tick_sqrt,
+            })
+            .collect();
+        if input_is_x {
+            next_ticks.reverse();
+        }
+
+        let mut output = dec!(0);
+        let mut remaining_input = PreciseDecimal::from(input_amount);
+        for (_, price_next_tick_sqrt) in next_ticks {
+            // See `calc_active_liquidity` in `swap.py`.
+            let active_liquidity = ticks
+                .iter()
+                .filter(|(tick, _)| match input_is_x {
+                    true => spec_tick_to_price_sqrt(**tick) < self.price_sqrt,
+                    false => spec_tick_to_price_sqrt(**tick) <= self.price_sqrt,
+                })
+                .fold(pdec!(0), |total, (_, delta)| total + *delta);
+            if active_liquidity.is_zero() {
+                self.price_sqrt = price_next_tick_sqrt;
+                continue;
+            }
+
+            let remaining_input_net = remaining_input * (pdec!(1) - input_fee_rate);
+            let price_new_sqrt = match input_is_x {
+                true => {
+                    active_liquidity * self.price_sqrt
+                        / (active_liquidity + remaining_input_net * self.price_sqrt)
+                }
+                false => remaining_input_net / active_liquidity + self.price_sqrt,
+            };
+
+            // Partially consume the current range.
+            if (input_is_x && price_next_tick_sqrt < price_new_sqrt)
+                || (!input_is_x && price_new_sqrt < price_next_tick_sqrt)
+            {
+                output += output_in_step(
+                    active_liquidity,
+                    self.price_sqrt,
+                    price_new_sqrt,
+                    input_is_x,
+                );
+                let fee_lp = remaining_input * input_fee_rate * fee_lp_share;
+                self.add_fees(self.price_sqrt, price_new_sqrt, fee_lp, input_is_x);
+                remaining_input = pdec!(0);
+                self.price_sqrt = price_new_sqrt;
+                break;
+            }
+
+            // Fully consume the current range.
+            output += output_in_step(
+                active_liquidity,
+                self.price_sqrt,
+                price_next_tick_sqrt,
+                input_is_x,
+            );
+            let input_step = PreciseDecimal::from(input_in_step(
+                active_liquidity,
+                self.price_sqrt,
+                price_next_tick_sqrt,
+                input_is_x,
+            ));
+            let input_step_with_fees = input_step / (pdec!(1) - input_fee_rate);
+            let fee_lp = input_step_with_fees * input_fee_rate * fee_lp_share;
+            self.add_fees(self.price_sqrt, price_next_tick_sqrt, fee_lp, input_is_x);
+            remaining_input -= input_step_with_fees;
+            self.price_sqrt = price_next_tick_sqrt;
+
+            if remaining_input <= pdec!(0) {
+                break;
+            }
+        }
+
+        (output, floor_to_decimal(max(remaining_input, pdec!(0))))
+    }
+
+    /// Claims the LP fees of a position, see `Fees.claim_fee_lp` in `swap.py`.
+    pub fn claim_fees(&mut self, index: usize) -> (Decimal, Decimal) {
+        let position = self.positions[index].as_mut().unwrap();
+        let fees = (
+            floor_to_decimal(position.x_fee),
+            floor_to_decimal(position.y_fee),
+        );
+        position.x_fee = pdec!(0);
+        position.y_fee = pdec!(0);
+        fees
+    }
+
+    /// Removes a position including its LP fees, see `remove_liquidity` in `remove_liquidity.py`.
+    pub fn remove_liquidity(&mut self, index: usize) -> (Decimal, Decimal) {
+        let position = self.positions[index].take().unwrap();
+        let (x_amount, y_amount) = self.amounts_for_liquidity(
+            position.liquidity,
+            position.left_bound,
+            position.right_bound,
+        );
+        (
+            floor_to_decimal(x_amount + position.x_fee),
+            floor_to_decimal(y_amount + position.y_fee),
+        )
+    }
+}
+
+fn x_delta(
+    liquidity: PreciseDecimal,
+    price_a_sqrt: PreciseDecimal,
+    price_b_sqrt: PreciseDecimal,
+) -> PreciseDecimal {
+    (liquidity / price_a_sqrt - liquidity / price_b_sqrt)
+        .checked_abs()
+        .unwrap()
+}
+
+fn y_delta(
+    liquidity: PreciseDecimal,
+    price_a_sqrt: PreciseDecimal,
+    price_b_sqrt: PreciseDecimal,
+) -> PreciseDecimal {
+    liquidity * (price_a_sqrt - price_b_sqrt).checked_abs().unwrap()
+}
+
+fn input_in_step(
+    liquidity: PreciseDecimal,
+    price_a_sqrt: PreciseDecimal,
+    price_b_sqrt: PreciseDecimal,
+    input_is_x: bool,
+) -> Decimal {
+    let delta = if input_is_x { x_delta } else { y_delta };
+    ceil_to_decimal(delta(liquidity, price_a_sqrt, price_b_sqrt))
+}
+
+fn output_in_step(
+    liquidity: PreciseDecimal,
+    price_a_sqrt: PreciseDecimal,
+    price_b_sqrt: PreciseDecimal,
+    input_is_x: bool,
+) -> Decimal {
+    let delta = if input_is_x { y_delta } else { x_delta };
+    floor_to_decimal(delta(liquidity, price_a_sqrt, price_b_sqrt))
+}
@@ -1,3 +1,7 @@
 pub mod test_hook;
 pub mod test_hook_swap;
+pub mod test_referrer;
+pub mod test_registry;
+pub mod test_registry_invalid;
+pub mod tick_math_benchmark;
 mod utils;
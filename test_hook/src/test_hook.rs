@@ -10,6 +10,7 @@ mod test_hook {
         },
         methods {
             calls => PUBLIC;
+            added_positions => PUBLIC;
             before_instantiate => restrict_to: [hook_admin];
             after_instantiate => restrict_to: [hook_admin];
             before_swap => restrict_to: [hook_admin];
@@ -23,6 +24,7 @@ mod test_hook {
     struct TestHook {
         calls: Vec<HookCall>,
         calls_access: TestAccess,
+        added_positions: Vec<NonFungibleLocalId>,
         x_vault: Vault,
         y_vault: Vault,
     }
@@ -55,6 +57,7 @@ mod test_hook {
             let hook_component = (Self {
                 calls,
                 calls_access,
+                added_positions: Vec::new(),
                 x_vault,
                 y_vault,
             })
@@ -72,6 +75,10 @@ mod test_hook {
             self.calls.clone()
         }
 
+        pub fn added_positions(&mut self) -> Vec<NonFungibleLocalId> {
+            self.added_positions.clone()
+        }
+
         pub fn before_instantiate(
             &mut self,
             state: BeforeInstantiateState,
@@ -162,8 +169,9 @@ mod test_hook {
 
             debug!(
                 "[TEST HOOK] after_add_liquidity: Liquidity_position_id: {:?}",
-                _liquidity_position_id.unwrap()
+                _liquidity_position_id.clone().unwrap()
             );
+            self.added_positions.push(_liquidity_position_id.unwrap());
             (add_liquidity_state, lp_token)
         }
 
@@ -0,0 +1,25 @@
+use scrypto::prelude::*;
+
+#[blueprint]
+mod test_referrer {
+    /// Referrer component which can not accept deposits and therefore withdraws its referrer fees itself.
+    struct TestReferrer {}
+
+    impl TestReferrer {
+        pub fn instantiate() -> Global<TestReferrer> {
+            (Self {})
+                .instantiate()
+                .prepare_to_globalize(OwnerRole::None)
+                .globalize()
+        }
+
+        /// Withdraws the referrer fees accrued for this component in the given pool.
+        pub fn withdraw_referrer_fees(&self, pool_address: ComponentAddress) -> (Bucket, Bucket) {
+            let pool: Global<AnyComponent> = pool_address.into();
+            pool.call::<(ComponentAddress,), (Bucket, Bucket)>(
+                "withdraw_referrer_fees",
+                &(Runtime::global_address(),),
+            )
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use scrypto::prelude::*;
+
+#[blueprint]
+mod test_registry {
+    struct TestRegistry {
+        x_vault: Vault,
+        y_vault: Vault,
+        fee_protocol_share: Decimal,
+        next_sync_time: u64,
+        sync_panics: bool,
+    }
+
+    impl TestRegistry {
+        pub fn instantiate(
+            x_address: ResourceAddress,
+            y_address: ResourceAddress,
+            fee_protocol_share: Decimal,
+            next_sync_time: u64,
+        ) -> Global<TestRegistry> {
+            (Self {
+                x_vault: Vault::new(x_address),
+                y_vault: Vault::new(y_address),
+                fee_protocol_share,
+                next_sync_time,
+                sync_panics: false,
+            })
+            .instantiate()
+            .prepare_to_globalize(OwnerRole::None)
+            .globalize()
+        }
+
+        pub fn sync(
+            &mut self,
+            _pool_address: ComponentAddress,
+            x_bucket: Bucket,
+            y_bucket: Bucket,
+        ) -> (Decimal, u64) {
+            assert!(!self.sync_panics, "Test registry is broken.");
+            self.x_vault.put(x_bucket);
+            self.y_vault.put(y_bucket);
+            (self.fee_protocol_share, self.next_sync_time)
+        }
+
+        pub fn set_response(&mut self, fee_protocol_share: Decimal, next_sync_time: u64) {
+            self.fee_protocol_share = fee_protocol_share;
+            self.next_sync_time = next_sync_time;
+        }
+
+        pub fn set_sync_panics(&mut self, sync_panics: bool) {
+            self.sync_panics = sync_panics;
+        }
+
+        pub fn fees(&self) -> (Decimal, Decimal) {
+            (self.x_vault.amount(), self.y_vault.amount())
+        }
+
+        pub fn migrate_pool(
+            &mut self,
+            pool_address: ComponentAddress,
+            new_registry_address: ComponentAddress,
+        ) {
+            let pool: Global<AnyComponent> = pool_address.into();
+            pool.call::<(ComponentAddress,), ()>("migrate_registry", &(new_registry_address,));
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use scrypto::prelude::*;
+
+#[blueprint]
+mod test_registry_invalid {
+    struct TestRegistryInvalid {
+        x_vault: Vault,
+        y_vault: Vault,
+    }
+
+    impl TestRegistryInvalid {
+        pub fn instantiate(
+            x_address: ResourceAddress,
+            y_address: ResourceAddress,
+        ) -> Global<TestRegistryInvalid> {
+            (Self {
+                x_vault: Vault::new(x_address),
+                y_vault: Vault::new(y_address),
+            })
+            .instantiate()
+            .prepare_to_globalize(OwnerRole::None)
+            .globalize()
+        }
+
+        /// Accepts the protocol fees, but returns a response the pool cannot decode.
+        pub fn sync(
+            &mut self,
+            _pool_address: ComponentAddress,
+            x_bucket: Bucket,
+            y_bucket: Bucket,
+        ) -> String {
+            self.x_vault.put(x_bucket);
+            self.y_vault.put(y_bucket);
+            "invalid".to_string()
+        }
+    }
+}
@@ -0,0 +1,24 @@
+use precision_pool_math::constants::TICK_BASE_SQRT;
+use precision_pool_math::tick_to_price_sqrt;
+use scrypto::prelude::*;
+
+#[blueprint]
+mod tick_math_benchmark {
+    /// Runs the tick to price conversions inside the engine, so their cost units can be compared.
+    struct TickMathBenchmark {}
+
+    impl TickMathBenchmark {
+        /// Converts the ticks with the precomputed power table used by the pool.
+        pub fn tick_to_price_sqrt_table(ticks: Vec<i32>) -> Vec<PreciseDecimal> {
+            ticks.into_iter().map(tick_to_price_sqrt).collect()
+        }
+
+        /// Converts the ticks by exponentiating the tick base, as the pool did before the power table.
+        pub fn tick_to_price_sqrt_powi(ticks: Vec<i32>) -> Vec<PreciseDecimal> {
+            ticks
+                .into_iter()
+                .map(|tick| TICK_BASE_SQRT.checked_powi(tick.into()).unwrap())
+                .collect()
+        }
+    }
+}
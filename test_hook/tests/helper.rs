@@ -85,9 +85,21 @@ impl HookTestTestHelper {
 
         let hook_badge_address: Vec<(ComponentAddress, Bucket)> =
             receipt.outputs("instantiate_test_hook");
+        self.hook_address = Some(hook_badge_address[0].0);
         (hook_badge_address[0].0, new_resource_ads[0])
     }
 
+    pub fn added_positions(&mut self) -> Vec<NonFungibleLocalId> {
+        let hook_address = self.hook_address.unwrap();
+        let manifest_builder =
+            mem::replace(&mut self.env().manifest_builder, ManifestBuilder::new());
+        self.env().manifest_builder =
+            manifest_builder.call_method(hook_address, "added_positions", manifest_args!());
+        self.env().new_instruction("added_positions", 1, 0);
+        let receipt = self.execute_expect_success(false);
+        receipt.outputs::<Vec<NonFungibleLocalId>>("added_positions")[0].clone()
+    }
+
     pub fn execute_all_calls(&mut self, hooks: Vec<(ComponentAddress, ResourceAddress)>) {
         // Whitelist registry and hook
         self.pool.set_whitelist_registry();
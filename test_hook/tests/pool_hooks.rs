@@ -6,6 +6,7 @@ mod test_hook_precision_pool {
     use helper::HookTestTestHelper;
     use precision_pool_hooks::HookCall;
     use scrypto::prelude::*;
+    use scrypto_testenv::*;
     use test_hook::test_hook::TestAccess;
 
     #[test]
@@ -121,6 +122,36 @@ mod test_hook_precision_pool {
         hook_helper.execute_all_calls(hooks);
     }
 
+    #[test]
+    fn test_compound_fees_calls_add_liquidity_hooks() {
+        let mut hook_helper = HookTestTestHelper::new();
+        let calls = vec![HookCall::BeforeAddLiquidity, HookCall::AfterAddLiquidity];
+        let hooks = vec![hook_helper.instantiate_test_hook_output(calls, TestAccess::new())];
+        hook_helper.pool.set_whitelist_hook("test_hook");
+        hook_helper.pool.instantiate_default_with_fees_and_hooks(
+            pdec!(1),
+            dec!("0.01"),
+            dec!(0),
+            hooks,
+            false,
+        );
+        hook_helper
+            .pool
+            .add_liquidity_default(-10000, 10000, dec!(10), dec!(10));
+        hook_helper.pool.swap(hook_helper.pool.x_address(), dec!(1));
+        hook_helper.pool.swap(hook_helper.pool.y_address(), dec!(1));
+        hook_helper.execute_expect_success(false);
+
+        hook_helper
+            .pool
+            .compound_fees(nft_ids!(1))
+            .registry
+            .execute_expect_success(false);
+
+        // The hooks are called for the compounded position without minting a new one.
+        assert_eq!(hook_helper.added_positions(), vec![nft_id!(1), nft_id!(1)]);
+    }
+
     fn hook_default_helper() -> (HookTestTestHelper, Vec<(ComponentAddress, ResourceAddress)>) {
         let mut hook_helper = HookTestTestHelper::new();
 
@@ -0,0 +1,109 @@
+#[cfg(test)]
+mod precision_pool_add_liquidity_by_price {
+    use precision_pool::pool_math::TickRounding;
+    use precision_pool_test_helper::*;
+    use scrypto::prelude::*;
+    use scrypto_testenv::*;
+    use test_case::test_case;
+
+    fn helper_with_tick_spacing(tick_spacing: u32) -> PoolTestHelper {
+        let mut helper = PoolTestHelper::new();
+        let receipt = helper
+            .instantiate_tick_spacing(tick_spacing)
+            .registry
+            .execute_expect_success(false);
+        let (pool_address, lp_address): (ComponentAddress, ResourceAddress) =
+            receipt.outputs("instantiate")[0];
+        helper.pool_address = Some(pool_address);
+        helper.lp_address = Some(lp_address);
+        helper
+    }
+
+    fn position_bounds(helper: &mut PoolTestHelper, position_id: NonFungibleLocalId) -> (i32, i32) {
+        let position: precision_pool::pool::LiquidityPosition = helper
+            .registry
+            .env
+            .test_runner
+            .get_non_fungible_data(helper.lp_address.unwrap(), position_id);
+        (position.left_bound, position.right_bound)
+    }
+
+    // Price 0.5 is at tick -6931.8 and price 2 at tick 6931.8.
+    #[test_case(TickRounding::Inward, -6930, 6930)]
+    #[test_case(TickRounding::Outward, -6940, 6940)]
+    #[test_case(TickRounding::Nearest, -6930, 6930)]
+    fn test_add_liquidity_by_price(rounding: TickRounding, left_bound: i32, right_bound: i32) {
+        let mut helper = helper_with_tick_spacing(10);
+        helper
+            .add_liquidity_by_price(dec!("0.5"), dec!(2), rounding, DEC_10, DEC_10)
+            .registry
+            .execute_expect_success(false);
+        assert_eq!(
+            position_bounds(&mut helper, nft_id!(1)),
+            (left_bound, right_bound)
+        );
+    }
+
+    // Price 0.9 is at tick -1053.6 and price 1.1 at tick 953.1.
+    #[test_case(TickRounding::Inward, -1000, 900)]
+    #[test_case(TickRounding::Outward, -1100, 1000)]
+    #[test_case(TickRounding::Nearest, -1100, 1000)]
+    fn test_add_liquidity_by_price_negative_ticks(
+        rounding: TickRounding,
+        left_bound: i32,
+        right_bound: i32,
+    ) {
+        let mut helper = helper_with_tick_spacing(100);
+        helper
+            .add_liquidity_by_price(dec!("0.9"), dec!("1.1"), rounding, DEC_10, DEC_10)
+            .registry
+            .execute_expect_success(false);
+        assert_eq!(
+            position_bounds(&mut helper, nft_id!(1)),
+            (left_bound, right_bound)
+        );
+    }
+
+    #[test]
+    fn test_add_liquidity_by_price_exact_ticks() {
+        let mut helper = helper_with_tick_spacing(1);
+        helper
+            .add_liquidity_by_price(dec!(1), dec!(2), TickRounding::Outward, DEC_10, DEC_10)
+            .registry
+            .execute_expect_success(false);
+        assert_eq!(position_bounds(&mut helper, nft_id!(1)), (0, 6932));
+    }
+
+    #[test]
+    fn test_add_liquidity_by_price_empty_range() {
+        let mut helper = helper_with_tick_spacing(10);
+        helper
+            .add_liquidity_by_price(
+                dec!("1.0001"),
+                dec!("1.0002"),
+                TickRounding::Inward,
+                DEC_10,
+                DEC_10,
+            )
+            .registry
+            .execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_add_liquidity_by_price_zero_price() {
+        let mut helper = helper_with_tick_spacing(10);
+        helper
+            .add_liquidity_by_price(dec!(0), dec!(2), TickRounding::Inward, DEC_10, DEC_10)
+            .registry
+            .execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_add_liquidity_by_price_left_greater_than_right() {
+        let mut helper = helper_with_tick_spacing(10);
+        helper
+            .add_liquidity_by_price(dec!(2), dec!("0.5"), TickRounding::Outward, DEC_10, DEC_10)
+            .registry
+            .execute_expect_failure(false);
+    }
+}
@@ -0,0 +1,135 @@
+#[cfg(test)]
+mod precision_pool_audit {
+    use common::pools::SwapType;
+    use precision_pool_test_helper::*;
+    use scrypto::prelude::*;
+    use scrypto_testenv::*;
+
+    const POSITIONS: [LiquidityPosition; 3] = [
+        LiquidityPosition {
+            left_bound: -300,
+            right_bound: -100,
+            x_amount: DEC_10,
+            y_amount: DEC_10,
+        },
+        LiquidityPosition {
+            left_bound: -100,
+            right_bound: 100,
+            x_amount: DEC_10,
+            y_amount: DEC_10,
+        },
+        LiquidityPosition {
+            left_bound: -100,
+            right_bound: 300,
+            x_amount: DEC_10,
+            y_amount: DEC_10,
+        },
+    ];
+
+    fn helper_with_positions() -> PoolTestHelper {
+        let mut helper = PoolTestHelper::new();
+        helper.instantiate_default_with_input_fee(pdec!(1), dec!(0.01), false);
+        helper.add_liquidity_default_batch(&POSITIONS);
+        helper.registry.execute_expect_success(false);
+        helper
+    }
+
+    #[test]
+    fn test_audit_empty_pool() {
+        let mut helper = PoolTestHelper::new();
+        helper.instantiate_default(pdec!(1), false);
+
+        let audit = helper.assert_invariants();
+
+        assert_eq!(audit.positions, 0);
+        assert_eq!(audit.x_removable, dec!(0));
+        assert_eq!(audit.y_removable, dec!(0));
+        assert_eq!(audit.active_liquidity, pdec!(0));
+        assert!(audit.inconsistent_ticks.is_empty());
+    }
+
+    #[test]
+    fn test_audit_after_add_liquidity() {
+        let mut helper = helper_with_positions();
+
+        let audit = helper.assert_invariants();
+
+        assert_eq!(audit.positions, POSITIONS.len() as u64);
+        assert!(audit.active_liquidity > pdec!(0));
+        assert_eq!(audit.x_claimable_fees, dec!(0));
+        assert_eq!(audit.y_claimable_fees, dec!(0));
+    }
+
+    #[test]
+    fn test_audit_removable_matches_removable_liquidity() {
+        let mut helper = helper_with_positions();
+        helper.swap(helper.x_address(), dec!(3));
+        helper.registry.execute_expect_success(false);
+
+        let audit = helper.assert_invariants();
+        let receipt = helper
+            .removable_liquidity(nft_ids!(1, 2, 3))
+            .registry
+            .execute_expect_success(false);
+        let removable: Vec<(IndexMap<ResourceAddress, Decimal>, Decimal)> =
+            receipt.outputs("removable_liquidity");
+
+        assert_eq!(
+            audit.x_removable + audit.x_claimable_fees,
+            *removable[0].0.get(&helper.x_address()).unwrap()
+        );
+        assert_eq!(
+            audit.y_removable + audit.y_claimable_fees,
+            *removable[0].0.get(&helper.y_address()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_audit_after_swaps_crossing_ticks() {
+        let mut helper = helper_with_positions();
+
+        for (swap_type, input_amount) in [(SwapType::SellX, dec!(15)), (SwapType::BuyX, dec!(25))] {
+            helper.swap_by_type(swap_type, input_amount);
+            helper.registry.execute_expect_success(false);
+            helper.assert_invariants();
+        }
+
+        let audit = helper.assert_invariants();
+        assert!(audit.x_fees > dec!(0));
+        assert!(audit.y_fees > dec!(0));
+    }
+
+    #[test]
+    fn test_audit_after_claim_and_remove() {
+        let mut helper = helper_with_positions();
+        helper.swap(helper.x_address(), dec!(5));
+        helper.swap(helper.y_address(), dec!(5));
+        helper.registry.execute_expect_success(false);
+
+        helper.claim_fees(nft_ids!(2));
+        helper.registry.execute_expect_success(false);
+        helper.assert_invariants();
+
+        helper.remove_liquidity(nft_ids!(1, 3));
+        helper.registry.execute_expect_success(false);
+        let audit = helper.assert_invariants();
+
+        assert_eq!(audit.positions, 1);
+        assert_eq!(audit.x_claimable_fees, dec!(0));
+    }
+
+    #[test]
+    fn test_audit_after_removing_all_positions() {
+        let mut helper = helper_with_positions();
+        helper.swap(helper.x_address(), dec!(5));
+        helper.registry.execute_expect_success(false);
+
+        helper.remove_liquidity(nft_ids!(1, 2, 3));
+        helper.registry.execute_expect_success(false);
+        let audit = helper.assert_invariants();
+
+        assert_eq!(audit.positions, 0);
+        assert_eq!(audit.active_liquidity, pdec!(0));
+        assert_eq!(audit.active_liquidity_expected, pdec!(0));
+    }
+}
@@ -0,0 +1,409 @@
+#[cfg(test)]
+mod precision_pool_benchmark {
+    use precision_pool_hooks::HookCall;
+    use precision_pool_test_helper::*;
+    use scrypto::prelude::*;
+    use scrypto_testenv::*;
+
+    // Records the execution cost units of the core operations and writes them as JSON reports to
+    // `target/benchmarks` (or `PRECISION_POOL_BENCHMARK_DIR`). Comparing the reports of two commits makes
+    // cost regressions visible, the assertions only cover the expected ordering of the measurements.
+
+    const POSITION_WIDTH: i32 = 10;
+    const POSITIONS_PER_TRANSACTION: usize = 20;
+
+    // Adjacent positions, the position with index 0 contains the price 1 in its center.
+    fn positions(from: i32, to: i32) -> Vec<LiquidityPosition> {
+        (from..to)
+            .map(|index| LiquidityPosition {
+                left_bound: index * POSITION_WIDTH - POSITION_WIDTH / 2,
+                right_bound: (index + 1) * POSITION_WIDTH - POSITION_WIDTH / 2,
+                x_amount: dec!(1),
+                y_amount: dec!(1),
+            })
+            .collect()
+    }
+
+    fn add_positions(helper: &mut PoolTestHelper, positions: &[LiquidityPosition]) {
+        for chunk in positions.chunks(POSITIONS_PER_TRANSACTION) {
+            helper.add_liquidity_default_batch(chunk);
+            helper.registry.execute_expect_success(false);
+        }
+    }
+
+    fn nft_range(count: u64) -> IndexSet<NonFungibleLocalId> {
+        (1..=count).map(NonFungibleLocalId::integer).collect()
+    }
+
+    fn assert_increasing(report: &BenchmarkReport, names: &[String]) {
+        for pair in names.windows(2) {
+            assert!(
+                report.get(&pair[0]).unwrap().execution_cost_units
+                    < report.get(&pair[1]).unwrap().execution_cost_units,
+                "Expected {} to consume less execution cost units than {}.",
+                pair[0],
+                pair[1]
+            );
+        }
+    }
+
+    #[test]
+    fn test_benchmark_swap_ticks_crossed() {
+        let mut report = BenchmarkReport::new("swap_ticks_crossed");
+        let ticks_crossed = [0, 1, 10, 100];
+        for max_ticks_crossed in ticks_crossed {
+            let mut helper = PoolTestHelper::new();
+            helper.instantiate_default(pdec!(1), false);
+            add_positions(&mut helper, &positions(-110, 10));
+
+            let receipt = helper
+                .swap_with_max_ticks_crossed(helper.x_address(), dec!(1000), max_ticks_crossed)
+                .registry
+                .execute_expect_success(false);
+            report.record(
+                &format!("swap_ticks_crossed_{}", max_ticks_crossed),
+                &receipt.execution_receipt,
+            );
+        }
+        report.write();
+
+        let names: Vec<String> = ticks_crossed
+            .iter()
+            .map(|max_ticks_crossed| format!("swap_ticks_crossed_{}", max_ticks_crossed))
+            .collect();
+        assert_increasing(&report, &names);
+    }
+
+    #[test]
+    fn test_benchmark_swap_sparse_ticks() {
+        // The swap crosses from a position around the price 1 into a position behind a gap of uninitialized
+        // ticks, which shows how the tick lookup scales with the distance between initialized ticks.
+        let mut report = BenchmarkReport::new("swap_sparse_ticks");
+        for gap in [10, 1000, 100000] {
+            let mut helper = PoolTestHelper::new();
+            helper.instantiate_default(pdec!(1), false);
+            add_positions(
+                &mut helper,
+                &[
+                    LiquidityPosition {
+                        left_bound: -POSITION_WIDTH / 2,
+                        right_bound: POSITION_WIDTH / 2,
+                        x_amount: dec!(1),
+                        y_amount: dec!(1),
+                    },
+                    LiquidityPosition {
+                        left_bound: POSITION_WIDTH / 2 + gap,
+                        right_bound: POSITION_WIDTH * 3 / 2 + gap,
+                        x_amount: dec!(1),
+                        y_amount: dec!(1),
+                    },
+                ],
+            );
+
+            let receipt = helper
+                .swap(helper.y_address(), dec!(1000))
+                .registry
+                .execute_expect_success(false);
+            report.record(
+                &format!("swap_sparse_ticks_gap_{}", gap),
+                &receipt.execution_receipt,
+            );
+        }
+        report.write();
+    }
+
+    #[test]
+    fn test_benchmark_tick_to_price_sqrt() {
+        // Runs the power table of the pool and the exponentiation it replaced in the same build, 0 ticks
+        // measure the overhead of the call itself.
+        let mut report = BenchmarkReport::new("tick_to_price_sqrt");
+        let tick_counts = [0, 10, 100];
+        let mut helper = PoolTestHelper::new_with_test_hook();
+        for tick_count in tick_counts {
+            // A prime stride samples ticks with all kinds of bit patterns over the full range.
+            let ticks: Vec<i32> = (MIN_TICK..=MAX_TICK)
+                .step_by(17737)
+                .take(tick_count)
+                .collect();
+            for implementation in ["table", "powi"] {
+                let function_name = format!("tick_to_price_sqrt_{}", implementation);
+                let receipt = helper
+                    .tick_to_price_sqrt_benchmark(&function_name, ticks.clone())
+                    .registry
+                    .execute_expect_success(false);
+                report.record(
+                    &format!("{}_ticks_{}", function_name, tick_count),
+                    &receipt.execution_receipt,
+                );
+            }
+        }
+        report.write();
+
+        assert_increasing(
+            &report,
+            &[
+                "tick_to_price_sqrt_table_ticks_100".to_string(),
+                "tick_to_price_sqrt_powi_ticks_100".to_string(),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_benchmark_add_remove_liquidity_ticks() {
+        let mut report = BenchmarkReport::new("add_remove_liquidity_ticks");
+        let mut helper = PoolTestHelper::new();
+        helper.instantiate_default(pdec!(1), false);
+        helper.add_liquidity_default(-100, 100, dec!(10), dec!(10));
+        helper.registry.execute_expect_success(false);
+
+        let receipt = helper
+            .add_liquidity_default(-200, 200, dec!(10), dec!(10))
+            .registry
+            .execute_expect_success(false);
+        report.record("add_liquidity_new_ticks", &receipt.execution_receipt);
+
+        let receipt = helper
+            .add_liquidity_default(-100, 100, dec!(10), dec!(10))
+            .registry
+            .execute_expect_success(false);
+        report.record("add_liquidity_existing_ticks", &receipt.execution_receipt);
+
+        // The ticks of the third position are still referenced by the first position.
+        let receipt = helper
+            .remove_liquidity(nft_ids!(3))
+            .registry
+            .execute_expect_success(false);
+        report.record(
+            "remove_liquidity_existing_ticks",
+            &receipt.execution_receipt,
+        );
+
+        // The ticks of the second position are removed together with the position.
+        let receipt = helper
+            .remove_liquidity(nft_ids!(2))
+            .registry
+            .execute_expect_success(false);
+        report.record("remove_liquidity_removed_ticks", &receipt.execution_receipt);
+        report.write();
+
+        assert_increasing(
+            &report,
+            &[
+                "add_liquidity_existing_ticks".to_string(),
+                "add_liquidity_new_ticks".to_string(),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_benchmark_add_liquidity_shape() {
+        let mut report = BenchmarkReport::new("add_liquidity_shape");
+        let position_counts = [1, 5, 25];
+        for position_count in position_counts {
+            let mut helper = PoolTestHelper::new();
+            helper.instantiate_default(pdec!(1), false);
+            let shape: Vec<ShapePosition> = positions(0, position_count)
+                .iter()
+                .map(|position| ShapePosition {
+                    left_bound: position.left_bound,
+                    right_bound: position.right_bound,
+                    x_address: helper.x_address(),
+                    x_amount: position.x_amount,
+                    y_address: helper.y_address(),
+                    y_amount: position.y_amount,
+                })
+                .collect();
+
+            let receipt = helper
+                .add_liquidity_shape_positions(&shape, None)
+                .registry
+                .execute_expect_success(false);
+            report.record(
+                &format!("add_liquidity_shape_positions_{}", position_count),
+                &receipt.execution_receipt,
+            );
+        }
+        report.write();
+
+        let names: Vec<String> = position_counts
+            .iter()
+            .map(|position_count| format!("add_liquidity_shape_positions_{}", position_count))
+            .collect();
+        assert_increasing(&report, &names);
+    }
+
+    #[test]
+    fn test_benchmark_claim_fees() {
+        let mut report = BenchmarkReport::new("claim_fees");
+        let nft_counts = [1, 10, 50];
+        for nft_count in nft_counts {
+            let mut helper = PoolTestHelper::new();
+            helper.instantiate_default_with_input_fee(pdec!(1), dec!(0.01), false);
+            let positions: Vec<LiquidityPosition> = (0..nft_count)
+                .map(|_| LiquidityPosition {
+                    left_bound: -100,
+                    right_bound: 100,
+                    x_amount: dec!(1),
+                    y_amount: dec!(1),
+                })
+                .collect();
+            add_positions(&mut helper, &positions);
+            helper.swap(helper.x_address(), dec!(1));
+            helper.swap(helper.y_address(), dec!(1));
+            helper.registry.execute_expect_success(false);
+
+            let receipt = helper
+                .claim_fees(nft_range(nft_count as u64))
+                .registry
+                .execute_expect_success(false);
+            report.record(
+                &format!("claim_fees_nfts_{}", nft_count),
+                &receipt.execution_receipt,
+            );
+        }
+        report.write();
+
+        let names: Vec<String> = nft_counts
+            .iter()
+            .map(|nft_count| format!("claim_fees_nfts_{}", nft_count))
+            .collect();
+        assert_increasing(&report, &names);
+    }
+
+    #[test]
+    fn test_benchmark_position_image() {
+        let mut report = BenchmarkReport::new("position_image");
+        let mut helper = PoolTestHelper::new();
+        helper.instantiate_default_with_input_fee(pdec!(1), dec!(0.01), false);
+        helper.add_liquidity_default(-10000, 10000, dec!(10), dec!(10));
+        helper.registry.execute_expect_success(false);
+
+        // Minting renders the image of the position.
+        let receipt = helper
+            .add_liquidity_default(-10, 10, dec!(10), dec!(10))
+            .registry
+            .execute_expect_success(false);
+        report.record("add_liquidity_render_image", &receipt.execution_receipt);
+
+        helper.swap(helper.x_address(), dec!(0.1));
+        helper.registry.execute_expect_success(false);
+        let receipt = helper
+            .claim_fees(nft_ids!(2))
+            .registry
+            .execute_expect_success(false);
+        report.record("claim_fees_image_unchanged", &receipt.execution_receipt);
+
+        // Moves the price out of the range of the second position, so claiming has to store a new image.
+        helper.swap(helper.x_address(), dec!(15));
+        helper.registry.execute_expect_success(false);
+        let receipt = helper
+            .claim_fees(nft_ids!(2))
+            .registry
+            .execute_expect_success(false);
+        report.record("claim_fees_image_changed", &receipt.execution_receipt);
+        report.write();
+
+        assert_increasing(
+            &report,
+            &[
+                "claim_fees_image_unchanged".to_string(),
+                "claim_fees_image_changed".to_string(),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_benchmark_hook_stages() {
+        let mut report = BenchmarkReport::new("hook_stages");
+        let stages = [
+            ("no_hook", None),
+            ("before_instantiate", Some(HookCall::BeforeInstantiate)),
+            ("after_instantiate", Some(HookCall::AfterInstantiate)),
+            ("before_add_liquidity", Some(HookCall::BeforeAddLiquidity)),
+            ("after_add_liquidity", Some(HookCall::AfterAddLiquidity)),
+            ("before_swap", Some(HookCall::BeforeSwap)),
+            ("after_swap", Some(HookCall::AfterSwap)),
+            (
+                "before_remove_liquidity",
+                Some(HookCall::BeforeRemoveLiquidity),
+            ),
+            (
+                "after_remove_liquidity",
+                Some(HookCall::AfterRemoveLiquidity),
+            ),
+        ];
+        for (stage, call) in stages {
+            let mut helper = PoolTestHelper::new_with_test_hook();
+            helper.set_whitelist_hook("test_hook");
+            helper.registry.execute_expect_success(false);
+            let hooks = match call {
+                Some(call) => vec![helper.instantiate_test_hook(vec![call])],
+                None => vec![],
+            };
+
+            helper.set_whitelist_registry();
+            helper.instantiate(
+                helper.x_address(),
+                helper.y_address(),
+                pdec!(1),
+                dec!(0),
+                dec!(0),
+                helper.registry.registry_address.unwrap(),
+                hooks,
+            );
+            let receipt = helper.registry.execute_expect_success(false);
+            let (pool_address, lp_address): (ComponentAddress, ResourceAddress) =
+                receipt.outputs("instantiate")[0];
+            helper.pool_address = Some(pool_address);
+            helper.lp_address = Some(lp_address);
+            report.record(
+                &format!("{}_instantiate", stage),
+                &receipt.execution_receipt,
+            );
+
+            let receipt = helper
+                .add_liquidity_default(-100, 100, dec!(10), dec!(10))
+                .registry
+                .execute_expect_success(false);
+            report.record(
+                &format!("{}_add_liquidity", stage),
+                &receipt.execution_receipt,
+            );
+
+            let receipt = helper
+                .swap(helper.x_address(), dec!(1))
+                .registry
+                .execute_expect_success(false);
+            report.record(&format!("{}_swap", stage), &receipt.execution_receipt);
+
+            let receipt = helper
+                .remove_liquidity(nft_ids!(1))
+                .registry
+                .execute_expect_success(false);
+            report.record(
+                &format!("{}_remove_liquidity", stage),
+                &receipt.execution_receipt,
+            );
+        }
+        report.write();
+
+        for (stage, operation) in [
+            ("before_instantiate", "instantiate"),
+            ("after_instantiate", "instantiate"),
+            ("before_add_liquidity", "add_liquidity"),
+            ("after_add_liquidity", "add_liquidity"),
+            ("before_swap", "swap"),
+            ("after_swap", "swap"),
+            ("before_remove_liquidity", "remove_liquidity"),
+            ("after_remove_liquidity", "remove_liquidity"),
+        ] {
+            assert_increasing(
+                &report,
+                &[
+                    format!("no_hook_{}", operation),
+                    format!("{}_{}", stage, operation),
+                ],
+            );
+        }
+    }
+}
@@ -0,0 +1,161 @@
+#[cfg(test)]
+mod precision_pool_compound {
+    use precision_pool_test_helper::*;
+    use radix_engine::system::system_modules::execution_trace::ResourceSpecifier::Amount;
+    use scrypto::prelude::*;
+    use scrypto_testenv::*;
+
+    static ONE_LP: [LiquidityPosition; 1] = [LiquidityPosition {
+        left_bound: TICK_LEFT_BOUND,
+        right_bound: TICK_RIGHT_BOUND,
+        x_amount: DEC_10,
+        y_amount: DEC_10,
+    }];
+
+    fn helper_with_fees() -> PoolTestHelper {
+        let mut helper = PoolTestHelper::new();
+        helper.instantiate_default_with_input_fee(
+            *PRICE_BETWEEN_MIDDLE_BOUNDS_SQRT,
+            FEE_RATE,
+            false,
+        );
+        helper
+            .add_liquidity_default_batch(&ONE_LP)
+            .registry
+            .execute_expect_success(false);
+        helper.swap_x_default(dec!(1));
+        helper.swap_y_default(dec!(1));
+        helper.registry.execute_expect_success(false);
+        helper
+    }
+
+    fn total_fees(helper: &mut PoolTestHelper) -> (Decimal, Decimal) {
+        let receipt = helper
+            .total_fees(nft_ids!(1))
+            .registry
+            .execute_expect_success(false);
+        let output: Vec<IndexMap<ResourceAddress, Decimal>> = receipt.outputs("total_fees");
+        (
+            output[0][&helper.x_address()],
+            output[0][&helper.y_address()],
+        )
+    }
+
+    #[test]
+    fn test_compound_fees_returns_leftover() {
+        let mut helper = helper_with_fees();
+        let receipt = helper
+            .compound_fees(nft_ids!(1))
+            .registry
+            .execute_expect_success(false);
+        let output_buckets = receipt.output_buckets("compound_fees");
+        let (x_leftover, y_leftover) = match &output_buckets[0][..] {
+            [Amount(_, x_leftover), Amount(_, y_leftover)] => (*x_leftover, *y_leftover),
+            _ => panic!("Expected two fungible buckets."),
+        };
+        // Fees of about 0.09 were collected for each token, both are at least partially reinvested.
+        assert!(x_leftover < dec!("0.09") && y_leftover < dec!("0.09"));
+    }
+
+    #[test]
+    fn test_compound_fees_no_fees_claimable_afterwards() {
+        let mut helper = helper_with_fees();
+        helper
+            .compound_fees(nft_ids!(1))
+            .registry
+            .execute_expect_success(false);
+        helper.claim_fees_success(nft_ids!(1), dec!(0), dec!(0));
+    }
+
+    #[test]
+    fn test_compound_fees_keeps_total_fees() {
+        let mut helper = helper_with_fees();
+        let (x_total_before, y_total_before) = total_fees(&mut helper);
+        helper
+            .compound_fees(nft_ids!(1))
+            .registry
+            .execute_expect_success(false);
+        let (x_total_after, y_total_after) = total_fees(&mut helper);
+        assert!((x_total_before - x_total_after) <= Decimal::ATTO);
+        assert!((y_total_before - y_total_after) <= Decimal::ATTO);
+    }
+
+    fn removable_liquidity(helper: &mut PoolTestHelper) -> (Decimal, Decimal) {
+        let receipt = helper
+            .removable_liquidity(nft_ids!(1))
+            .registry
+            .execute_expect_success(false);
+        let output: Vec<(IndexMap<ResourceAddress, Decimal>, Decimal)> =
+            receipt.outputs("removable_liquidity");
+        (
+            output[0].0[&helper.x_address()],
+            output[0].0[&helper.y_address()],
+        )
+    }
+
+    #[test]
+    fn test_compound_fees_increases_position_liquidity() {
+        let mut helper = helper_with_fees();
+        let (x_removable, y_removable) = removable_liquidity(&mut helper);
+        let receipt = helper
+            .claimable_fees(nft_ids!(1))
+            .registry
+            .execute_expect_success(false);
+        let claimable: Vec<IndexMap<ResourceAddress, Decimal>> = receipt.outputs("claimable_fees");
+        let x_principal = x_removable - claimable[0][&helper.x_address()];
+        let y_principal = y_removable - claimable[0][&helper.y_address()];
+
+        helper
+            .compound_fees(nft_ids!(1))
+            .registry
+            .execute_expect_success(false);
+
+        let (x_removable, y_removable) = removable_liquidity(&mut helper);
+        assert!(
+            x_removable > x_principal,
+            "{:?} <= {:?}",
+            x_removable,
+            x_principal
+        );
+        assert!(
+            y_removable > y_principal,
+            "{:?} <= {:?}",
+            y_removable,
+            y_principal
+        );
+    }
+
+    #[test]
+    fn test_compound_fees_without_fees() {
+        let mut helper = PoolTestHelper::new();
+        helper.instantiate_default(*PRICE_BETWEEN_MIDDLE_BOUNDS_SQRT, false);
+        helper
+            .add_liquidity_default_batch(&ONE_LP)
+            .registry
+            .execute_expect_success(false);
+        let receipt = helper
+            .compound_fees(nft_ids!(1))
+            .registry
+            .execute_expect_success(false);
+        assert_eq!(
+            receipt.output_buckets("compound_fees"),
+            vec![vec![
+                Amount(helper.x_address(), dec!(0)),
+                Amount(helper.y_address(), dec!(0))
+            ]]
+        );
+    }
+
+    #[test]
+    fn test_compound_fees_multiple_positions_in_proof() {
+        let mut helper = helper_with_fees();
+        helper
+            .add_liquidity_default_batch(&ONE_LP)
+            .registry
+            .execute_expect_success(false);
+        helper
+            .compound_fees(nft_ids!(1, 2))
+            .registry
+            .execute_expect_failure(false);
+    }
+}
@@ -0,0 +1,97 @@
+#[cfg(test)]
+mod precision_pool_deadline {
+    use precision_pool_test_helper::*;
+    use scrypto::prelude::*;
+    use scrypto_testenv::*;
+
+    static ONE_LP: [LiquidityPosition; 1] = [LiquidityPosition {
+        left_bound: TICK_LEFT_BOUND,
+        right_bound: TICK_RIGHT_BOUND,
+        x_amount: DEC_10,
+        y_amount: DEC_10,
+    }];
+
+    const NOW: u64 = 1_700_000_000;
+
+    fn helper_with_liquidity() -> PoolTestHelper {
+        let mut helper = PoolTestHelper::new_default_with_positions(pdec!(1), &ONE_LP);
+        helper.registry.execute_expect_success(false);
+        helper.jump_to_timestamp_seconds(NOW);
+        helper
+    }
+
+    #[test]
+    fn test_swap_with_deadline_none() {
+        let mut helper = helper_with_liquidity();
+        helper
+            .swap_with_deadline(helper.x_address(), dec!(1), None)
+            .registry
+            .execute_expect_success(false);
+    }
+
+    #[test]
+    fn test_swap_with_deadline_now() {
+        let mut helper = helper_with_liquidity();
+        helper
+            .swap_with_deadline(helper.x_address(), dec!(1), Some(NOW))
+            .registry
+            .execute_expect_success(false);
+    }
+
+    #[test]
+    fn test_swap_with_deadline_passed() {
+        let mut helper = helper_with_liquidity();
+        helper
+            .swap_with_deadline(helper.y_address(), dec!(1), Some(NOW - 1))
+            .registry
+            .execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_add_liquidity_with_deadline_future() {
+        let mut helper = helper_with_liquidity();
+        helper
+            .add_liquidity_with_deadline(
+                TICK_LEFT_BOUND,
+                TICK_RIGHT_BOUND,
+                dec!(1),
+                dec!(1),
+                Some(NOW + 60),
+            )
+            .registry
+            .execute_expect_success(false);
+    }
+
+    #[test]
+    fn test_add_liquidity_with_deadline_passed() {
+        let mut helper = helper_with_liquidity();
+        helper
+            .add_liquidity_with_deadline(
+                TICK_LEFT_BOUND,
+                TICK_RIGHT_BOUND,
+                dec!(1),
+                dec!(1),
+                Some(NOW - 60),
+            )
+            .registry
+            .execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_remove_liquidity_with_deadline_now() {
+        let mut helper = helper_with_liquidity();
+        helper
+            .remove_liquidity_with_deadline(nft_ids!(1), Some(NOW))
+            .registry
+            .execute_expect_success(false);
+    }
+
+    #[test]
+    fn test_remove_liquidity_with_deadline_passed() {
+        let mut helper = helper_with_liquidity();
+        helper
+            .remove_liquidity_with_deadline(nft_ids!(1), Some(NOW - 1))
+            .registry
+            .execute_expect_failure(false);
+    }
+}
@@ -0,0 +1,150 @@
+#[cfg(test)]
+mod precision_pool_distribution {
+    use precision_pool::pool_math::LiquidityShape;
+    use precision_pool_test_helper::*;
+    use radix_engine::system::system_modules::execution_trace::ResourceSpecifier::{Amount, Ids};
+    use scrypto::prelude::*;
+    use scrypto_testenv::*;
+    use test_case::test_case;
+
+    // The price of 2 corresponds to tick 6931.
+    const CENTER: i32 = 6931;
+
+    fn helper() -> PoolTestHelper {
+        let mut helper = PoolTestHelper::new();
+        helper.instantiate_default(*PRICE_BETWEEN_MIDDLE_BOUNDS_SQRT, false);
+        helper
+    }
+
+    #[test_case(LiquidityShape::Uniform)]
+    #[test_case(LiquidityShape::Normal)]
+    #[test_case(LiquidityShape::Curve)]
+    #[test_case(LiquidityShape::BidAsk)]
+    #[test_case(LiquidityShape::Exponential)]
+    fn test_add_liquidity_distribution(shape: LiquidityShape) {
+        let mut helper = helper();
+        let receipt = helper
+            .add_liquidity_distribution(shape, CENTER, 100, 5, DEC_10, DEC_10)
+            .registry
+            .execute_expect_success(false);
+        let output_buckets = receipt.output_buckets("add_liquidity_distribution");
+        match &output_buckets[0][..] {
+            [Ids(lp_address, ids), Amount(_, x_remainder), Amount(_, y_remainder)] => {
+                assert_eq!(*lp_address, helper.lp_address.unwrap());
+                assert_eq!(*ids, nft_ids!(1, 2, 3, 4, 5));
+                // Only the bin containing the current price can leave a larger remainder of one of the tokens.
+                assert!(
+                    *x_remainder < DEC_10 && *y_remainder < DEC_10,
+                    "{:?} {:?}",
+                    x_remainder,
+                    y_remainder
+                );
+            }
+            _ => panic!("Expected the positions and two fungible buckets."),
+        }
+    }
+
+    #[test]
+    fn test_add_liquidity_distribution_only_x() {
+        let mut helper = helper();
+        let receipt = helper
+            .add_liquidity_distribution(LiquidityShape::Uniform, CENTER, 100, 5, DEC_10, dec!(0))
+            .registry
+            .execute_expect_success(false);
+        // Only the two bins above the current price receive liquidity.
+        assert_eq!(
+            receipt.output_buckets("add_liquidity_distribution"),
+            vec![vec![
+                Ids(helper.lp_address.unwrap(), nft_ids!(1, 2)),
+                Amount(helper.x_address(), dec!(0)),
+                Amount(helper.y_address(), dec!(0)),
+            ]]
+        );
+    }
+
+    #[test]
+    fn test_add_liquidity_distribution_only_y() {
+        let mut helper = helper();
+        let receipt = helper
+            .add_liquidity_distribution(LiquidityShape::Uniform, CENTER, 100, 5, dec!(0), DEC_10)
+            .registry
+            .execute_expect_success(false);
+        // Only the two bins below the current price receive liquidity.
+        assert_eq!(
+            receipt.output_buckets("add_liquidity_distribution"),
+            vec![vec![
+                Ids(helper.lp_address.unwrap(), nft_ids!(1, 2)),
+                Amount(helper.x_address(), dec!(0)),
+                Amount(helper.y_address(), dec!(0)),
+            ]]
+        );
+    }
+
+    #[test]
+    fn test_add_liquidity_distribution_single_bin() {
+        let mut helper = helper();
+        helper
+            .add_liquidity_distribution(LiquidityShape::Normal, CENTER, 100, 1, DEC_10, DEC_10)
+            .registry
+            .execute_expect_success(false);
+    }
+
+    #[test]
+    fn test_add_liquidity_distribution_no_tokens() {
+        let mut helper = helper();
+        helper
+            .add_liquidity_distribution(LiquidityShape::Uniform, CENTER, 100, 5, dec!(0), dec!(0))
+            .registry
+            .execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_add_liquidity_distribution_width_not_multiple_of_spacing() {
+        let mut helper = PoolTestHelper::new();
+        let receipt = helper
+            .instantiate_tick_spacing(10)
+            .registry
+            .execute_expect_success(false);
+        let (pool_address, lp_address): (ComponentAddress, ResourceAddress) =
+            receipt.outputs("instantiate")[0];
+        helper.pool_address = Some(pool_address);
+        helper.lp_address = Some(lp_address);
+        helper
+            .add_liquidity_distribution(LiquidityShape::Uniform, 0, 15, 5, DEC_10, DEC_10)
+            .registry
+            .execute_expect_failure(false);
+    }
+
+    #[test_case(0)]
+    #[test_case(51)]
+    fn test_add_liquidity_distribution_invalid_number_of_bins(num_bins: u32) {
+        let mut helper = helper();
+        helper
+            .add_liquidity_distribution(
+                LiquidityShape::Uniform,
+                CENTER,
+                100,
+                num_bins,
+                DEC_10,
+                DEC_10,
+            )
+            .registry
+            .execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_add_liquidity_distribution_exceeding_max_tick() {
+        let mut helper = helper();
+        helper
+            .add_liquidity_distribution(
+                LiquidityShape::Uniform,
+                MAX_TICK - 100,
+                100,
+                5,
+                DEC_10,
+                DEC_10,
+            )
+            .registry
+            .execute_expect_failure(false);
+    }
+}
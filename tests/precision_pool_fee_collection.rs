@@ -0,0 +1,182 @@
+#[cfg(test)]
+mod precision_pool_fee_collection {
+    use common::pools::SwapType;
+    use precision_pool::pool::{FeeCollectionMode, PoolSnapshot, SwapQuote};
+    use precision_pool_test_helper::*;
+    use radix_engine::system::system_modules::execution_trace::ResourceSpecifier;
+    use scrypto::prelude::*;
+    use scrypto_testenv::*;
+    use test_case::test_case;
+
+    fn helper_with_position(fee_collection_mode: FeeCollectionMode) -> PoolTestHelper {
+        let mut helper = PoolTestHelper::new();
+        helper.instantiate_default_with_fee_collection_mode(
+            pdec!(1),
+            dec!(0.01),
+            fee_collection_mode,
+            false,
+        );
+        helper.add_liquidity_default(-200, 200, dec!(10), dec!(10));
+        helper.registry.execute_expect_success(false);
+        helper
+    }
+
+    fn quote(helper: &mut PoolTestHelper, swap_type: SwapType, input_amount: Decimal) -> SwapQuote {
+        let receipt = helper
+            .quote_swap(helper.input_address(swap_type), input_amount, None)
+            .registry
+            .execute_expect_success(false);
+        let quotes: Vec<SwapQuote> = receipt.outputs("quote_swap");
+        quotes[0].clone()
+    }
+
+    fn swap(helper: &mut PoolTestHelper, swap_type: SwapType, input_amount: Decimal) -> Decimal {
+        let receipt = helper
+            .swap(helper.input_address(swap_type), input_amount)
+            .registry
+            .execute_expect_success(false);
+        match &receipt.output_buckets("swap")[0][0] {
+            ResourceSpecifier::Amount(_, output) => *output,
+            _ => panic!("Expected fungible output bucket."),
+        }
+    }
+
+    fn lp_fees(helper: &mut PoolTestHelper) -> (Decimal, Decimal) {
+        let receipt = helper.snapshot().registry.execute_expect_success(false);
+        let snapshots: Vec<PoolSnapshot> = receipt.outputs("snapshot");
+        let PoolSnapshot::V1(snapshot) = snapshots[0].clone();
+        (snapshot.x_fees, snapshot.y_fees)
+    }
+
+    #[test_case(FeeCollectionMode::Input ; "input")]
+    #[test_case(FeeCollectionMode::Output ; "output")]
+    #[test_case(FeeCollectionMode::X ; "x")]
+    #[test_case(FeeCollectionMode::Y ; "y")]
+    fn test_fee_collection_mode(fee_collection_mode: FeeCollectionMode) {
+        let mut helper = helper_with_position(fee_collection_mode);
+
+        let receipt = helper
+            .fee_collection_mode()
+            .registry
+            .execute_expect_success(false);
+
+        assert_eq!(
+            receipt.outputs::<FeeCollectionMode>("fee_collection_mode"),
+            vec![fee_collection_mode]
+        );
+    }
+
+    #[test]
+    fn test_fee_collection_mode_default_input() {
+        let mut helper = PoolTestHelper::new();
+        helper.instantiate_default_with_input_fee(pdec!(1), dec!(0.01), false);
+
+        let receipt = helper
+            .fee_collection_mode()
+            .registry
+            .execute_expect_success(false);
+
+        assert_eq!(
+            receipt.outputs::<FeeCollectionMode>("fee_collection_mode"),
+            vec![FeeCollectionMode::Input]
+        );
+    }
+
+    #[test_case(FeeCollectionMode::Input, SwapType::SellX, true ; "input_sell_x")]
+    #[test_case(FeeCollectionMode::Input, SwapType::BuyX, false ; "input_buy_x")]
+    #[test_case(FeeCollectionMode::Output, SwapType::SellX, false ; "output_sell_x")]
+    #[test_case(FeeCollectionMode::Output, SwapType::BuyX, true ; "output_buy_x")]
+    #[test_case(FeeCollectionMode::X, SwapType::SellX, true ; "x_sell_x")]
+    #[test_case(FeeCollectionMode::X, SwapType::BuyX, true ; "x_buy_x")]
+    #[test_case(FeeCollectionMode::Y, SwapType::SellX, false ; "y_sell_x")]
+    #[test_case(FeeCollectionMode::Y, SwapType::BuyX, false ; "y_buy_x")]
+    fn test_swap_collects_fees_in_token(
+        fee_collection_mode: FeeCollectionMode,
+        swap_type: SwapType,
+        fee_in_x: bool,
+    ) {
+        let mut helper = helper_with_position(fee_collection_mode);
+
+        swap(&mut helper, swap_type, dec!(1));
+
+        let (x_fees, y_fees) = lp_fees(&mut helper);
+        if fee_in_x {
+            assert!(x_fees > dec!(0));
+            assert_eq!(y_fees, dec!(0));
+        } else {
+            assert_eq!(x_fees, dec!(0));
+            assert!(y_fees > dec!(0));
+        }
+    }
+
+    #[test_case(SwapType::SellX ; "sell_x")]
+    #[test_case(SwapType::BuyX ; "buy_x")]
+    fn test_swap_output_fee(swap_type: SwapType) {
+        let mut helper = helper_with_position(FeeCollectionMode::Output);
+        let quote = quote(&mut helper, swap_type, dec!(1));
+
+        let output = swap(&mut helper, swap_type, dec!(1));
+
+        assert_eq!(output, quote.output_amount);
+        assert_eq!(quote.input_fee_lp, dec!(0));
+        assert_eq!(quote.input_fee_protocol, dec!(0));
+        // The output fee is rounded up in favour of the pool.
+        let output_fee = quote.output_fee_lp + quote.output_fee_protocol;
+        let output_fee_exact = PreciseDecimal::from(quote.output_amount + output_fee) * pdec!(0.01);
+        assert!(PreciseDecimal::from(output_fee) >= output_fee_exact);
+        assert!(PreciseDecimal::from(output_fee) - output_fee_exact < pdec!(0.000000000000000001));
+    }
+
+    #[test]
+    fn test_swap_output_fee_reduces_output() {
+        let mut input_helper = helper_with_position(FeeCollectionMode::Input);
+        let mut output_helper = helper_with_position(FeeCollectionMode::Output);
+        let without_fee = {
+            let mut helper = PoolTestHelper::new();
+            helper.instantiate_default(pdec!(1), false);
+            helper.add_liquidity_default(-200, 200, dec!(10), dec!(10));
+            helper.registry.execute_expect_success(false);
+            quote(&mut helper, SwapType::SellX, dec!(1))
+        };
+
+        let input_quote = quote(&mut input_helper, SwapType::SellX, dec!(1));
+        let output_quote = quote(&mut output_helper, SwapType::SellX, dec!(1));
+
+        assert!(input_quote.output_amount < without_fee.output_amount);
+        assert!(output_quote.output_amount < without_fee.output_amount);
+        assert_eq!(
+            output_quote.output_amount
+                + output_quote.output_fee_lp
+                + output_quote.output_fee_protocol,
+            without_fee.output_amount
+        );
+    }
+
+    #[test_case(FeeCollectionMode::Output ; "output")]
+    #[test_case(FeeCollectionMode::X ; "x")]
+    #[test_case(FeeCollectionMode::Y ; "y")]
+    fn test_claim_fees_after_swaps(fee_collection_mode: FeeCollectionMode) {
+        let mut helper = helper_with_position(fee_collection_mode);
+        swap(&mut helper, SwapType::SellX, dec!(1));
+        swap(&mut helper, SwapType::BuyX, dec!(2));
+        swap(&mut helper, SwapType::SellX, dec!(3));
+        let (x_fees, y_fees) = lp_fees(&mut helper);
+
+        let receipt = helper
+            .claim_fees(nft_ids!(1))
+            .registry
+            .execute_expect_success(false);
+        let output_buckets = receipt.output_buckets("claim_fees");
+
+        // The only position receives all fees, except for rounding dust staying in the pool.
+        let (x_claimed, y_claimed) = match (&output_buckets[0][0], &output_buckets[0][1]) {
+            (ResourceSpecifier::Amount(_, x_claimed), ResourceSpecifier::Amount(_, y_claimed)) => {
+                (*x_claimed, *y_claimed)
+            }
+            _ => panic!("Expected fungible output buckets."),
+        };
+        assert!(x_claimed <= x_fees && x_fees - x_claimed <= dec!(0.000000000000000001));
+        assert!(y_claimed <= y_fees && y_fees - y_claimed <= dec!(0.000000000000000001));
+        helper.assert_invariants();
+    }
+}
@@ -0,0 +1,236 @@
+#[cfg(test)]
+mod precision_pool_locked {
+    use precision_pool_test_helper::*;
+    use radix_engine::system::system_modules::execution_trace::ResourceSpecifier::Amount;
+    use scrypto::prelude::*;
+    use scrypto_testenv::*;
+
+    const NOW: u64 = 1_700_000_000;
+    const LOCK_DURATION: u64 = 86_400;
+
+    fn helper_with_locked_position() -> PoolTestHelper {
+        let mut helper = PoolTestHelper::new();
+        helper.instantiate_default_with_input_fee(pdec!(1), FEE_RATE, false);
+        helper.jump_to_timestamp_seconds(NOW);
+        helper
+            .add_liquidity_locked(
+                TICK_LEFT_BOUND,
+                TICK_RIGHT_BOUND,
+                DEC_10,
+                DEC_10,
+                NOW + LOCK_DURATION,
+            )
+            .registry
+            .execute_expect_success(false);
+        helper
+    }
+
+    fn locked_until(helper: &mut PoolTestHelper, nft_id: NonFungibleLocalId) -> Option<u64> {
+        let receipt = helper
+            .locked_until(nft_id)
+            .registry
+            .execute_expect_success(false);
+        receipt.outputs::<Option<u64>>("locked_until")[0]
+    }
+
+    #[test]
+    fn test_add_liquidity_locked() {
+        let mut helper = helper_with_locked_position();
+
+        assert_eq!(
+            helper.position_data(nft_id!(1)).locked_until,
+            Some(NOW + LOCK_DURATION)
+        );
+        assert_eq!(
+            locked_until(&mut helper, nft_id!(1)),
+            Some(NOW + LOCK_DURATION)
+        );
+    }
+
+    #[test]
+    fn test_add_liquidity_not_locked() {
+        let mut helper = PoolTestHelper::new();
+        helper.instantiate_default(pdec!(1), false);
+        helper.add_liquidity_success(-10, 10, dec!(10), dec!(10), dec!(0), dec!(0));
+
+        assert_eq!(helper.position_data(nft_id!(1)).locked_until, None);
+        assert_eq!(locked_until(&mut helper, nft_id!(1)), None);
+    }
+
+    #[test]
+    fn test_add_liquidity_locked_until_now() {
+        let mut helper = PoolTestHelper::new();
+        helper.instantiate_default(pdec!(1), false);
+        helper.jump_to_timestamp_seconds(NOW);
+        helper
+            .add_liquidity_locked(TICK_LEFT_BOUND, TICK_RIGHT_BOUND, DEC_10, DEC_10, NOW)
+            .registry
+            .execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_locked_until_after_lock_ended() {
+        let mut helper = helper_with_locked_position();
+        helper.jump_to_timestamp_seconds(NOW + LOCK_DURATION);
+
+        assert_eq!(locked_until(&mut helper, nft_id!(1)), None);
+        assert_eq!(
+            helper.position_data(nft_id!(1)).locked_until,
+            Some(NOW + LOCK_DURATION)
+        );
+    }
+
+    #[test]
+    fn test_remove_liquidity_locked() {
+        let mut helper = helper_with_locked_position();
+        helper.jump_to_timestamp_seconds(NOW + LOCK_DURATION - 1);
+        helper
+            .remove_liquidity(nft_ids!(1))
+            .registry
+            .execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_remove_liquidity_after_lock_ended() {
+        let mut helper = helper_with_locked_position();
+        helper.jump_to_timestamp_seconds(NOW + LOCK_DURATION);
+        helper
+            .remove_liquidity(nft_ids!(1))
+            .registry
+            .execute_expect_success(false);
+    }
+
+    #[test]
+    fn test_remove_liquidity_locked_together_with_unlocked() {
+        let mut helper = helper_with_locked_position();
+        helper.add_liquidity_default(TICK_LEFT_BOUND, TICK_RIGHT_BOUND, DEC_10, DEC_10);
+        helper.registry.execute_expect_success(false);
+        helper
+            .remove_liquidity(nft_ids!(1, 2))
+            .registry
+            .execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_remove_liquidity_with_deadline_locked() {
+        let mut helper = helper_with_locked_position();
+        helper
+            .remove_liquidity_with_deadline(nft_ids!(1), None)
+            .registry
+            .execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_reposition_locked() {
+        let mut helper = helper_with_locked_position();
+        helper
+            .reposition(
+                nft_ids!(1),
+                TICK_LEFT_BOUND * 2,
+                TICK_RIGHT_BOUND * 2,
+                (dec!(1), dec!("0.1")),
+            )
+            .registry
+            .execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_claim_fees_locked() {
+        let mut helper = helper_with_locked_position();
+        helper.swap_x_default(dec!(1));
+        helper.registry.execute_expect_success(false);
+
+        let receipt = helper
+            .claim_fees(nft_ids!(1))
+            .registry
+            .execute_expect_success(false);
+        let output_buckets = receipt.output_buckets("claim_fees");
+
+        match &output_buckets[0][0] {
+            Amount(_, x_claimed) => assert!(*x_claimed > dec!(0)),
+            _ => panic!("Expected fungible output bucket."),
+        }
+    }
+
+    #[test]
+    fn test_compound_fees_locked() {
+        let mut helper = helper_with_locked_position();
+        helper.swap_x_default(dec!(1));
+        helper.swap_y_default(dec!(1));
+        helper.registry.execute_expect_success(false);
+
+        helper
+            .compound_fees(nft_ids!(1))
+            .registry
+            .execute_expect_success(false);
+
+        assert_eq!(
+            helper.position_data(nft_id!(1)).locked_until,
+            Some(NOW + LOCK_DURATION)
+        );
+    }
+
+    #[test]
+    fn test_split_position_inherits_lock() {
+        let mut helper = helper_with_locked_position();
+        helper
+            .split_position(nft_ids!(1), vec![dec!(0.5)])
+            .registry
+            .execute_expect_success(false);
+
+        assert_eq!(
+            helper.position_data(nft_id!(2)).locked_until,
+            Some(NOW + LOCK_DURATION)
+        );
+        helper
+            .remove_liquidity(nft_ids!(2))
+            .registry
+            .execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_merge_positions_keeps_latest_lock() {
+        let mut helper = helper_with_locked_position();
+        helper.add_liquidity_default(TICK_LEFT_BOUND, TICK_RIGHT_BOUND, DEC_10, DEC_10);
+        helper.registry.execute_expect_success(false);
+        helper
+            .merge_positions(nft_ids!(1, 2))
+            .registry
+            .execute_expect_success(false);
+
+        assert_eq!(
+            helper.position_data(nft_id!(3)).locked_until,
+            Some(NOW + LOCK_DURATION)
+        );
+        helper
+            .remove_liquidity(nft_ids!(3))
+            .registry
+            .execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_merge_unlocked_and_locked_positions_is_locked() {
+        let mut helper = helper_with_locked_position();
+        helper.add_liquidity_default(TICK_LEFT_BOUND, TICK_RIGHT_BOUND, DEC_10, DEC_10);
+        helper.registry.execute_expect_success(false);
+        helper
+            .merge_positions(nft_ids!(2, 1))
+            .registry
+            .execute_expect_success(false);
+
+        assert_eq!(
+            locked_until(&mut helper, nft_id!(3)),
+            Some(NOW + LOCK_DURATION)
+        );
+        helper
+            .remove_liquidity(nft_ids!(3))
+            .registry
+            .execute_expect_failure(false);
+
+        helper.jump_to_timestamp_seconds(NOW + LOCK_DURATION);
+        helper
+            .remove_liquidity(nft_ids!(3))
+            .registry
+            .execute_expect_success(false);
+    }
+}
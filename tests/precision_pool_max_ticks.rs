@@ -0,0 +1,225 @@
+#[cfg(test)]
+mod precision_pool_max_ticks {
+    use common::pools::SwapType;
+    use precision_pool::pool::SwapQuote;
+    use precision_pool_test_helper::*;
+    use scrypto::prelude::*;
+    use scrypto_testenv::*;
+    use test_case::test_case;
+
+    // Adjacent positions on both sides of the current price (tick 0), so every step crosses a tick.
+    const POSITIONS_ADJACENT: [LiquidityPosition; 6] = [
+        LiquidityPosition {
+            left_bound: -300,
+            right_bound: -200,
+            x_amount: DEC_10,
+            y_amount: DEC_10,
+        },
+        LiquidityPosition {
+            left_bound: -200,
+            right_bound: -100,
+            x_amount: DEC_10,
+            y_amount: DEC_10,
+        },
+        LiquidityPosition {
+            left_bound: -100,
+            right_bound: 0,
+            x_amount: DEC_10,
+            y_amount: DEC_10,
+        },
+        LiquidityPosition {
+            left_bound: 0,
+            right_bound: 100,
+            x_amount: DEC_10,
+            y_amount: DEC_10,
+        },
+        LiquidityPosition {
+            left_bound: 100,
+            right_bound: 200,
+            x_amount: DEC_10,
+            y_amount: DEC_10,
+        },
+        LiquidityPosition {
+            left_bound: 200,
+            right_bound: 300,
+            x_amount: DEC_10,
+            y_amount: DEC_10,
+        },
+    ];
+
+    fn helper_with_positions() -> PoolTestHelper {
+        let mut helper = PoolTestHelper::new_default_with_positions(pdec!(1), &POSITIONS_ADJACENT);
+        helper.registry.execute_expect_success(false);
+        helper
+    }
+
+    fn quote(
+        helper: &mut PoolTestHelper,
+        swap_type: SwapType,
+        input_amount: Decimal,
+        max_ticks_crossed: Option<u32>,
+    ) -> SwapQuote {
+        let receipt = helper
+            .quote_swap(
+                helper.input_address(swap_type),
+                input_amount,
+                max_ticks_crossed,
+            )
+            .registry
+            .execute_expect_success(false);
+        let quotes: Vec<SwapQuote> = receipt.outputs("quote_swap");
+        quotes[0].clone()
+    }
+
+    fn swap_with_max_ticks_crossed(
+        helper: &mut PoolTestHelper,
+        swap_type: SwapType,
+        input_amount: Decimal,
+        max_ticks_crossed: u32,
+    ) -> (Decimal, Decimal) {
+        let receipt = helper
+            .swap_with_max_ticks_crossed(
+                helper.input_address(swap_type),
+                input_amount,
+                max_ticks_crossed,
+            )
+            .registry
+            .execute_expect_success(false);
+        PoolTestHelper::amounts(&receipt.output_buckets("swap_with_max_ticks_crossed")[0])
+    }
+
+    #[test_case(SwapType::BuyX, dec!(1) ; "buy_within_range")]
+    #[test_case(SwapType::BuyX, dec!(25) ; "buy_crossing_ticks")]
+    #[test_case(SwapType::BuyX, dec!(100) ; "buy_exhausting_liquidity")]
+    #[test_case(SwapType::SellX, dec!(1) ; "sell_within_range")]
+    #[test_case(SwapType::SellX, dec!(25) ; "sell_crossing_ticks")]
+    #[test_case(SwapType::SellX, dec!(100) ; "sell_exhausting_liquidity")]
+    fn test_quote_swap_matches_swap(swap_type: SwapType, input_amount: Decimal) {
+        let mut helper = helper_with_positions();
+        let quote = quote(&mut helper, swap_type, input_amount, None);
+
+        let receipt = helper
+            .swap(helper.input_address(swap_type), input_amount)
+            .registry
+            .execute_expect_success(false);
+        let (output, remainder) = PoolTestHelper::amounts(&receipt.output_buckets("swap")[0]);
+
+        assert_eq!(quote.output_amount, output);
+        assert_eq!(quote.remainder_amount, remainder);
+        assert_eq!(quote.active_tick, helper.active_tick_success());
+        assert!(!quote.partial_fill);
+    }
+
+    #[test]
+    fn test_quote_swap_does_not_change_state() {
+        let mut helper = helper_with_positions();
+        let quote_before = quote(&mut helper, SwapType::BuyX, dec!(25), None);
+        let quote_after = quote(&mut helper, SwapType::BuyX, dec!(25), None);
+
+        assert_eq!(quote_before, quote_after);
+        assert_eq!(helper.active_tick_success(), Some(0));
+    }
+
+    #[test_case(SwapType::BuyX, dec!(25), 2 ; "buy")]
+    #[test_case(SwapType::SellX, dec!(25), 3 ; "sell")]
+    fn test_quote_swap_ticks_crossed(swap_type: SwapType, input_amount: Decimal, expected: u32) {
+        let mut helper = helper_with_positions();
+
+        let quote = quote(&mut helper, swap_type, input_amount, None);
+
+        assert_eq!(quote.ticks_crossed, expected);
+    }
+
+    #[test_case(SwapType::BuyX, 0, Some(0) ; "buy_no_crossing")]
+    #[test_case(SwapType::BuyX, 1, Some(100) ; "buy_one_crossing")]
+    #[test_case(SwapType::BuyX, 2, Some(200) ; "buy_two_crossings")]
+    #[test_case(SwapType::SellX, 0, Some(0) ; "sell_no_crossing")]
+    #[test_case(SwapType::SellX, 1, Some(-100) ; "sell_one_crossing")]
+    #[test_case(SwapType::SellX, 2, Some(-200) ; "sell_two_crossings")]
+    fn test_swap_with_max_ticks_crossed_stops_at_limit(
+        swap_type: SwapType,
+        max_ticks_crossed: u32,
+        expected_active_tick: Option<i32>,
+    ) {
+        let mut helper = helper_with_positions();
+        let quote = quote(&mut helper, swap_type, dec!(100), Some(max_ticks_crossed));
+
+        let (output, remainder) =
+            swap_with_max_ticks_crossed(&mut helper, swap_type, dec!(100), max_ticks_crossed);
+
+        assert_eq!(helper.active_tick_success(), expected_active_tick);
+        assert!(remainder > dec!(0));
+        assert_eq!(quote.output_amount, output);
+        assert_eq!(quote.remainder_amount, remainder);
+        assert_eq!(quote.ticks_crossed, max_ticks_crossed);
+        assert!(quote.partial_fill);
+    }
+
+    #[test]
+    fn test_swap_with_max_ticks_crossed_buy_fills_active_range() {
+        let mut helper = helper_with_positions();
+
+        let (output, _) = swap_with_max_ticks_crossed(&mut helper, SwapType::BuyX, dec!(100), 0);
+
+        // Almost all of the X tokens of the position [0, 100] are bought without crossing tick 100.
+        assert!(output > dec!(9.99) && output < dec!(10), "{}", output);
+        assert_eq!(helper.active_tick_success(), Some(0));
+    }
+
+    #[test]
+    fn test_swap_with_max_ticks_crossed_sell_at_tick_returns_input() {
+        let mut helper = helper_with_positions();
+
+        let (output, remainder) =
+            swap_with_max_ticks_crossed(&mut helper, SwapType::SellX, dec!(100), 0);
+
+        // The price sits on tick 0, so selling X requires crossing it.
+        assert_eq!(output, dec!(0));
+        assert_eq!(remainder, dec!(100));
+    }
+
+    #[test]
+    fn test_swap_with_max_ticks_crossed_not_reached() {
+        let mut helper = helper_with_positions();
+        let quote = quote(&mut helper, SwapType::BuyX, dec!(1), Some(1));
+
+        let (output, remainder) =
+            swap_with_max_ticks_crossed(&mut helper, SwapType::BuyX, dec!(1), 1);
+
+        assert_eq!(quote.output_amount, output);
+        assert_eq!(remainder, dec!(0));
+        assert!(!quote.partial_fill);
+    }
+
+    #[test]
+    fn test_swap_with_max_ticks_crossed_continue_after_limit() {
+        let mut helper = helper_with_positions();
+        let quote = quote(&mut helper, SwapType::BuyX, dec!(25), None);
+
+        let (output_first, remainder) =
+            swap_with_max_ticks_crossed(&mut helper, SwapType::BuyX, dec!(25), 1);
+        let (output_second, _) = swap_with_max_ticks_crossed(
+            &mut helper,
+            SwapType::BuyX,
+            remainder,
+            quote.ticks_crossed,
+        );
+
+        assert_eq!(helper.active_tick_success(), quote.active_tick);
+        assert!(output_first + output_second <= quote.output_amount);
+    }
+
+    #[test]
+    fn test_quote_swap_invalid_address() {
+        let mut helper = helper_with_positions();
+        helper.quote_swap(helper.v_address(), dec!(1), None);
+        helper.registry.execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_quote_swap_zero_amount() {
+        let mut helper = helper_with_positions();
+        helper.quote_swap(helper.x_address(), dec!(0), None);
+        helper.registry.execute_expect_failure(false);
+    }
+}
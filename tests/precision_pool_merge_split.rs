@@ -0,0 +1,233 @@
+#[cfg(test)]
+mod precision_pool_merge_split {
+    use precision_pool_test_helper::*;
+    use radix_engine::system::system_modules::execution_trace::ResourceSpecifier::{Amount, Ids};
+    use scrypto::prelude::*;
+    use scrypto_testenv::*;
+
+    static TWO_LP: [LiquidityPosition; 2] = [
+        LiquidityPosition {
+            left_bound: TICK_LEFT_BOUND,
+            right_bound: TICK_RIGHT_BOUND,
+            x_amount: DEC_10,
+            y_amount: DEC_10,
+        },
+        LiquidityPosition {
+            left_bound: TICK_LEFT_BOUND,
+            right_bound: TICK_RIGHT_BOUND,
+            x_amount: DEC_5,
+            y_amount: DEC_5,
+        },
+    ];
+
+    static DIFFERENT_BOUNDS_LP: [LiquidityPosition; 2] = [
+        LiquidityPosition {
+            left_bound: TICK_LEFT_BOUND,
+            right_bound: TICK_RIGHT_BOUND,
+            x_amount: DEC_10,
+            y_amount: DEC_10,
+        },
+        LiquidityPosition {
+            left_bound: TICK_LEFT_MIDDLE_BOUND,
+            right_bound: TICK_RIGHT_BOUND,
+            x_amount: DEC_10,
+            y_amount: DEC_10,
+        },
+    ];
+
+    fn helper_with_positions(positions: &[LiquidityPosition]) -> PoolTestHelper {
+        let mut helper = PoolTestHelper::new();
+        helper.instantiate_default_with_input_fee(
+            *PRICE_BETWEEN_MIDDLE_BOUNDS_SQRT,
+            FEE_RATE,
+            false,
+        );
+        helper
+            .add_liquidity_default_batch(positions)
+            .registry
+            .execute_expect_success(false);
+        helper.swap_x_default(dec!(1));
+        helper.swap_y_default(dec!(1));
+        helper.registry.execute_expect_success(false);
+        helper
+    }
+
+    fn removable_liquidity(
+        helper: &mut PoolTestHelper,
+        lp_positions: IndexSet<NonFungibleLocalId>,
+    ) -> (Decimal, Decimal) {
+        let receipt = helper
+            .removable_liquidity(lp_positions)
+            .registry
+            .execute_expect_success(false);
+        let output: Vec<(IndexMap<ResourceAddress, Decimal>, Decimal)> =
+            receipt.outputs("removable_liquidity");
+        (
+            output[0].0[&helper.x_address()],
+            output[0].0[&helper.y_address()],
+        )
+    }
+
+    fn total_fees(
+        helper: &mut PoolTestHelper,
+        lp_positions: IndexSet<NonFungibleLocalId>,
+    ) -> (Decimal, Decimal) {
+        let receipt = helper
+            .total_fees(lp_positions)
+            .registry
+            .execute_expect_success(false);
+        let output: Vec<IndexMap<ResourceAddress, Decimal>> = receipt.outputs("total_fees");
+        (
+            output[0][&helper.x_address()],
+            output[0][&helper.y_address()],
+        )
+    }
+
+    fn assert_close(a: Decimal, b: Decimal, tolerance: Decimal) {
+        assert!(
+            (a - b).checked_abs().unwrap() <= tolerance,
+            "{:?} != {:?}",
+            a,
+            b
+        );
+    }
+
+    #[test]
+    fn test_merge_positions() {
+        let mut helper = helper_with_positions(&TWO_LP);
+        let receipt = helper
+            .claimable_fees(nft_ids!(1, 2))
+            .registry
+            .execute_expect_success(false);
+        let claimable: Vec<IndexMap<ResourceAddress, Decimal>> = receipt.outputs("claimable_fees");
+        let receipt = helper
+            .merge_positions(nft_ids!(1, 2))
+            .registry
+            .execute_expect_success(false);
+        assert_eq!(
+            receipt.output_buckets("merge_positions"),
+            vec![vec![
+                Ids(helper.lp_address.unwrap(), nft_ids!(3)),
+                Amount(helper.x_address(), claimable[0][&helper.x_address()]),
+                Amount(helper.y_address(), claimable[0][&helper.y_address()]),
+            ]]
+        );
+    }
+
+    #[test]
+    fn test_merge_positions_keeps_liquidity_and_total_fees() {
+        let mut helper = helper_with_positions(&TWO_LP);
+        let (x_total_before, y_total_before) = total_fees(&mut helper, nft_ids!(1, 2));
+        helper
+            .claim_fees(nft_ids!(1, 2))
+            .registry
+            .execute_expect_success(false);
+        let (x_removable_before, y_removable_before) =
+            removable_liquidity(&mut helper, nft_ids!(1, 2));
+
+        helper
+            .merge_positions(nft_ids!(1, 2))
+            .registry
+            .execute_expect_success(false);
+
+        let (x_total_after, y_total_after) = total_fees(&mut helper, nft_ids!(3));
+        let (x_removable_after, y_removable_after) = removable_liquidity(&mut helper, nft_ids!(3));
+        assert_close(x_total_before, x_total_after, dec!("0.000000000000000002"));
+        assert_close(y_total_before, y_total_after, dec!("0.000000000000000002"));
+        assert_close(
+            x_removable_before,
+            x_removable_after,
+            dec!("0.000000000000000002"),
+        );
+        assert_close(
+            y_removable_before,
+            y_removable_after,
+            dec!("0.000000000000000002"),
+        );
+    }
+
+    #[test]
+    fn test_merge_positions_different_bounds() {
+        let mut helper = helper_with_positions(&DIFFERENT_BOUNDS_LP);
+        helper
+            .merge_positions(nft_ids!(1, 2))
+            .registry
+            .execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_merge_positions_single_position() {
+        let mut helper = helper_with_positions(&TWO_LP);
+        helper
+            .merge_positions(nft_ids!(1))
+            .registry
+            .execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_split_position() {
+        let mut helper = helper_with_positions(&TWO_LP);
+        let receipt = helper
+            .split_position(nft_ids!(1), vec![dec!("0.25"), dec!("0.25")])
+            .registry
+            .execute_expect_success(false);
+        assert_eq!(
+            receipt.output_buckets("split_position"),
+            vec![vec![Ids(helper.lp_address.unwrap(), nft_ids!(3, 4))]]
+        );
+    }
+
+    #[test]
+    fn test_split_position_proportional_liquidity_and_fees() {
+        let mut helper = helper_with_positions(&TWO_LP);
+        let (x_removable_before, y_removable_before) =
+            removable_liquidity(&mut helper, nft_ids!(1));
+        let (x_total_before, y_total_before) = total_fees(&mut helper, nft_ids!(1));
+
+        helper
+            .split_position(nft_ids!(1), vec![dec!("0.25"), dec!("0.25")])
+            .registry
+            .execute_expect_success(false);
+
+        // The original position keeps half of the liquidity, the new positions a quarter each.
+        let (x_removable_original, y_removable_original) =
+            removable_liquidity(&mut helper, nft_ids!(1));
+        let (x_removable_new, y_removable_new) = removable_liquidity(&mut helper, nft_ids!(3));
+        let tolerance = dec!("0.000000000000000010");
+        assert_close(x_removable_original, x_removable_before / 2, tolerance);
+        assert_close(y_removable_original, y_removable_before / 2, tolerance);
+        assert_close(x_removable_new, x_removable_before / 4, tolerance);
+        assert_close(y_removable_new, y_removable_before / 4, tolerance);
+
+        let (x_total_after, y_total_after) = total_fees(&mut helper, nft_ids!(1, 3, 4));
+        assert_close(x_total_before, x_total_after, tolerance);
+        assert_close(y_total_before, y_total_after, tolerance);
+    }
+
+    #[test]
+    fn test_split_position_fractions_sum_to_one() {
+        let mut helper = helper_with_positions(&TWO_LP);
+        helper
+            .split_position(nft_ids!(1), vec![dec!("0.5"), dec!("0.5")])
+            .registry
+            .execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_split_position_zero_fraction() {
+        let mut helper = helper_with_positions(&TWO_LP);
+        helper
+            .split_position(nft_ids!(1), vec![dec!(0)])
+            .registry
+            .execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_split_position_no_fractions() {
+        let mut helper = helper_with_positions(&TWO_LP);
+        helper
+            .split_position(nft_ids!(1), vec![])
+            .registry
+            .execute_expect_failure(false);
+    }
+}
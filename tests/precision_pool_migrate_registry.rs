@@ -0,0 +1,129 @@
+#[cfg(test)]
+mod precision_pool_migrate_registry {
+    use common::pools::SwapType;
+    use precision_pool_test_helper::*;
+    use pretty_assertions::assert_eq;
+    use scrypto::prelude::*;
+    use scrypto_testenv::*;
+
+    fn helper_with_registries() -> (PoolTestHelper, ComponentAddress, ComponentAddress) {
+        let mut helper = PoolTestHelper::new_with_test_hook();
+        let old_registry = helper.instantiate_test_registry(dec!(0.1), 100000);
+        let new_registry = helper.instantiate_test_registry(dec!(0.2), 200000);
+        helper.instantiate_default_with_registry(pdec!(1), dec!(0.1), old_registry, false);
+        helper.add_liquidity_success(-10000, 10000, dec!(10), dec!(10), dec!(0), dec!(0));
+        // Syncs with the old registry, the protocol fees of this swap stay in the pool.
+        helper.swap_success(SwapType::BuyX, dec!(3), dec!(2.440716293858554572), dec!(0));
+        (helper, old_registry, new_registry)
+    }
+
+    fn registry_fees(
+        helper: &mut PoolTestHelper,
+        registry: ComponentAddress,
+    ) -> (Decimal, Decimal) {
+        let receipt = helper
+            .test_registry_fees(registry)
+            .registry
+            .execute_expect_success(false);
+        receipt.outputs("fees")[0]
+    }
+
+    fn pool_registry(helper: &mut PoolTestHelper) -> ComponentAddress {
+        let receipt = helper
+            .pool_registry()
+            .registry
+            .execute_expect_success(false);
+        receipt.outputs("registry")[0]
+    }
+
+    fn registry_metadata(helper: &mut PoolTestHelper) -> Option<MetadataValue> {
+        let pool_address = helper.pool_address.unwrap();
+        helper
+            .registry
+            .env
+            .test_runner
+            .get_metadata(pool_address.into(), "registry")
+    }
+
+    #[test]
+    fn test_migrate_registry() {
+        let (mut helper, old_registry, new_registry) = helper_with_registries();
+
+        helper.test_registry_migrate_pool(old_registry, new_registry);
+        helper.registry.execute_expect_success(false);
+
+        assert_eq!(pool_registry(&mut helper), new_registry);
+        assert_eq!(
+            registry_metadata(&mut helper),
+            Some(MetadataValue::GlobalAddress(new_registry.into()))
+        );
+    }
+
+    #[test]
+    fn test_migrate_registry_syncs_with_new_registry() {
+        let (mut helper, old_registry, new_registry) = helper_with_registries();
+        helper.test_registry_migrate_pool(old_registry, new_registry);
+        helper.registry.execute_expect_success(false);
+
+        // The next swap syncs immediately with the new registry and remits the pending protocol fees.
+        helper.swap(helper.y_address(), dec!(1));
+        helper.registry.execute_expect_success(false);
+
+        assert_eq!(registry_fees(&mut helper, old_registry), (dec!(0), dec!(0)));
+        assert_eq!(
+            registry_fees(&mut helper, new_registry),
+            (dec!(0), dec!(0.03))
+        );
+        let receipt = helper
+            .fee_protocol_share()
+            .next_sync_time()
+            .registry
+            .execute_expect_success(false);
+        assert_eq!(
+            receipt.outputs::<Decimal>("fee_protocol_share"),
+            vec![dec!(0.2)]
+        );
+        assert_eq!(receipt.outputs::<u64>("next_sync_time"), vec![200000]);
+    }
+
+    #[test]
+    fn test_migrate_registry_twice() {
+        let (mut helper, old_registry, new_registry) = helper_with_registries();
+        helper.test_registry_migrate_pool(old_registry, new_registry);
+        helper.registry.execute_expect_success(false);
+
+        // Only the new registry is allowed to migrate the pool further.
+        helper.test_registry_migrate_pool(old_registry, old_registry);
+        helper.registry.execute_expect_failure(false);
+        helper.test_registry_migrate_pool(new_registry, old_registry);
+        helper.registry.execute_expect_success(false);
+
+        assert_eq!(pool_registry(&mut helper), old_registry);
+    }
+
+    #[test]
+    fn test_migrate_registry_not_authorized_by_registry() {
+        let (mut helper, _, new_registry) = helper_with_registries();
+
+        helper.migrate_registry(new_registry);
+        helper.registry.execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_migrate_registry_by_other_registry() {
+        let (mut helper, old_registry, new_registry) = helper_with_registries();
+
+        helper.test_registry_migrate_pool(new_registry, new_registry);
+        helper.registry.execute_expect_failure(false);
+
+        assert_eq!(pool_registry(&mut helper), old_registry);
+    }
+
+    #[test]
+    fn test_migrate_registry_same_registry() {
+        let (mut helper, old_registry, _) = helper_with_registries();
+
+        helper.test_registry_migrate_pool(old_registry, old_registry);
+        helper.registry.execute_expect_failure(false);
+    }
+}
@@ -0,0 +1,119 @@
+#[cfg(test)]
+mod precision_pool_position_metadata {
+    use precision_pool_test_helper::*;
+    use scrypto::prelude::*;
+    use scrypto_testenv::*;
+
+    fn helper_with_positions() -> PoolTestHelper {
+        let mut helper = PoolTestHelper::new();
+        helper.instantiate_default(pdec!(1), false);
+        helper.add_liquidity_default(-10, 10, dec!(10), dec!(10));
+        helper.add_liquidity_default(-10000, 10000, dec!(10), dec!(10));
+        helper.registry.execute_expect_success(false);
+        helper
+    }
+
+    fn move_price_out_of_range(helper: &mut PoolTestHelper) {
+        helper.swap_x_default(dec!(15));
+        helper.registry.execute_expect_success(false);
+    }
+
+    #[test]
+    fn test_position_name_and_description() {
+        let mut helper = helper_with_positions();
+
+        let position = helper.position_data(nft_id!(2));
+
+        assert!(position.name.starts_with("Ociswap LP "));
+        assert!(position.name.ends_with(" #2"));
+        assert!(position
+            .description
+            .starts_with("Liquidity position of the Ociswap Precision Pool"));
+        assert!(position
+            .description
+            .ends_with("between the prices 0.367897 and 2.71814."));
+    }
+
+    #[test]
+    fn test_position_image_in_range() {
+        let mut helper = helper_with_positions();
+
+        let key_image_url = helper.position_data(nft_id!(1)).key_image_url;
+
+        assert!(key_image_url
+            .as_str()
+            .starts_with("data:image/svg+xml,%3Csvg"));
+        assert!(key_image_url.as_str().contains("In%20range"));
+    }
+
+    #[test]
+    fn test_position_image_not_refreshed_by_swap() {
+        let mut helper = helper_with_positions();
+        move_price_out_of_range(&mut helper);
+
+        let key_image_url = helper.position_data(nft_id!(1)).key_image_url;
+
+        assert!(key_image_url.as_str().contains("In%20range"));
+    }
+
+    #[test]
+    fn test_position_image_refreshed_by_claim_fees() {
+        let mut helper = helper_with_positions();
+        move_price_out_of_range(&mut helper);
+
+        helper
+            .claim_fees(nft_ids!(1, 2))
+            .registry
+            .execute_expect_success(false);
+
+        assert!(helper
+            .position_data(nft_id!(1))
+            .key_image_url
+            .as_str()
+            .contains("Out%20of%20range"));
+        assert!(helper
+            .position_data(nft_id!(2))
+            .key_image_url
+            .as_str()
+            .contains("In%20range"));
+    }
+
+    #[test]
+    fn test_position_image_refreshed_by_split_position() {
+        let mut helper = helper_with_positions();
+        move_price_out_of_range(&mut helper);
+
+        helper
+            .split_position(nft_ids!(1), vec![dec!(0.5)])
+            .registry
+            .execute_expect_success(false);
+
+        assert!(helper
+            .position_data(nft_id!(1))
+            .key_image_url
+            .as_str()
+            .contains("Out%20of%20range"));
+        assert!(helper
+            .position_data(nft_id!(3))
+            .key_image_url
+            .as_str()
+            .contains("Out%20of%20range"));
+    }
+
+    #[test]
+    fn test_position_image_refreshed_by_compound_fees() {
+        let mut helper = helper_with_positions();
+        move_price_out_of_range(&mut helper);
+
+        helper
+            .compound_fees(nft_ids!(1))
+            .registry
+            .execute_expect_success(false);
+
+        assert!(helper
+            .position_data(nft_id!(1))
+            .key_image_url
+            .as_str()
+            .contains("Out%20of%20range"));
+    }
+}
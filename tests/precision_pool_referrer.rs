@@ -0,0 +1,356 @@
+#[cfg(test)]
+mod precision_pool_referrer {
+    use common::pools::SwapType;
+    use precision_pool::pool::SwapQuote;
+    use precision_pool_test_helper::*;
+    use radix_engine::system::system_modules::execution_trace::ResourceSpecifier;
+    use scrypto::prelude::*;
+    use scrypto_testenv::*;
+    use test_case::test_case;
+
+    const POSITIONS: [LiquidityPosition; 2] = [
+        LiquidityPosition {
+            left_bound: -200,
+            right_bound: 0,
+            x_amount: DEC_10,
+            y_amount: DEC_10,
+        },
+        LiquidityPosition {
+            left_bound: 0,
+            right_bound: 200,
+            x_amount: DEC_10,
+            y_amount: DEC_10,
+        },
+    ];
+
+    fn helper_with_positions() -> PoolTestHelper {
+        add_positions(PoolTestHelper::new())
+    }
+
+    fn add_positions(mut helper: PoolTestHelper) -> PoolTestHelper {
+        helper.instantiate_default_with_input_fee(pdec!(1), dec!(0.01), false);
+        helper.add_liquidity_default_batch(&POSITIONS);
+        helper.registry.execute_expect_success(false);
+        helper
+    }
+
+    fn new_referrer(helper: &mut PoolTestHelper) -> ComponentAddress {
+        let (_, _, account) = helper.registry.env.test_runner.new_allocated_account();
+        account
+    }
+
+    fn quote(
+        helper: &mut PoolTestHelper,
+        swap_type: SwapType,
+        input_amount: Decimal,
+        referrer_fee_rate: Decimal,
+    ) -> SwapQuote {
+        let receipt = helper
+            .quote_swap_with_referrer(
+                helper.input_address(swap_type),
+                input_amount,
+                None,
+                referrer_fee_rate,
+            )
+            .registry
+            .execute_expect_success(false);
+        let quotes: Vec<SwapQuote> = receipt.outputs("quote_swap_with_referrer");
+        quotes[0].clone()
+    }
+
+    fn swap_with_referrer(
+        helper: &mut PoolTestHelper,
+        swap_type: SwapType,
+        input_amount: Decimal,
+        referrer: Option<(ComponentAddress, Decimal)>,
+    ) -> (Decimal, Decimal) {
+        let receipt = helper
+            .swap_with_referrer(helper.input_address(swap_type), input_amount, referrer)
+            .registry
+            .execute_expect_success(false);
+        let output_buckets = receipt.output_buckets("swap_with_referrer");
+        match (&output_buckets[0][0], &output_buckets[0][1]) {
+            (ResourceSpecifier::Amount(_, output), ResourceSpecifier::Amount(_, remainder)) => {
+                (*output, *remainder)
+            }
+            _ => panic!("Expected fungible output buckets."),
+        }
+    }
+
+    fn referrer_fees(
+        helper: &mut PoolTestHelper,
+        referrer: ComponentAddress,
+    ) -> (Decimal, Decimal) {
+        let receipt = helper
+            .referrer_fees(referrer)
+            .registry
+            .execute_expect_success(false);
+        let fees: Vec<IndexMap<ResourceAddress, Decimal>> = receipt.outputs("referrer_fees");
+        (
+            *fees[0].get(&helper.x_address()).unwrap(),
+            *fees[0].get(&helper.y_address()).unwrap(),
+        )
+    }
+
+    #[test_case(SwapType::SellX, dec!(5) ; "sell_x")]
+    #[test_case(SwapType::BuyX, dec!(5) ; "buy_x")]
+    fn test_swap_with_referrer(swap_type: SwapType, input_amount: Decimal) {
+        let mut helper = helper_with_positions();
+        let referrer = new_referrer(&mut helper);
+        let quote = quote(&mut helper, swap_type, input_amount, dec!("0.005"));
+
+        let (output, remainder) = swap_with_referrer(
+            &mut helper,
+            swap_type,
+            input_amount,
+            Some((referrer, dec!("0.005"))),
+        );
+
+        assert_eq!(quote.input_fee_referrer, dec!("0.025"));
+        assert_eq!(
+            (output, remainder),
+            (quote.output_amount, quote.remainder_amount)
+        );
+        let expected_fees = match swap_type {
+            SwapType::SellX => (dec!("0.025"), dec!(0)),
+            SwapType::BuyX => (dec!(0), dec!("0.025")),
+        };
+        assert_eq!(referrer_fees(&mut helper, referrer), expected_fees);
+    }
+
+    #[test]
+    fn test_swap_with_referrer_reduces_output() {
+        let mut helper = helper_with_positions();
+        let without_referrer = quote(&mut helper, SwapType::SellX, dec!(5), dec!(0));
+        let with_referrer = quote(&mut helper, SwapType::SellX, dec!(5), dec!("0.01"));
+
+        assert_eq!(without_referrer.input_fee_referrer, dec!(0));
+        assert_eq!(with_referrer.input_fee_referrer, dec!("0.05"));
+        assert!(with_referrer.output_amount < without_referrer.output_amount);
+        // The input fee of the pool is applied to the gross input, so referring oneself does not lower it.
+        assert_eq!(without_referrer.input_fee_lp, dec!("0.05"));
+        assert_eq!(with_referrer.input_fee_lp, dec!("0.05"));
+    }
+
+    #[test]
+    fn test_swap_with_zero_referrer_fee_rate() {
+        let mut helper = helper_with_positions();
+        let referrer = new_referrer(&mut helper);
+
+        swap_with_referrer(
+            &mut helper,
+            SwapType::SellX,
+            dec!(1),
+            Some((referrer, dec!(0))),
+        );
+
+        // No balance is created for a referrer without fees, so there is nothing to claim.
+        assert_eq!(referrer_fees(&mut helper, referrer), (dec!(0), dec!(0)));
+        helper.claim_referrer_fees(referrer);
+        helper.registry.execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_swap_without_referrer_matches_swap() {
+        let mut helper = helper_with_positions();
+        let quote = quote(&mut helper, SwapType::SellX, dec!(5), dec!(0));
+
+        let (output, remainder) = swap_with_referrer(&mut helper, SwapType::SellX, dec!(5), None);
+
+        assert_eq!(
+            (output, remainder),
+            (quote.output_amount, quote.remainder_amount)
+        );
+    }
+
+    #[test]
+    fn test_swap_with_referrer_partial_fill() {
+        let mut helper = helper_with_positions();
+        let referrer = new_referrer(&mut helper);
+        let quote = quote(&mut helper, SwapType::SellX, dec!(100), dec!("0.01"));
+
+        let (_, remainder) = swap_with_referrer(
+            &mut helper,
+            SwapType::SellX,
+            dec!(100),
+            Some((referrer, dec!("0.01"))),
+        );
+
+        // The referrer fee is only charged on the swapped part of the input.
+        assert!(remainder > dec!(0));
+        assert!(quote.input_fee_referrer < dec!(1));
+        assert_eq!(
+            referrer_fees(&mut helper, referrer),
+            (quote.input_fee_referrer, dec!(0))
+        );
+    }
+
+    #[test_case(dec!("0.01"), true ; "max")]
+    #[test_case(dec!("0.010000000000000001"), false ; "above_max")]
+    #[test_case(dec!("-0.000000000000000001"), false ; "negative")]
+    fn test_swap_with_referrer_fee_rate(referrer_fee_rate: Decimal, expect_success: bool) {
+        let mut helper = helper_with_positions();
+        let referrer = new_referrer(&mut helper);
+
+        helper.swap_with_referrer(
+            helper.x_address(),
+            dec!(1),
+            Some((referrer, referrer_fee_rate)),
+        );
+
+        if expect_success {
+            helper.registry.execute_expect_success(false);
+        } else {
+            helper.registry.execute_expect_failure(false);
+        }
+    }
+
+    #[test]
+    fn test_referrer_fees_accrue_per_referrer() {
+        let mut helper = helper_with_positions();
+        let referrer_a = new_referrer(&mut helper);
+        let referrer_b = new_referrer(&mut helper);
+
+        swap_with_referrer(
+            &mut helper,
+            SwapType::SellX,
+            dec!(1),
+            Some((referrer_a, dec!("0.01"))),
+        );
+        swap_with_referrer(
+            &mut helper,
+            SwapType::BuyX,
+            dec!(2),
+            Some((referrer_a, dec!("0.01"))),
+        );
+        swap_with_referrer(
+            &mut helper,
+            SwapType::SellX,
+            dec!(3),
+            Some((referrer_b, dec!("0.01"))),
+        );
+
+        assert_eq!(
+            referrer_fees(&mut helper, referrer_a),
+            (dec!("0.01"), dec!("0.02"))
+        );
+        assert_eq!(
+            referrer_fees(&mut helper, referrer_b),
+            (dec!("0.03"), dec!(0))
+        );
+    }
+
+    #[test]
+    fn test_claim_referrer_fees() {
+        let mut helper = helper_with_positions();
+        let referrer = new_referrer(&mut helper);
+        swap_with_referrer(
+            &mut helper,
+            SwapType::SellX,
+            dec!(1),
+            Some((referrer, dec!("0.01"))),
+        );
+        swap_with_referrer(
+            &mut helper,
+            SwapType::BuyX,
+            dec!(2),
+            Some((referrer, dec!("0.01"))),
+        );
+        let x_balance = helper
+            .registry
+            .env
+            .test_runner
+            .get_component_balance(referrer, helper.x_address());
+        let y_balance = helper
+            .registry
+            .env
+            .test_runner
+            .get_component_balance(referrer, helper.y_address());
+
+        helper.claim_referrer_fees(referrer);
+        helper.registry.execute_expect_success(false);
+
+        assert_eq!(
+            helper
+                .registry
+                .env
+                .test_runner
+                .get_component_balance(referrer, helper.x_address()),
+            x_balance + dec!("0.01")
+        );
+        assert_eq!(
+            helper
+                .registry
+                .env
+                .test_runner
+                .get_component_balance(referrer, helper.y_address()),
+            y_balance + dec!("0.02")
+        );
+        assert_eq!(referrer_fees(&mut helper, referrer), (dec!(0), dec!(0)));
+    }
+
+    #[test]
+    fn test_claim_referrer_fees_unknown_referrer() {
+        let mut helper = helper_with_positions();
+        let referrer = new_referrer(&mut helper);
+        swap_with_referrer(&mut helper, SwapType::SellX, dec!(1), None);
+
+        assert_eq!(referrer_fees(&mut helper, referrer), (dec!(0), dec!(0)));
+        helper.claim_referrer_fees(referrer);
+        helper.registry.execute_expect_failure(false);
+    }
+
+    fn helper_with_test_referrer_fees() -> (PoolTestHelper, ComponentAddress) {
+        let mut helper = add_positions(PoolTestHelper::new_with_test_hook());
+        let referrer = helper.instantiate_test_referrer();
+        swap_with_referrer(
+            &mut helper,
+            SwapType::SellX,
+            dec!(1),
+            Some((referrer, dec!("0.01"))),
+        );
+        (helper, referrer)
+    }
+
+    #[test]
+    fn test_withdraw_referrer_fees() {
+        let (mut helper, referrer) = helper_with_test_referrer_fees();
+
+        let receipt = helper
+            .test_referrer_withdraw_referrer_fees(referrer)
+            .registry
+            .execute_expect_success(false);
+
+        let output_buckets = receipt.output_buckets("withdraw_referrer_fees");
+        assert_eq!(
+            output_buckets,
+            vec![vec![
+                ResourceSpecifier::Amount(helper.x_address(), dec!("0.01")),
+                ResourceSpecifier::Amount(helper.y_address(), dec!(0)),
+            ]]
+        );
+        assert_eq!(referrer_fees(&mut helper, referrer), (dec!(0), dec!(0)));
+    }
+
+    #[test]
+    fn test_withdraw_referrer_fees_not_referrer() {
+        let (mut helper, referrer) = helper_with_test_referrer_fees();
+
+        helper.withdraw_referrer_fees(referrer);
+        helper.registry.execute_expect_failure(false);
+
+        assert_eq!(
+            referrer_fees(&mut helper, referrer),
+            (dec!("0.01"), dec!(0))
+        );
+    }
+
+    #[test]
+    fn test_claim_referrer_fees_not_account() {
+        let (mut helper, referrer) = helper_with_test_referrer_fees();
+
+        // Fees are only pushed to accounts, the test referrer component has to withdraw its fees instead.
+        helper.claim_referrer_fees(referrer);
+        helper.registry.execute_expect_failure(false);
+    }
+}
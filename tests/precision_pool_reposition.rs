@@ -0,0 +1,166 @@
+#[cfg(test)]
+mod precision_pool_reposition {
+    use precision_pool_test_helper::*;
+    use radix_engine::system::system_modules::execution_trace::ResourceSpecifier::{Amount, Ids};
+    use scrypto::prelude::*;
+    use scrypto_testenv::*;
+
+    static TWO_LP: [LiquidityPosition; 2] = [
+        LiquidityPosition {
+            left_bound: TICK_LEFT_BOUND,
+            right_bound: TICK_RIGHT_BOUND,
+            x_amount: DEC_10,
+            y_amount: DEC_10,
+        },
+        LiquidityPosition {
+            left_bound: TICK_LEFT_BOUND,
+            right_bound: TICK_RIGHT_BOUND,
+            x_amount: dec!(100),
+            y_amount: dec!(100),
+        },
+    ];
+
+    fn helper_with_liquidity(input_fee_rate: Decimal) -> PoolTestHelper {
+        let mut helper = PoolTestHelper::new();
+        helper.instantiate_default_with_input_fee(
+            *PRICE_BETWEEN_MIDDLE_BOUNDS_SQRT,
+            input_fee_rate,
+            false,
+        );
+        helper
+            .add_liquidity_default_batch(&TWO_LP)
+            .registry
+            .execute_expect_success(false);
+        helper
+    }
+
+    // The price of the pool, i.e. the price the tests expect the rebalancing swap to be executed at.
+    const PRICE: Decimal = dec!(2);
+
+    fn reposition_success(
+        helper: &mut PoolTestHelper,
+        left_bound: i32,
+        right_bound: i32,
+        max_deviation: Decimal,
+        max_dust: Decimal,
+    ) {
+        let receipt = helper
+            .reposition(nft_ids!(1), left_bound, right_bound, (PRICE, max_deviation))
+            .registry
+            .execute_expect_success(false);
+        let output_buckets = receipt.output_buckets("reposition");
+        match &output_buckets[0][..] {
+            [Ids(lp_address, ids), Amount(_, x_dust), Amount(_, y_dust)] => {
+                assert_eq!(*lp_address, helper.lp_address.unwrap());
+                assert_eq!(*ids, nft_ids!(3));
+                assert!(*x_dust <= max_dust, "X dust too large: {:?}", x_dust);
+                assert!(*y_dust <= max_dust, "Y dust too large: {:?}", y_dust);
+            }
+            _ => panic!("Expected the new position and two fungible buckets."),
+        }
+    }
+
+    #[test]
+    fn test_reposition_narrower_range() {
+        let mut helper = helper_with_liquidity(dec!(0));
+        reposition_success(
+            &mut helper,
+            TICK_LEFT_MIDDLE_BOUND,
+            TICK_RIGHT_MIDDLE_BOUND,
+            dec!("0.1"),
+            dec!("0.01"),
+        );
+    }
+
+    #[test]
+    fn test_reposition_range_above_price() {
+        let mut helper = helper_with_liquidity(dec!(0));
+        reposition_success(&mut helper, 20000, 30000, dec!("0.1"), dec!(0));
+    }
+
+    #[test]
+    fn test_reposition_range_below_price() {
+        let mut helper = helper_with_liquidity(dec!(0));
+        reposition_success(&mut helper, -20000, 0, dec!("0.1"), dec!(0));
+    }
+
+    #[test]
+    fn test_reposition_old_position_burned() {
+        let mut helper = helper_with_liquidity(dec!(0));
+        helper
+            .reposition(nft_ids!(1), -20000, 0, (PRICE, dec!("0.1")))
+            .registry
+            .execute_expect_success(false);
+        helper
+            .remove_liquidity(nft_ids!(1))
+            .registry
+            .execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_reposition_slippage_exceeded() {
+        // The input fee alone exceeds the allowed slippage.
+        let mut helper = helper_with_liquidity(FEE_RATE);
+        helper
+            .reposition(nft_ids!(1), 20000, 30000, (PRICE, dec!("0.01")))
+            .registry
+            .execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_reposition_slippage_within_limit() {
+        let mut helper = helper_with_liquidity(FEE_RATE);
+        reposition_success(&mut helper, 20000, 30000, dec!("0.2"), dec!(0));
+    }
+
+    #[test]
+    fn test_reposition_manipulated_price() {
+        // A swap right before the reposition in the same transaction, e.g. by a sandwich attack,
+        // moves the price away from the price expected by the caller.
+        let mut helper = helper_with_liquidity(dec!(0));
+        helper.swap_x_default(dec!(20));
+        helper
+            .reposition(
+                nft_ids!(1),
+                TICK_LEFT_MIDDLE_BOUND,
+                TICK_RIGHT_MIDDLE_BOUND,
+                (PRICE, dec!("0.01")),
+            )
+            .registry
+            .execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_reposition_manipulated_price_expected_by_caller() {
+        // The same reposition succeeds if the caller expects the moved price.
+        let mut helper = helper_with_liquidity(dec!(0));
+        helper.swap_x_default(dec!(20));
+        helper.registry.execute_expect_success(false);
+        let receipt = helper.price_sqrt().registry.execute_expect_success(false);
+        let price_sqrt: PreciseDecimal = receipt.outputs("price_sqrt")[0];
+        let price = Decimal::try_from(
+            (price_sqrt * price_sqrt)
+                .checked_round(18, RoundingMode::ToZero)
+                .unwrap(),
+        )
+        .unwrap();
+        helper
+            .reposition(
+                nft_ids!(1),
+                TICK_LEFT_MIDDLE_BOUND,
+                TICK_RIGHT_MIDDLE_BOUND,
+                (price, dec!("0.01")),
+            )
+            .registry
+            .execute_expect_success(false);
+    }
+
+    #[test]
+    fn test_reposition_multiple_positions() {
+        let mut helper = helper_with_liquidity(dec!(0));
+        helper
+            .reposition(nft_ids!(1, 2), -20000, 0, (PRICE, dec!("0.1")))
+            .registry
+            .execute_expect_failure(false);
+    }
+}
@@ -0,0 +1,269 @@
+#[cfg(test)]
+mod precision_pool_shape {
+    use precision_pool_test_helper::*;
+    use radix_engine::system::system_modules::execution_trace::ResourceSpecifier::Amount;
+    use scrypto::prelude::*;
+    use scrypto_testenv::*;
+
+    // Adds a shape consisting of the positions 1 and 2 and returns its identifier.
+    fn add_shape(helper: &mut PoolTestHelper) -> NonFungibleLocalId {
+        helper
+            .add_liquidity_shape(
+                -10,
+                10,
+                helper.x_address(),
+                dec!(10),
+                helper.y_address(),
+                dec!(10),
+                None,
+            )
+            .registry
+            .execute_expect_success(false);
+        shape_id(helper, nft_id!(1))
+    }
+
+    fn shape_id(
+        helper: &mut PoolTestHelper,
+        position_id: NonFungibleLocalId,
+    ) -> NonFungibleLocalId {
+        let position: precision_pool::pool::LiquidityPosition = helper
+            .registry
+            .env
+            .test_runner
+            .get_non_fungible_data(helper.lp_address.unwrap(), position_id);
+        position.shape_id.unwrap()
+    }
+
+    fn shape_positions(
+        helper: &mut PoolTestHelper,
+        shape_id: NonFungibleLocalId,
+    ) -> IndexSet<NonFungibleLocalId> {
+        let receipt = helper
+            .shape_positions(shape_id)
+            .registry
+            .execute_expect_success(false);
+        let output: Vec<IndexSet<NonFungibleLocalId>> = receipt.outputs("shape_positions");
+        output[0].clone()
+    }
+
+    fn helper() -> PoolTestHelper {
+        let mut helper = PoolTestHelper::new();
+        helper.instantiate_default(pdec!(1), false);
+        helper
+    }
+
+    #[test]
+    fn test_shape_positions() {
+        let mut helper = helper();
+        let shape_id = add_shape(&mut helper);
+        assert_eq!(shape_positions(&mut helper, shape_id), nft_ids!(1, 2));
+    }
+
+    #[test]
+    fn test_shape_positions_added_with_proof() {
+        let mut helper = helper();
+        let shape_id = add_shape(&mut helper);
+        helper
+            .add_liquidity_shape(
+                -20,
+                20,
+                helper.x_address(),
+                dec!(10),
+                helper.y_address(),
+                dec!(10),
+                Some(nft_ids!(1)),
+            )
+            .registry
+            .execute_expect_success(false);
+        assert_eq!(shape_positions(&mut helper, shape_id), nft_ids!(1, 2, 3, 4));
+    }
+
+    #[test]
+    fn test_shape_positions_separate_shapes() {
+        let mut helper = helper();
+        let first_shape_id = add_shape(&mut helper);
+        helper
+            .add_liquidity_shape(
+                -10,
+                10,
+                helper.x_address(),
+                dec!(10),
+                helper.y_address(),
+                dec!(10),
+                None,
+            )
+            .registry
+            .execute_expect_success(false);
+        let second_shape_id = shape_id(&mut helper, nft_id!(3));
+
+        assert_ne!(first_shape_id, second_shape_id);
+        assert_eq!(shape_positions(&mut helper, first_shape_id), nft_ids!(1, 2));
+        assert_eq!(
+            shape_positions(&mut helper, second_shape_id),
+            nft_ids!(3, 4)
+        );
+    }
+
+    #[test]
+    fn test_shape_positions_unknown_shape() {
+        let mut helper = helper();
+        add_shape(&mut helper);
+        assert_eq!(
+            shape_positions(&mut helper, NonFungibleLocalId::integer(1)),
+            IndexSet::new()
+        );
+    }
+
+    #[test]
+    fn test_shape_positions_after_remove_liquidity() {
+        let mut helper = helper();
+        let shape_id = add_shape(&mut helper);
+        helper
+            .remove_liquidity(nft_ids!(1))
+            .registry
+            .execute_expect_success(false);
+        assert_eq!(shape_positions(&mut helper, shape_id), nft_ids!(2));
+    }
+
+    #[test]
+    fn test_shape_positions_after_merge() {
+        let mut helper = helper();
+        let shape_id = add_shape(&mut helper);
+        helper
+            .merge_positions(nft_ids!(1, 2))
+            .registry
+            .execute_expect_success(false);
+        assert_eq!(shape_positions(&mut helper, shape_id), nft_ids!(3));
+    }
+
+    #[test]
+    fn test_shape_positions_after_split() {
+        let mut helper = helper();
+        let shape_id = add_shape(&mut helper);
+        helper
+            .split_position(nft_ids!(1), vec![dec!("0.5")])
+            .registry
+            .execute_expect_success(false);
+        assert_eq!(shape_positions(&mut helper, shape_id), nft_ids!(1, 2, 3));
+    }
+
+    #[test]
+    fn test_remove_shape() {
+        let mut helper = helper();
+        let shape_id = add_shape(&mut helper);
+
+        let receipt = helper
+            .shape_value(shape_id.clone())
+            .registry
+            .execute_expect_success(false);
+        let value: Vec<IndexMap<ResourceAddress, Decimal>> = receipt.outputs("shape_value");
+        let receipt = helper
+            .remove_shape(nft_ids!(1, 2))
+            .registry
+            .execute_expect_success(false);
+        let output_buckets = receipt.output_buckets("remove_shape");
+
+        assert_eq!(
+            output_buckets,
+            vec![vec![
+                Amount(helper.x_address(), value[0][&helper.x_address()]),
+                Amount(helper.y_address(), value[0][&helper.y_address()])
+            ]]
+        );
+        assert_eq!(shape_positions(&mut helper, shape_id), IndexSet::new());
+    }
+
+    #[test]
+    fn test_remove_shape_missing_position() {
+        let mut helper = helper();
+        add_shape(&mut helper);
+        helper
+            .remove_shape(nft_ids!(1))
+            .registry
+            .execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_remove_shape_different_shapes() {
+        let mut helper = helper();
+        add_shape(&mut helper);
+        add_shape(&mut helper);
+        helper
+            .remove_shape(nft_ids!(1, 2, 3, 4))
+            .registry
+            .execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_remove_shape_without_shape() {
+        let mut helper = helper();
+        helper.add_liquidity_success(-10, 10, dec!(10), dec!(10), dec!(0), dec!(0));
+        helper
+            .remove_shape(nft_ids!(1))
+            .registry
+            .execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_claim_shape_fees() {
+        let mut helper = helper();
+        add_shape(&mut helper);
+        helper
+            .swap(helper.x_address(), dec!(1))
+            .registry
+            .execute_expect_success(false);
+
+        let receipt = helper
+            .claimable_fees(nft_ids!(1, 2))
+            .registry
+            .execute_expect_success(false);
+        let claimable: Vec<IndexMap<ResourceAddress, Decimal>> = receipt.outputs("claimable_fees");
+        let receipt = helper
+            .claim_shape_fees(nft_ids!(1, 2))
+            .registry
+            .execute_expect_success(false);
+        let output_buckets = receipt.output_buckets("claim_shape_fees");
+
+        assert_eq!(
+            output_buckets,
+            vec![vec![
+                Amount(helper.x_address(), claimable[0][&helper.x_address()]),
+                Amount(helper.y_address(), claimable[0][&helper.y_address()])
+            ]]
+        );
+    }
+
+    #[test]
+    fn test_claim_shape_fees_missing_position() {
+        let mut helper = helper();
+        add_shape(&mut helper);
+        helper
+            .claim_shape_fees(nft_ids!(2))
+            .registry
+            .execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_shape_value() {
+        let mut helper = helper();
+        let shape_id = add_shape(&mut helper);
+        helper
+            .swap(helper.x_address(), dec!(1))
+            .registry
+            .execute_expect_success(false);
+
+        let receipt = helper
+            .removable_liquidity(nft_ids!(1, 2))
+            .registry
+            .execute_expect_success(false);
+        let removable: Vec<(IndexMap<ResourceAddress, Decimal>, Decimal)> =
+            receipt.outputs("removable_liquidity");
+        let receipt = helper
+            .shape_value(shape_id)
+            .registry
+            .execute_expect_success(false);
+        let value: Vec<IndexMap<ResourceAddress, Decimal>> = receipt.outputs("shape_value");
+
+        assert_eq!(value, vec![removable[0].0.clone()]);
+    }
+}
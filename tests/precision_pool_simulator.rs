@@ -0,0 +1,242 @@
+#[cfg(test)]
+mod precision_pool_simulator {
+    use common::pools::SwapType;
+    use precision_pool::constants::SNAPSHOT_TICKS_LIMIT_MAX;
+    use precision_pool::pool::{FeeCollectionMode, PoolSnapshot, TicksSnapshot};
+    use precision_pool_math::{PoolSimulator, SimulatedPosition};
+    use precision_pool_test_helper::*;
+    use radix_engine::system::system_modules::execution_trace::ResourceSpecifier;
+    use scrypto::prelude::*;
+    use scrypto_testenv::*;
+    use test_case::test_case;
+
+    const POSITIONS: [LiquidityPosition; 3] = [
+        LiquidityPosition {
+            left_bound: -300,
+            right_bound: -100,
+            x_amount: DEC_10,
+            y_amount: DEC_10,
+        },
+        LiquidityPosition {
+            left_bound: -100,
+            right_bound: 100,
+            x_amount: DEC_10,
+            y_amount: DEC_10,
+        },
+        LiquidityPosition {
+            left_bound: 100,
+            right_bound: 300,
+            x_amount: DEC_10,
+            y_amount: DEC_10,
+        },
+    ];
+
+    fn helper_with_positions() -> PoolTestHelper {
+        helper_with_positions_and_fee_collection_mode(FeeCollectionMode::Input)
+    }
+
+    fn helper_with_positions_and_fee_collection_mode(
+        fee_collection_mode: FeeCollectionMode,
+    ) -> PoolTestHelper {
+        let mut helper = PoolTestHelper::new();
+        helper.instantiate_default_with_fee_collection_mode(
+            pdec!(1),
+            dec!(0.01),
+            fee_collection_mode,
+            false,
+        );
+        for position in POSITIONS {
+            helper.add_liquidity_default(
+                position.left_bound,
+                position.right_bound,
+                position.x_amount,
+                position.y_amount,
+            );
+        }
+        helper.registry.execute_expect_success(false);
+        helper
+    }
+
+    fn now(helper: &mut PoolTestHelper) -> u64 {
+        helper
+            .registry
+            .env
+            .test_runner
+            .get_current_time(TimePrecision::Second)
+            .seconds_since_unix_epoch as u64
+    }
+
+    fn simulator(helper: &mut PoolTestHelper) -> PoolSimulator {
+        let receipt = helper.snapshot().registry.execute_expect_success(false);
+        let snapshots: Vec<PoolSnapshot> = receipt.outputs("snapshot");
+
+        let mut ticks = vec![];
+        let mut cursor = None;
+        loop {
+            let receipt = helper
+                .snapshot_ticks(cursor, SNAPSHOT_TICKS_LIMIT_MAX)
+                .registry
+                .execute_expect_success(false);
+            let pages: Vec<TicksSnapshot> = receipt.outputs("snapshot_ticks");
+            let TicksSnapshot::V1(page) = pages[0].clone();
+            ticks.extend(page.ticks);
+            cursor = page.next_cursor;
+            if cursor.is_none() {
+                break;
+            }
+        }
+
+        let now = now(helper);
+        PoolSimulator::new(snapshots[0].clone(), ticks, now)
+    }
+
+    fn assert_state_eq(helper: &mut PoolTestHelper, simulator: &PoolSimulator) {
+        let receipt = helper.snapshot().registry.execute_expect_success(false);
+        let snapshots: Vec<PoolSnapshot> = receipt.outputs("snapshot");
+        let PoolSnapshot::V1(snapshot) = snapshots[0].clone();
+
+        assert_eq!(simulator.price_sqrt(), snapshot.price_sqrt);
+        assert_eq!(simulator.active_tick(), snapshot.active_tick);
+        assert_eq!(simulator.active_liquidity(), snapshot.active_liquidity);
+        assert_eq!(
+            simulator.total_liquidity(),
+            (snapshot.x_liquidity, snapshot.y_liquidity)
+        );
+    }
+
+    #[test_case(SwapType::BuyX, dec!(1) ; "buy_within_range")]
+    #[test_case(SwapType::BuyX, dec!(15) ; "buy_crossing_ticks")]
+    #[test_case(SwapType::BuyX, dec!(100) ; "buy_exhausting_liquidity")]
+    #[test_case(SwapType::SellX, dec!(1) ; "sell_within_range")]
+    #[test_case(SwapType::SellX, dec!(15) ; "sell_crossing_ticks")]
+    #[test_case(SwapType::SellX, dec!(100) ; "sell_exhausting_liquidity")]
+    fn test_swap_matches_ledger(swap_type: SwapType, input_amount: Decimal) {
+        let mut helper = helper_with_positions();
+        let mut simulator = simulator(&mut helper);
+
+        let quote = simulator.swap(helper.input_address(swap_type), input_amount, None);
+        let receipt = helper
+            .swap(helper.input_address(swap_type), input_amount)
+            .registry
+            .execute_expect_success(false);
+        let (output, remainder) = PoolTestHelper::amounts(&receipt.output_buckets("swap")[0]);
+
+        assert_eq!(quote.output_amount, output);
+        assert_eq!(quote.remainder_amount, remainder);
+        assert_state_eq(&mut helper, &simulator);
+    }
+
+    #[test_case(FeeCollectionMode::Output ; "output")]
+    #[test_case(FeeCollectionMode::X ; "x")]
+    #[test_case(FeeCollectionMode::Y ; "y")]
+    fn test_swap_fee_collection_mode_matches_ledger(fee_collection_mode: FeeCollectionMode) {
+        let mut helper = helper_with_positions_and_fee_collection_mode(fee_collection_mode);
+        let mut simulator = simulator(&mut helper);
+
+        for (swap_type, input_amount) in [(SwapType::BuyX, dec!(15)), (SwapType::SellX, dec!(20))] {
+            let quote = simulator.swap(helper.input_address(swap_type), input_amount, None);
+            let receipt = helper
+                .swap(helper.input_address(swap_type), input_amount)
+                .registry
+                .execute_expect_success(false);
+            let (output, remainder) = PoolTestHelper::amounts(&receipt.output_buckets("swap")[0]);
+            assert_eq!(quote.output_amount, output);
+            assert_eq!(quote.remainder_amount, remainder);
+        }
+        assert_state_eq(&mut helper, &simulator);
+    }
+
+    #[test]
+    fn test_swap_sequence_matches_ledger() {
+        let mut helper = helper_with_positions();
+        let mut simulator = simulator(&mut helper);
+
+        for (swap_type, input_amount) in [
+            (SwapType::BuyX, dec!(12)),
+            (SwapType::SellX, dec!(20)),
+            (SwapType::BuyX, dec!(3)),
+        ] {
+            let quote = simulator.swap(helper.input_address(swap_type), input_amount, None);
+            let receipt = helper
+                .swap(helper.input_address(swap_type), input_amount)
+                .registry
+                .execute_expect_success(false);
+            let (output, _) = PoolTestHelper::amounts(&receipt.output_buckets("swap")[0]);
+            assert_eq!(quote.output_amount, output);
+        }
+        assert_state_eq(&mut helper, &simulator);
+    }
+
+    #[test_case(-200, 200 ; "around_price")]
+    #[test_case(100, 200 ; "above_price")]
+    #[test_case(-200, -100 ; "below_price")]
+    fn test_add_liquidity_matches_ledger(left_bound: i32, right_bound: i32) {
+        let mut helper = helper_with_positions();
+        helper.swap(helper.x_address(), dec!(5));
+        helper.registry.execute_expect_success(false);
+        let mut simulator = simulator(&mut helper);
+
+        let (position, x_amount, y_amount) =
+            simulator.add_liquidity(left_bound, right_bound, dec!(10), dec!(10));
+        let receipt = helper
+            .add_liquidity_default(left_bound, right_bound, dec!(10), dec!(10))
+            .registry
+            .execute_expect_success(false);
+        let output_buckets = receipt.output_buckets("add_liquidity");
+
+        assert_eq!(
+            output_buckets[0][1..].to_vec(),
+            vec![
+                ResourceSpecifier::Amount(helper.x_address(), dec!(10) - x_amount),
+                ResourceSpecifier::Amount(helper.y_address(), dec!(10) - y_amount),
+            ]
+        );
+        assert_eq!(
+            (position.left_bound, position.right_bound),
+            (left_bound, right_bound)
+        );
+        assert_state_eq(&mut helper, &simulator);
+    }
+
+    #[test]
+    fn test_remove_liquidity_matches_ledger() {
+        let mut helper = PoolTestHelper::new();
+        helper.instantiate_default_with_input_fee(pdec!(1), dec!(0.01), false);
+        let mut simulator = simulator(&mut helper);
+        let positions: Vec<SimulatedPosition> = POSITIONS
+            .iter()
+            .map(|position| {
+                helper.add_liquidity_default(
+                    position.left_bound,
+                    position.right_bound,
+                    position.x_amount,
+                    position.y_amount,
+                );
+                simulator
+                    .add_liquidity(
+                        position.left_bound,
+                        position.right_bound,
+                        position.x_amount,
+                        position.y_amount,
+                    )
+                    .0
+            })
+            .collect();
+        helper.registry.execute_expect_success(false);
+
+        helper.swap(helper.y_address(), dec!(15));
+        helper.swap(helper.x_address(), dec!(7));
+        helper.registry.execute_expect_success(false);
+        simulator.swap(helper.y_address(), dec!(15), None);
+        simulator.swap(helper.x_address(), dec!(7), None);
+
+        let receipt = helper
+            .remove_liquidity(nft_ids!(2))
+            .registry
+            .execute_expect_success(false);
+        let output = PoolTestHelper::amounts(&receipt.output_buckets("remove_liquidity")[0]);
+
+        assert_eq!(simulator.remove_liquidity(positions[1].clone()), output);
+        assert_state_eq(&mut helper, &simulator);
+    }
+}
@@ -0,0 +1,207 @@
+#[cfg(test)]
+mod precision_pool_slippage {
+    use precision_pool_test_helper::*;
+    use scrypto::prelude::*;
+    use scrypto_testenv::*;
+
+    static ONE_LP: [LiquidityPosition; 1] = [LiquidityPosition {
+        left_bound: TICK_LEFT_BOUND,
+        right_bound: TICK_RIGHT_BOUND,
+        x_amount: DEC_10,
+        y_amount: DEC_10,
+    }];
+
+    fn helper_with_liquidity() -> PoolTestHelper {
+        let mut helper = PoolTestHelper::new();
+        helper.instantiate_default(*PRICE_BETWEEN_MIDDLE_BOUNDS_SQRT, true);
+        helper
+            .add_liquidity_default_batch(&ONE_LP)
+            .registry
+            .execute_expect_success(false);
+        helper
+    }
+
+    #[test]
+    fn test_add_liquidity_with_limits_no_limits() {
+        let mut helper = PoolTestHelper::new();
+        helper.instantiate_default(*PRICE_BETWEEN_MIDDLE_BOUNDS_SQRT, true);
+        helper
+            .add_liquidity_with_limits(
+                TICK_LEFT_BOUND,
+                TICK_RIGHT_BOUND,
+                DEC_10,
+                DEC_10,
+                dec!(0),
+                dec!(0),
+                None,
+            )
+            .registry
+            .execute_expect_success(false);
+    }
+
+    #[test]
+    fn test_add_liquidity_with_limits_min_amounts_met() {
+        let mut helper = PoolTestHelper::new();
+        helper.instantiate_default(*PRICE_BETWEEN_MIDDLE_BOUNDS_SQRT, true);
+        helper
+            .add_liquidity_with_limits(
+                TICK_LEFT_BOUND,
+                TICK_RIGHT_BOUND,
+                DEC_10,
+                DEC_10,
+                dec!("2.9"),
+                dec!("9.99"),
+                Some((dec!(2), dec!(0))),
+            )
+            .registry
+            .execute_expect_success(false);
+    }
+
+    #[test]
+    fn test_add_liquidity_with_limits_x_min_not_met() {
+        let mut helper = PoolTestHelper::new();
+        helper.instantiate_default(*PRICE_BETWEEN_MIDDLE_BOUNDS_SQRT, true);
+        helper
+            .add_liquidity_with_limits(
+                TICK_LEFT_BOUND,
+                TICK_RIGHT_BOUND,
+                DEC_10,
+                DEC_10,
+                dec!(3),
+                dec!(0),
+                None,
+            )
+            .registry
+            .execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_add_liquidity_with_limits_y_min_not_met_after_price_move() {
+        let mut helper = helper_with_liquidity();
+        helper.swap_x_default(dec!(5));
+        helper
+            .add_liquidity_with_limits(
+                TICK_LEFT_BOUND,
+                TICK_RIGHT_BOUND,
+                DEC_10,
+                DEC_10,
+                dec!(0),
+                DEC_10,
+                None,
+            )
+            .registry
+            .execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_add_liquidity_with_limits_price_within_deviation() {
+        let mut helper = helper_with_liquidity();
+        helper
+            .add_liquidity_with_limits(
+                TICK_LEFT_BOUND,
+                TICK_RIGHT_BOUND,
+                DEC_10,
+                DEC_10,
+                dec!(0),
+                dec!(0),
+                Some((dec!("2.05"), dec!("0.05"))),
+            )
+            .registry
+            .execute_expect_success(false);
+    }
+
+    #[test]
+    fn test_add_liquidity_with_limits_price_deviation_exceeded() {
+        let mut helper = helper_with_liquidity();
+        helper.swap_x_default(dec!(5));
+        helper
+            .add_liquidity_with_limits(
+                TICK_LEFT_BOUND,
+                TICK_RIGHT_BOUND,
+                DEC_10,
+                DEC_10,
+                dec!(0),
+                dec!(0),
+                Some((dec!(2), dec!("0.01"))),
+            )
+            .registry
+            .execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_add_liquidity_with_limits_negative_deviation() {
+        let mut helper = helper_with_liquidity();
+        helper
+            .add_liquidity_with_limits(
+                TICK_LEFT_BOUND,
+                TICK_RIGHT_BOUND,
+                DEC_10,
+                DEC_10,
+                dec!(0),
+                dec!(0),
+                Some((dec!(2), dec!(-1))),
+            )
+            .registry
+            .execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_remove_liquidity_with_limits_exact_removable_amounts() {
+        let mut helper = helper_with_liquidity();
+        helper
+            .remove_liquidity_with_limits(
+                nft_ids!(1),
+                dec!("2.906176684560680878"),
+                dec!("9.999999999999999997"),
+            )
+            .registry
+            .execute_expect_success(false);
+    }
+
+    #[test]
+    fn test_remove_liquidity_with_limits_includes_fees() {
+        let mut helper = PoolTestHelper::new();
+        helper.instantiate_default_with_input_fee(
+            *PRICE_BETWEEN_MIDDLE_BOUNDS_SQRT,
+            dec!("0.1"),
+            true,
+        );
+        helper
+            .add_liquidity_default_batch(&ONE_LP)
+            .registry
+            .execute_expect_success(false);
+        helper.swap_x_default(dec!(1));
+        let receipt = helper
+            .removable_liquidity(nft_ids!(1))
+            .registry
+            .execute_expect_success(false);
+        let output: Vec<(IndexMap<ResourceAddress, Decimal>, Decimal)> =
+            receipt.outputs("removable_liquidity");
+        let (x_removable, y_removable) = (
+            output[0].0[&helper.x_address()],
+            output[0].0[&helper.y_address()],
+        );
+        helper
+            .remove_liquidity_with_limits(nft_ids!(1), x_removable, y_removable)
+            .registry
+            .execute_expect_success(false);
+    }
+
+    #[test]
+    fn test_remove_liquidity_with_limits_x_min_not_met() {
+        let mut helper = helper_with_liquidity();
+        helper
+            .remove_liquidity_with_limits(nft_ids!(1), dec!("2.906176684560680879"), dec!(0))
+            .registry
+            .execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_remove_liquidity_with_limits_y_min_not_met() {
+        let mut helper = helper_with_liquidity();
+        helper
+            .remove_liquidity_with_limits(nft_ids!(1), dec!(0), DEC_10)
+            .registry
+            .execute_expect_failure(false);
+    }
+}
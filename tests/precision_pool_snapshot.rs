@@ -0,0 +1,199 @@
+#[cfg(test)]
+mod precision_pool_snapshot {
+    use precision_pool::constants::SNAPSHOT_TICKS_LIMIT_MAX;
+    use precision_pool::pool::{PoolSnapshot, PoolSnapshotV1, TicksSnapshot, TicksSnapshotV1};
+    use precision_pool::pool_math::tick_to_price_sqrt;
+    use precision_pool_test_helper::*;
+    use scrypto::prelude::*;
+    use scrypto_testenv::*;
+    use test_case::test_case;
+
+    const POSITIONS: [LiquidityPosition; 3] = [
+        LiquidityPosition {
+            left_bound: -300,
+            right_bound: -100,
+            x_amount: DEC_10,
+            y_amount: DEC_10,
+        },
+        LiquidityPosition {
+            left_bound: -100,
+            right_bound: 100,
+            x_amount: DEC_10,
+            y_amount: DEC_10,
+        },
+        LiquidityPosition {
+            left_bound: 200,
+            right_bound: 300,
+            x_amount: DEC_10,
+            y_amount: DEC_10,
+        },
+    ];
+
+    const TICKS: [i32; 5] = [-300, -100, 100, 200, 300];
+
+    fn helper_with_positions() -> PoolTestHelper {
+        let mut helper = PoolTestHelper::new_default_with_positions(pdec!(1), &POSITIONS);
+        helper.registry.execute_expect_success(false);
+        helper
+    }
+
+    fn snapshot(helper: &mut PoolTestHelper) -> PoolSnapshotV1 {
+        let receipt = helper.snapshot().registry.execute_expect_success(false);
+        let snapshots: Vec<PoolSnapshot> = receipt.outputs("snapshot");
+        match snapshots[0].clone() {
+            PoolSnapshot::V1(snapshot) => snapshot,
+        }
+    }
+
+    fn snapshot_ticks(
+        helper: &mut PoolTestHelper,
+        cursor: Option<i32>,
+        limit: u32,
+    ) -> TicksSnapshotV1 {
+        let receipt = helper
+            .snapshot_ticks(cursor, limit)
+            .registry
+            .execute_expect_success(false);
+        let pages: Vec<TicksSnapshot> = receipt.outputs("snapshot_ticks");
+        match pages[0].clone() {
+            TicksSnapshot::V1(page) => page,
+        }
+    }
+
+    #[test]
+    fn test_snapshot_after_instantiate() {
+        let mut helper = PoolTestHelper::new();
+        helper.instantiate_default(pdec!(1), false);
+
+        let snapshot = snapshot(&mut helper);
+
+        assert_eq!(snapshot.pool_address, helper.pool_address.unwrap());
+        assert_eq!(snapshot.x_address, helper.x_address());
+        assert_eq!(snapshot.y_address, helper.y_address());
+        assert_eq!(snapshot.lp_address, helper.lp_address.unwrap());
+        assert_eq!(snapshot.price_sqrt, pdec!(1));
+        assert_eq!(snapshot.active_tick, None);
+        assert_eq!(snapshot.active_liquidity, pdec!(0));
+        assert_eq!(snapshot.x_liquidity, dec!(0));
+        assert_eq!(snapshot.y_liquidity, dec!(0));
+        assert_eq!(snapshot.x_lp_fee, pdec!(0));
+        assert_eq!(snapshot.y_lp_fee, pdec!(0));
+        assert_eq!(snapshot.lp_counter, 0);
+        assert!(snapshot.hooks.is_empty());
+    }
+
+    #[test]
+    fn test_snapshot_matches_getters() {
+        let mut helper = helper_with_positions();
+        helper.swap(helper.x_address(), dec!(1));
+        helper.registry.execute_expect_success(false);
+
+        let snapshot = snapshot(&mut helper);
+
+        let receipt = helper.price_sqrt().registry.execute_expect_success(false);
+        let price_sqrt: Vec<PreciseDecimal> = receipt.outputs("price_sqrt");
+        let receipt = helper
+            .total_liquidity()
+            .registry
+            .execute_expect_success(false);
+        let total_liquidity: Vec<IndexMap<ResourceAddress, Decimal>> =
+            receipt.outputs("total_liquidity");
+        let receipt = helper.active_tick().registry.execute_expect_success(false);
+        let active_tick: Vec<Option<i32>> = receipt.outputs("active_tick");
+
+        assert_eq!(snapshot.price_sqrt, price_sqrt[0]);
+        assert_eq!(snapshot.active_tick, active_tick[0]);
+        assert_eq!(
+            snapshot.x_liquidity,
+            *total_liquidity[0].get(&helper.x_address()).unwrap()
+        );
+        assert_eq!(
+            snapshot.y_liquidity,
+            *total_liquidity[0].get(&helper.y_address()).unwrap()
+        );
+        assert_eq!(snapshot.lp_counter, POSITIONS.len() as u64);
+    }
+
+    #[test]
+    fn test_snapshot_fee_growth() {
+        let mut helper = PoolTestHelper::new();
+        helper.instantiate_default_with_input_fee(pdec!(1), dec!(0.01), false);
+        helper.add_liquidity_default(-100, 100, dec!(10), dec!(10));
+        helper.registry.execute_expect_success(false);
+        helper.swap(helper.x_address(), dec!(1));
+        helper.registry.execute_expect_success(false);
+
+        let snapshot = snapshot(&mut helper);
+
+        assert_eq!(snapshot.input_fee_rate, dec!(0.01));
+        assert!(snapshot.x_lp_fee > pdec!(0));
+        assert_eq!(snapshot.y_lp_fee, pdec!(0));
+        assert!(snapshot.x_fees > dec!(0));
+    }
+
+    #[test]
+    fn test_snapshot_ticks_single_page() {
+        let mut helper = helper_with_positions();
+
+        let page = snapshot_ticks(&mut helper, None, SNAPSHOT_TICKS_LIMIT_MAX);
+
+        let indices: Vec<i32> = page.ticks.iter().map(|tick| tick.index).collect();
+        assert_eq!(indices, TICKS.to_vec());
+        assert_eq!(page.next_cursor, None);
+        for tick in page.ticks {
+            assert_eq!(tick.price_sqrt, tick_to_price_sqrt(tick.index));
+        }
+    }
+
+    #[test_case(1 ; "limit_1")]
+    #[test_case(2 ; "limit_2")]
+    #[test_case(5 ; "limit_5")]
+    #[test_case(6 ; "limit_6")]
+    fn test_snapshot_ticks_pagination(limit: u32) {
+        let mut helper = helper_with_positions();
+
+        let mut indices: Vec<i32> = vec![];
+        let mut cursor = None;
+        loop {
+            let page = snapshot_ticks(&mut helper, cursor, limit);
+            assert!(page.ticks.len() <= limit as usize);
+            indices.extend(page.ticks.iter().map(|tick| tick.index));
+            cursor = page.next_cursor;
+            if cursor.is_none() {
+                break;
+            }
+        }
+
+        assert_eq!(indices, TICKS.to_vec());
+    }
+
+    #[test]
+    fn test_snapshot_ticks_cursor_between_ticks() {
+        let mut helper = helper_with_positions();
+
+        let page = snapshot_ticks(&mut helper, Some(50), 2);
+
+        let indices: Vec<i32> = page.ticks.iter().map(|tick| tick.index).collect();
+        assert_eq!(indices, vec![100, 200]);
+        assert_eq!(page.next_cursor, Some(200));
+    }
+
+    #[test]
+    fn test_snapshot_ticks_empty_pool() {
+        let mut helper = PoolTestHelper::new();
+        helper.instantiate_default(pdec!(1), false);
+
+        let page = snapshot_ticks(&mut helper, None, 10);
+
+        assert!(page.ticks.is_empty());
+        assert_eq!(page.next_cursor, None);
+    }
+
+    #[test_case(0 ; "zero")]
+    #[test_case(SNAPSHOT_TICKS_LIMIT_MAX + 1 ; "above_max")]
+    fn test_snapshot_ticks_invalid_limit(limit: u32) {
+        let mut helper = helper_with_positions();
+        helper.snapshot_ticks(None, limit);
+        helper.registry.execute_expect_failure(false);
+    }
+}
@@ -0,0 +1,105 @@
+#[cfg(test)]
+mod precision_pool_sparse_ticks {
+    use common::pools::SwapType;
+    use precision_pool_test_helper::*;
+    use radix_engine::system::system_modules::execution_trace::ResourceSpecifier;
+    use scrypto::prelude::*;
+    use scrypto_testenv::*;
+
+    // Two positions far away from the current price with a wide gap of uninitialized ticks in between.
+    const POSITIONS_SPARSE: [LiquidityPosition; 2] = [
+        LiquidityPosition {
+            left_bound: -11000,
+            right_bound: -10000,
+            x_amount: DEC_10,
+            y_amount: DEC_10,
+        },
+        LiquidityPosition {
+            left_bound: 10000,
+            right_bound: 11000,
+            x_amount: DEC_10,
+            y_amount: DEC_10,
+        },
+    ];
+
+    fn swap(helper: &mut PoolTestHelper, swap_type: SwapType, input_amount: Decimal) -> Decimal {
+        let receipt = helper
+            .swap(helper.input_address(swap_type), input_amount)
+            .registry
+            .execute_expect_success(false);
+        let output_buckets = receipt.output_buckets("swap");
+        match output_buckets[0][0] {
+            ResourceSpecifier::Amount(_, amount) => amount,
+            _ => panic!("Expected a fungible output bucket."),
+        }
+    }
+
+    #[test]
+    fn test_swap_buy_skips_uninitialized_ticks() {
+        let mut helper = PoolTestHelper::new_default_with_positions(pdec!(1), &POSITIONS_SPARSE);
+        helper.registry.execute_expect_success(false);
+        assert_eq!(helper.active_tick_success(), Some(-10000));
+
+        let output = swap(&mut helper, SwapType::BuyX, dec!(1));
+
+        assert!(output > dec!(0));
+        assert_eq!(helper.active_tick_success(), Some(10000));
+    }
+
+    #[test]
+    fn test_swap_sell_skips_uninitialized_ticks() {
+        let mut helper = PoolTestHelper::new_default_with_positions(pdec!(1), &POSITIONS_SPARSE);
+        helper.registry.execute_expect_success(false);
+
+        let output = swap(&mut helper, SwapType::SellX, dec!(1));
+
+        assert!(output > dec!(0));
+        assert_eq!(helper.active_tick_success(), Some(-11000));
+    }
+
+    #[test]
+    fn test_swap_back_and_forth_across_gap() {
+        let mut helper = PoolTestHelper::new_default_with_positions(pdec!(1), &POSITIONS_SPARSE);
+        helper.registry.execute_expect_success(false);
+
+        let x_output = swap(&mut helper, SwapType::BuyX, dec!(1));
+        assert_eq!(helper.active_tick_success(), Some(10000));
+
+        swap(&mut helper, SwapType::SellX, x_output * dec!(2));
+        assert_eq!(helper.active_tick_success(), Some(-11000));
+    }
+
+    #[test]
+    fn test_swap_skips_removed_ticks() {
+        let mut helper = PoolTestHelper::new();
+        helper.instantiate_default(pdec!(1), false);
+        helper.add_liquidity_default(5000, 6000, DEC_10, DEC_10);
+        helper.add_liquidity_default(10000, 11000, DEC_10, DEC_10);
+        helper.registry.execute_expect_success(false);
+        helper
+            .remove_liquidity(nft_ids!(1))
+            .registry
+            .execute_expect_success(false);
+
+        swap(&mut helper, SwapType::BuyX, dec!(1));
+
+        assert_eq!(helper.active_tick_success(), Some(10000));
+    }
+
+    #[test]
+    fn test_swap_shared_tick_remains_initialized() {
+        let mut helper = PoolTestHelper::new();
+        helper.instantiate_default(pdec!(1), false);
+        helper.add_liquidity_default(5000, 6000, DEC_10, DEC_10);
+        helper.add_liquidity_default(6000, 7000, DEC_10, DEC_10);
+        helper.registry.execute_expect_success(false);
+        helper
+            .remove_liquidity(nft_ids!(1))
+            .registry
+            .execute_expect_success(false);
+
+        swap(&mut helper, SwapType::BuyX, dec!(1));
+
+        assert_eq!(helper.active_tick_success(), Some(6000));
+    }
+}
@@ -0,0 +1,191 @@
+#[cfg(test)]
+mod precision_pool_specification {
+    use precision_pool::pool::PoolSnapshot;
+    use precision_pool_test_helper::*;
+    use proptest::collection::vec;
+    use proptest::prelude::*;
+    use scrypto::prelude::*;
+    use scrypto_testenv::*;
+
+    // Differential tests of random operation sequences against the Rust port of the specification model in
+    // `tests/specification_concentrated_pool`. Failing sequences are shrunk by proptest to a minimal sequence.
+    // Every case instantiates a new ledger, the number of cases can be raised via `PROPTEST_CASES`.
+
+    #[derive(Clone, Debug)]
+    enum Operation {
+        AddLiquidity {
+            left_bound: i32,
+            right_bound: i32,
+            x_amount: Decimal,
+            y_amount: Decimal,
+        },
+        RemoveLiquidity {
+            position: usize,
+        },
+        Swap {
+            input_is_x: bool,
+            input_amount: Decimal,
+        },
+        ClaimFees {
+            position: usize,
+        },
+        JumpTime {
+            seconds: u64,
+        },
+    }
+
+    fn amount() -> impl Strategy<Value = Decimal> {
+        (1u64..1_000_000).prop_map(|milli| Decimal::from(milli) / dec!(1000))
+    }
+
+    fn bounds() -> impl Strategy<Value = (i32, i32)> {
+        (-20i32..20, 1i32..10).prop_map(|(left, width)| (left * 100, (left + width) * 100))
+    }
+
+    fn operation() -> impl Strategy<Value = Operation> {
+        prop_oneof![
+            3 => (bounds(), amount(), amount()).prop_map(|((left_bound, right_bound), x_amount, y_amount)| {
+                Operation::AddLiquidity {
+                    left_bound,
+                    right_bound,
+                    x_amount,
+                    y_amount,
+                }
+            }),
+            1 => any::<usize>().prop_map(|position| Operation::RemoveLiquidity { position }),
+            4 => (any::<bool>(), amount()).prop_map(|(input_is_x, input_amount)| {
+                Operation::Swap {
+                    input_is_x,
+                    input_amount,
+                }
+            }),
+            1 => any::<usize>().prop_map(|position| Operation::ClaimFees { position }),
+            1 => (1u64..100_000).prop_map(|seconds| Operation::JumpTime { seconds }),
+        ]
+    }
+
+    fn input_fee_rate() -> impl Strategy<Value = Decimal> {
+        prop_oneof![
+            Just(dec!(0)),
+            Just(dec!("0.003")),
+            Just(dec!("0.01")),
+            Just(dec!("0.1"))
+        ]
+    }
+
+    fn fee_protocol_share(helper: &mut PoolTestHelper) -> Decimal {
+        let receipt = helper.snapshot().registry.execute_expect_success(false);
+        let snapshots: Vec<PoolSnapshot> = receipt.outputs("snapshot");
+        let PoolSnapshot::V1(snapshot) = snapshots[0].clone();
+        snapshot.fee_protocol_share
+    }
+
+    fn execute(helper: &mut PoolTestHelper, model: &mut SpecPool, operation: &Operation) {
+        match *operation {
+            Operation::AddLiquidity {
+                left_bound,
+                right_bound,
+                x_amount,
+                y_amount,
+            } => {
+                let receipt = helper
+                    .add_liquidity_default(left_bound, right_bound, x_amount, y_amount)
+                    .registry
+                    .execute_expect_success(false);
+                let output_buckets = receipt.output_buckets("add_liquidity");
+                let (x_remainder, y_remainder) = PoolTestHelper::amounts(&output_buckets[0][1..]);
+
+                let (_, x_added, y_added) =
+                    model.add_liquidity(left_bound, right_bound, x_amount, y_amount);
+                assert_within_spec_bounds(x_amount - x_remainder, x_added, "Add liquidity X");
+                assert_within_spec_bounds(y_amount - y_remainder, y_added, "Add liquidity Y");
+            }
+            Operation::RemoveLiquidity { position } => {
+                let positions = model.active_positions();
+                if positions.is_empty() {
+                    return;
+                }
+                let index = positions[position % positions.len()];
+                let receipt = helper
+                    .remove_liquidity(nft_ids!(index as u64 + 1))
+                    .registry
+                    .execute_expect_success(false);
+                let (x_amount, y_amount) =
+                    PoolTestHelper::amounts(&receipt.output_buckets("remove_liquidity")[0]);
+
+                let (x_expected, y_expected) = model.remove_liquidity(index);
+                assert_within_spec_bounds(x_amount, x_expected, "Remove liquidity X");
+                assert_within_spec_bounds(y_amount, y_expected, "Remove liquidity Y");
+            }
+            Operation::Swap {
+                input_is_x,
+                input_amount,
+            } => {
+                let input_address = match input_is_x {
+                    true => helper.x_address(),
+                    false => helper.y_address(),
+                };
+                let receipt = helper
+                    .swap(input_address, input_amount)
+                    .registry
+                    .execute_expect_success(false);
+                let (output, remainder) =
+                    PoolTestHelper::amounts(&receipt.output_buckets("swap")[0]);
+
+                // The pool synchronizes the protocol fee share with the registry at the start of the swap.
+                model.fee_protocol_share = fee_protocol_share(helper);
+                let (output_expected, remainder_expected) = model.swap(input_amount, input_is_x);
+                assert_within_spec_bounds(output, output_expected, "Swap output");
+                assert_within_spec_bounds(remainder, remainder_expected, "Swap remainder");
+            }
+            Operation::ClaimFees { position } => {
+                let positions = model.active_positions();
+                if positions.is_empty() {
+                    return;
+                }
+                let index = positions[position % positions.len()];
+                let receipt = helper
+                    .claim_fees(nft_ids!(index as u64 + 1))
+                    .registry
+                    .execute_expect_success(false);
+                let (x_fees, y_fees) =
+                    PoolTestHelper::amounts(&receipt.output_buckets("claim_fees")[0]);
+
+                let (x_expected, y_expected) = model.claim_fees(index);
+                assert_within_spec_bounds(x_fees, x_expected, "Claim fees X");
+                assert_within_spec_bounds(y_fees, y_expected, "Claim fees Y");
+            }
+            Operation::JumpTime { seconds } => {
+                // Time only affects the oracle and the seconds inside of positions, which the model does not cover.
+                helper.advance_timestamp_by_seconds(seconds);
+            }
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(16))]
+
+        #[test]
+        fn test_operations_match_specification(
+            input_fee_rate in input_fee_rate(),
+            operations in vec(operation(), 1..16),
+        ) {
+            let mut helper = PoolTestHelper::new();
+            helper.instantiate_default_with_input_fee(pdec!(1), input_fee_rate, false);
+            let mut model = SpecPool::new(pdec!(1), input_fee_rate);
+
+            for operation in &operations {
+                execute(&mut helper, &mut model, operation);
+            }
+
+            // All remaining positions can be removed and return the amounts of the model.
+            while !model.active_positions().is_empty() {
+                execute(
+                    &mut helper,
+                    &mut model,
+                    &Operation::RemoveLiquidity { position: 0 },
+                );
+            }
+        }
+    }
+}
@@ -1,6 +1,7 @@
 #[cfg(test)]
 mod precision_pool_sync {
     use common::pools::SwapType;
+    use precision_pool::constants::{REGISTRY_SYNC_INTERVAL_MIN, REGISTRY_SYNC_RETRY_INTERVAL};
     use precision_pool_test_helper::*;
     use pretty_assertions::assert_eq;
     use radix_engine::system::system_modules::execution_trace::ResourceSpecifier::Amount;
@@ -253,4 +254,333 @@ mod precision_pool_sync {
             .registry
             .withdraw_protocol_fees_success(dec!(0.075), dec!(0.05));
     }
+
+    fn helper_with_protocol_fees() -> PoolTestHelper {
+        let mut helper = PoolTestHelper::new_without_instantiate_registry();
+        helper.registry.instantiate_execute(
+            helper.registry.admin_badge_address(),
+            dec!(0.25),
+            3041,
+            32,
+        );
+        helper.instantiate_default_with_fees_and_hooks(
+            pdec!(1),
+            dec!(0.1),
+            dec!(0.009),
+            vec![],
+            false,
+        );
+        helper.add_liquidity_success(-10000, 10000, dec!(10), dec!(10), dec!(0), dec!(0));
+        // The first swap syncs the protocol fee share, the protocol fees stay in the pool until the next sync.
+        helper.swap_success(SwapType::BuyX, dec!(3), dec!(2.440716293858554572), dec!(0));
+        helper
+    }
+
+    fn protocol_fees(helper: &mut PoolTestHelper) -> (Decimal, Decimal) {
+        let receipt = helper
+            .protocol_fees()
+            .registry
+            .execute_expect_success(false);
+        let fees: Vec<IndexMap<ResourceAddress, Decimal>> = receipt.outputs("protocol_fees");
+        (
+            *fees[0].get(&helper.x_address()).unwrap(),
+            *fees[0].get(&helper.y_address()).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_protocol_fees() {
+        let mut helper = helper_with_protocol_fees();
+
+        assert_eq!(protocol_fees(&mut helper), (dec!(0), dec!(0.075)));
+    }
+
+    #[test]
+    fn test_collect_protocol_fees_before_next_sync_time() {
+        let mut helper = helper_with_protocol_fees();
+
+        helper.collect_protocol_fees();
+        helper.registry.execute_expect_success(false);
+
+        assert_eq!(protocol_fees(&mut helper), (dec!(0), dec!(0)));
+        helper.registry.load_owner_auth();
+        helper
+            .registry
+            .withdraw_protocol_fees_success(dec!(0), dec!(0.075));
+    }
+
+    #[test]
+    fn test_collect_protocol_fees_without_fees() {
+        let mut helper = PoolTestHelper::new();
+        helper.instantiate_default(pdec!(1), false);
+
+        helper.collect_protocol_fees();
+        helper.registry.execute_expect_success(false);
+
+        assert_eq!(protocol_fees(&mut helper), (dec!(0), dec!(0)));
+    }
+
+    #[test]
+    fn test_sync_registry_waits_for_next_sync_time() {
+        let mut helper = helper_with_protocol_fees();
+
+        helper.sync_registry();
+        helper.registry.execute_expect_success(false);
+
+        assert_eq!(protocol_fees(&mut helper), (dec!(0), dec!(0.075)));
+    }
+
+    #[test]
+    fn test_remove_liquidity_syncs_registry() {
+        let mut helper = helper_with_protocol_fees();
+        helper.advance_timestamp_by_seconds(5701);
+
+        helper.remove_liquidity(nft_ids!(1));
+        helper.registry.execute_expect_success(false);
+
+        assert_eq!(protocol_fees(&mut helper), (dec!(0), dec!(0)));
+        helper.registry.load_owner_auth();
+        helper
+            .registry
+            .withdraw_protocol_fees_success(dec!(0), dec!(0.075));
+    }
+
+    #[test]
+    fn test_remove_liquidity_without_sync_does_not_sync_registry() {
+        let mut helper = helper_with_protocol_fees();
+        helper.advance_timestamp_by_seconds(5701);
+
+        helper.remove_liquidity_without_sync(nft_ids!(1));
+        helper.registry.execute_expect_success(false);
+
+        assert_eq!(protocol_fees(&mut helper), (dec!(0), dec!(0.075)));
+    }
+
+    #[test]
+    fn test_flash_loan_syncs_registry() {
+        let mut helper = helper_with_protocol_fees();
+        let receipt = helper
+            .flash_loan_address()
+            .registry
+            .execute_expect_success(false);
+        let transient_address: ResourceAddress = receipt.outputs("flash_loan_address")[0];
+        helper.advance_timestamp_by_seconds(5701);
+
+        helper.flash_loan(helper.x_address(), dec!(1));
+        helper.repay_loan(
+            helper.x_address(),
+            dec!(1),
+            dec!(0.009),
+            transient_address,
+            dec!(1),
+        );
+        helper.registry.execute_expect_success(false);
+
+        // The flash loan remits the swap fees, the loan fee is only deposited afterwards by the repayment.
+        assert_eq!(protocol_fees(&mut helper), (dec!(0.009), dec!(0)));
+    }
+
+    #[test]
+    fn test_repay_loan_syncs_registry() {
+        let mut helper = PoolTestHelper::new();
+        helper.instantiate_default_with_flash_loan_fee(pdec!(1), dec!(0.009), false);
+        let receipt = helper
+            .add_liquidity_default(-10000, 10000, dec!(10), dec!(10))
+            .flash_loan_address()
+            .registry
+            .execute_expect_success(false);
+        let transient_address: ResourceAddress = receipt.outputs("flash_loan_address")[0];
+
+        helper.flash_loan(helper.x_address(), dec!(1));
+        helper.repay_loan(
+            helper.x_address(),
+            dec!(1),
+            dec!(0.009),
+            transient_address,
+            dec!(1),
+        );
+        helper.registry.execute_expect_success(false);
+
+        assert_eq!(protocol_fees(&mut helper), (dec!(0), dec!(0)));
+        helper.registry.load_owner_auth();
+        helper
+            .registry
+            .withdraw_protocol_fees_success(dec!(0.009), dec!(0));
+    }
+
+    fn helper_with_test_registry(
+        fee_protocol_share: Decimal,
+        next_sync_time: u64,
+    ) -> (PoolTestHelper, ComponentAddress) {
+        let mut helper = PoolTestHelper::new_with_test_hook();
+        let registry_address = helper.instantiate_test_registry(fee_protocol_share, next_sync_time);
+        helper.instantiate_default_with_registry(pdec!(1), dec!(0.1), registry_address, false);
+        helper.add_liquidity_success(-10000, 10000, dec!(10), dec!(10), dec!(0), dec!(0));
+        (helper, registry_address)
+    }
+
+    fn now(helper: &mut PoolTestHelper) -> u64 {
+        helper
+            .registry
+            .env
+            .test_runner
+            .get_current_time(TimePrecision::Second)
+            .seconds_since_unix_epoch as u64
+    }
+
+    fn fee_protocol_share(helper: &mut PoolTestHelper) -> Decimal {
+        let receipt = helper
+            .fee_protocol_share()
+            .registry
+            .execute_expect_success(false);
+        receipt.outputs("fee_protocol_share")[0]
+    }
+
+    fn next_sync_time(helper: &mut PoolTestHelper) -> u64 {
+        let receipt = helper
+            .next_sync_time()
+            .registry
+            .execute_expect_success(false);
+        receipt.outputs("next_sync_time")[0]
+    }
+
+    #[test]
+    fn test_sync_registry_valid_response() {
+        let (mut helper, _) = helper_with_test_registry(dec!(0.1), 100000);
+
+        helper.swap_success(SwapType::BuyX, dec!(3), dec!(2.440716293858554572), dec!(0));
+
+        assert_eq!(fee_protocol_share(&mut helper), dec!(0.1));
+        assert_eq!(next_sync_time(&mut helper), 100000);
+    }
+
+    #[test]
+    fn test_sync_registry_clamps_fee_protocol_share() {
+        let (mut helper, _) = helper_with_test_registry(dec!(0.5), 100000);
+
+        helper.swap_success(SwapType::BuyX, dec!(3), dec!(2.440716293858554572), dec!(0));
+
+        assert_eq!(fee_protocol_share(&mut helper), dec!(0.25));
+        assert_eq!(protocol_fees(&mut helper), (dec!(0), dec!(0.075)));
+    }
+
+    #[test]
+    fn test_sync_registry_clamps_negative_fee_protocol_share() {
+        let (mut helper, _) = helper_with_test_registry(dec!(-0.1), 100000);
+
+        helper.swap_success(SwapType::BuyX, dec!(3), dec!(2.440716293858554572), dec!(0));
+
+        assert_eq!(fee_protocol_share(&mut helper), dec!(0));
+    }
+
+    #[test]
+    fn test_sync_registry_past_next_sync_time_waits_min_interval() {
+        let (mut helper, registry_address) = helper_with_test_registry(dec!(0.1), 0);
+        let now = now(&mut helper);
+
+        helper.swap_success(SwapType::BuyX, dec!(3), dec!(2.440716293858554572), dec!(0));
+        assert_eq!(fee_protocol_share(&mut helper), dec!(0.1));
+        assert_eq!(
+            next_sync_time(&mut helper),
+            now + REGISTRY_SYNC_INTERVAL_MIN
+        );
+
+        // Swaps within the minimum interval do not call the registry.
+        helper.swap(helper.y_address(), dec!(1));
+        helper.registry.execute_expect_success(false);
+        let receipt = helper
+            .test_registry_fees(registry_address)
+            .registry
+            .execute_expect_success(false);
+        let fees: Vec<(Decimal, Decimal)> = receipt.outputs("fees");
+        assert_eq!(fees, vec![(dec!(0), dec!(0))]);
+
+        // After the minimum interval the pool synchronizes again.
+        let pending_fees = protocol_fees(&mut helper);
+        helper.advance_timestamp_by_seconds(REGISTRY_SYNC_INTERVAL_MIN);
+        helper.sync_registry();
+        helper.registry.execute_expect_success(false);
+        let receipt = helper
+            .test_registry_fees(registry_address)
+            .registry
+            .execute_expect_success(false);
+        let fees: Vec<(Decimal, Decimal)> = receipt.outputs("fees");
+        assert_eq!(fees, vec![pending_fees]);
+    }
+
+    #[test]
+    fn test_sync_registry_invalid_response() {
+        let mut helper = PoolTestHelper::new_with_test_hook();
+        let registry_address = helper.instantiate_test_registry_invalid();
+        helper.instantiate_default_with_registry(pdec!(1), dec!(0.1), registry_address, false);
+        helper.add_liquidity_success(-10000, 10000, dec!(10), dec!(10), dec!(0), dec!(0));
+        let now = now(&mut helper);
+
+        // The pool stays tradeable and keeps its protocol fee share.
+        helper.swap_success(SwapType::BuyX, dec!(3), dec!(2.440716293858554572), dec!(0));
+        helper.swap(helper.x_address(), dec!(1));
+        helper.registry.execute_expect_success(false);
+
+        assert_eq!(fee_protocol_share(&mut helper), dec!(0));
+        assert_eq!(
+            next_sync_time(&mut helper),
+            now + REGISTRY_SYNC_RETRY_INTERVAL
+        );
+    }
+
+    #[test]
+    fn test_sync_registry_recovery() {
+        let (mut helper, registry_address) = helper_with_test_registry(dec!(0.1), 0);
+        helper.swap_success(SwapType::BuyX, dec!(3), dec!(2.440716293858554572), dec!(0));
+
+        // After the registry has been fixed, anyone can resynchronize the pool immediately.
+        helper.set_test_registry_response(registry_address, dec!(0.2), 100000);
+        helper.collect_protocol_fees();
+        helper.registry.execute_expect_success(false);
+
+        assert_eq!(fee_protocol_share(&mut helper), dec!(0.2));
+        assert_eq!(next_sync_time(&mut helper), 100000);
+        let receipt = helper
+            .test_registry_fees(registry_address)
+            .registry
+            .execute_expect_success(false);
+        let fees: Vec<(Decimal, Decimal)> = receipt.outputs("fees");
+        assert_eq!(fees, vec![(dec!(0), dec!(0.03))]);
+    }
+
+    #[test]
+    fn test_sync_registry_panicking_registry() {
+        let (mut helper, registry_address) = helper_with_test_registry(dec!(0.1), 0);
+        helper.swap_success(SwapType::BuyX, dec!(3), dec!(2.440716293858554572), dec!(0));
+        helper.set_test_registry_sync_panics(registry_address, true);
+        helper.registry.execute_expect_success(false);
+        helper.advance_timestamp_by_seconds(REGISTRY_SYNC_INTERVAL_MIN);
+
+        // A panicking registry reverts every synchronizing transaction.
+        helper.swap(helper.x_address(), dec!(1));
+        helper.registry.execute_expect_failure(false);
+        helper.remove_liquidity(nft_ids!(1));
+        helper.registry.execute_expect_failure(false);
+
+        // Liquidity providers can still exit without calling the registry.
+        helper.remove_liquidity_without_sync(nft_ids!(1));
+        helper.registry.execute_expect_success(false);
+    }
+
+    #[test]
+    fn test_sync_registry_panicking_registry_fixed() {
+        let (mut helper, registry_address) = helper_with_test_registry(dec!(0.1), 0);
+        helper.swap_success(SwapType::BuyX, dec!(3), dec!(2.440716293858554572), dec!(0));
+        helper.set_test_registry_sync_panics(registry_address, true);
+        helper.registry.execute_expect_success(false);
+        helper.advance_timestamp_by_seconds(REGISTRY_SYNC_INTERVAL_MIN);
+        helper.swap(helper.x_address(), dec!(1));
+        helper.registry.execute_expect_failure(false);
+
+        // Once the registry operator fixes the registry, the pool is tradeable again.
+        helper.set_test_registry_sync_panics(registry_address, false);
+        helper.registry.execute_expect_success(false);
+        helper.swap(helper.x_address(), dec!(1));
+        helper.registry.execute_expect_success(false);
+    }
 }
@@ -0,0 +1,275 @@
+#[cfg(test)]
+mod precision_pool_zap {
+    use precision_pool::pool::FeeCollectionMode;
+    use precision_pool_test_helper::*;
+    use radix_engine::system::system_modules::execution_trace::{
+        ResourceSpecifier, ResourceSpecifier::Amount, ResourceSpecifier::Ids,
+    };
+    use scrypto::prelude::*;
+    use scrypto_testenv::*;
+    use test_case::test_case;
+
+    static ONE_LP: [LiquidityPosition; 1] = [LiquidityPosition {
+        left_bound: TICK_LEFT_BOUND,
+        right_bound: TICK_RIGHT_BOUND,
+        x_amount: DEC_10,
+        y_amount: DEC_10,
+    }];
+
+    fn helper_with_liquidity() -> PoolTestHelper {
+        let mut helper = PoolTestHelper::new();
+        helper.instantiate_default(*PRICE_BETWEEN_MIDDLE_BOUNDS_SQRT, true);
+        helper
+            .add_liquidity_default_batch(&ONE_LP)
+            .registry
+            .execute_expect_success(false);
+        helper
+    }
+
+    // The price of the pool and a limit loose enough for the price impact and fees of the tested swaps.
+    const PRICE: Decimal = dec!(2);
+    const MAX_DEVIATION: Decimal = dec!("0.5");
+
+    fn amount_of(bucket: &ResourceSpecifier) -> Decimal {
+        match bucket {
+            Amount(_, amount) => *amount,
+            _ => panic!("Expected a fungible bucket."),
+        }
+    }
+
+    fn zap_in_success(
+        helper: &mut PoolTestHelper,
+        left_bound: i32,
+        right_bound: i32,
+        input_address: ResourceAddress,
+        input_amount: Decimal,
+        max_dust: Decimal,
+    ) {
+        let receipt = helper
+            .zap_in(
+                left_bound,
+                right_bound,
+                input_address,
+                input_amount,
+                (PRICE, MAX_DEVIATION),
+            )
+            .registry
+            .execute_expect_success(false);
+        let output_buckets = receipt.output_buckets("zap_in");
+
+        assert_eq!(
+            output_buckets[0][0],
+            Ids(helper.lp_address.unwrap(), nft_ids!(2))
+        );
+        let (x_dust, y_dust) = (
+            amount_of(&output_buckets[0][1]),
+            amount_of(&output_buckets[0][2]),
+        );
+        assert!(x_dust <= max_dust, "X dust too large: {:?}", x_dust);
+        assert!(y_dust <= max_dust, "Y dust too large: {:?}", y_dust);
+    }
+
+    #[test]
+    fn test_zap_in_x_small_amount() {
+        let mut helper = helper_with_liquidity();
+        let x_address = helper.x_address();
+        zap_in_success(
+            &mut helper,
+            TICK_LEFT_BOUND,
+            TICK_RIGHT_BOUND,
+            x_address,
+            dec!("0.1"),
+            dec!("0.001"),
+        );
+    }
+
+    #[test]
+    fn test_zap_in_y_small_amount() {
+        let mut helper = helper_with_liquidity();
+        let y_address = helper.y_address();
+        zap_in_success(
+            &mut helper,
+            TICK_LEFT_BOUND,
+            TICK_RIGHT_BOUND,
+            y_address,
+            dec!("0.1"),
+            dec!("0.001"),
+        );
+    }
+
+    #[test]
+    fn test_zap_in_x_large_amount() {
+        let mut helper = helper_with_liquidity();
+        let x_address = helper.x_address();
+        zap_in_success(
+            &mut helper,
+            TICK_LEFT_BOUND,
+            TICK_RIGHT_BOUND,
+            x_address,
+            dec!(5),
+            dec!("0.01"),
+        );
+    }
+
+    #[test]
+    fn test_zap_in_x_range_above_price_without_swap() {
+        let mut helper = helper_with_liquidity();
+        let x_address = helper.x_address();
+        zap_in_success(&mut helper, 20000, 30000, x_address, dec!(1), dec!(0));
+    }
+
+    #[test]
+    fn test_zap_in_y_range_below_price_without_swap() {
+        let mut helper = helper_with_liquidity();
+        let y_address = helper.y_address();
+        zap_in_success(&mut helper, 0, 5000, y_address, dec!(1), dec!(0));
+    }
+
+    #[test]
+    fn test_zap_in_y_range_above_price_swaps_everything() {
+        let mut helper = helper_with_liquidity();
+        let y_address = helper.y_address();
+        zap_in_success(&mut helper, 20000, 30000, y_address, dec!(1), dec!(0));
+    }
+
+    #[test]
+    fn test_zap_in_with_fees() {
+        let mut helper = PoolTestHelper::new();
+        helper.instantiate_default_with_input_fee(
+            *PRICE_BETWEEN_MIDDLE_BOUNDS_SQRT,
+            dec!("0.01"),
+            true,
+        );
+        helper
+            .add_liquidity_default_batch(&ONE_LP)
+            .registry
+            .execute_expect_success(false);
+        let x_address = helper.x_address();
+        zap_in_success(
+            &mut helper,
+            TICK_LEFT_BOUND,
+            TICK_RIGHT_BOUND,
+            x_address,
+            dec!(1),
+            dec!("0.001"),
+        );
+    }
+
+    #[test_case(FeeCollectionMode::Input, true ; "input_x")]
+    #[test_case(FeeCollectionMode::Input, false ; "input_y")]
+    #[test_case(FeeCollectionMode::Output, true ; "output_x")]
+    #[test_case(FeeCollectionMode::Output, false ; "output_y")]
+    #[test_case(FeeCollectionMode::X, true ; "x_x")]
+    #[test_case(FeeCollectionMode::X, false ; "x_y")]
+    #[test_case(FeeCollectionMode::Y, true ; "y_x")]
+    #[test_case(FeeCollectionMode::Y, false ; "y_y")]
+    fn test_zap_in_with_fee_collection_mode(fee_collection_mode: FeeCollectionMode, input_x: bool) {
+        let mut helper = PoolTestHelper::new();
+        helper.instantiate_default_with_fee_collection_mode(
+            *PRICE_BETWEEN_MIDDLE_BOUNDS_SQRT,
+            dec!("0.1"),
+            fee_collection_mode,
+            true,
+        );
+        helper
+            .add_liquidity_default_batch(&ONE_LP)
+            .registry
+            .execute_expect_success(false);
+        let input_address = match input_x {
+            true => helper.x_address(),
+            false => helper.y_address(),
+        };
+        zap_in_success(
+            &mut helper,
+            TICK_LEFT_BOUND,
+            TICK_RIGHT_BOUND,
+            input_address,
+            dec!(1),
+            dec!("0.001"),
+        );
+    }
+
+    #[test]
+    fn test_zap_in_wrong_token() {
+        let mut helper = helper_with_liquidity();
+        let v_address = helper.v_address();
+        helper
+            .zap_in(
+                TICK_LEFT_BOUND,
+                TICK_RIGHT_BOUND,
+                v_address,
+                dec!(1),
+                (PRICE, MAX_DEVIATION),
+            )
+            .registry
+            .execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_zap_in_manipulated_price() {
+        // A swap right before the zap in the same transaction moves the price away from the expected price.
+        let mut helper = helper_with_liquidity();
+        let x_address = helper.x_address();
+        helper.swap_x_default(dec!(5));
+        helper
+            .zap_in(
+                TICK_LEFT_BOUND,
+                TICK_RIGHT_BOUND,
+                x_address,
+                dec!(1),
+                (PRICE, dec!("0.01")),
+            )
+            .registry
+            .execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_zap_in_swap_slippage_exceeded() {
+        // The price impact of the swap exceeds the allowed deviation.
+        let mut helper = helper_with_liquidity();
+        let x_address = helper.x_address();
+        helper
+            .zap_in(
+                TICK_LEFT_BOUND,
+                TICK_RIGHT_BOUND,
+                x_address,
+                dec!(5),
+                (PRICE, dec!("0.01")),
+            )
+            .registry
+            .execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_zap_in_without_active_liquidity() {
+        let mut helper = PoolTestHelper::new();
+        helper.instantiate_default(*PRICE_BETWEEN_MIDDLE_BOUNDS_SQRT, true);
+        let x_address = helper.x_address();
+        helper
+            .zap_in(
+                TICK_LEFT_BOUND,
+                TICK_RIGHT_BOUND,
+                x_address,
+                dec!(1),
+                (PRICE, MAX_DEVIATION),
+            )
+            .registry
+            .execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_zap_in_without_active_liquidity_range_above_price() {
+        // Positions outside of the current price do not require a swap.
+        let mut helper = PoolTestHelper::new();
+        helper.instantiate_default(*PRICE_BETWEEN_MIDDLE_BOUNDS_SQRT, true);
+        let x_address = helper.x_address();
+        let receipt = helper
+            .zap_in(20000, 30000, x_address, dec!(1), (PRICE, MAX_DEVIATION))
+            .registry
+            .execute_expect_success(false);
+        assert_eq!(
+            receipt.output_buckets("zap_in")[0][0],
+            Ids(helper.lp_address.unwrap(), nft_ids!(1))
+        );
+    }
+}
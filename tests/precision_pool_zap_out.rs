@@ -0,0 +1,121 @@
+#[cfg(test)]
+mod precision_pool_zap_out {
+    use precision_pool_test_helper::*;
+    use radix_engine::system::system_modules::execution_trace::{
+        ResourceSpecifier, ResourceSpecifier::Amount,
+    };
+    use scrypto::prelude::*;
+    use scrypto_testenv::*;
+
+    static TWO_LP: [LiquidityPosition; 2] = [
+        LiquidityPosition {
+            left_bound: TICK_LEFT_BOUND,
+            right_bound: TICK_RIGHT_BOUND,
+            x_amount: DEC_10,
+            y_amount: DEC_10,
+        },
+        LiquidityPosition {
+            left_bound: TICK_LEFT_BOUND,
+            right_bound: TICK_RIGHT_BOUND,
+            x_amount: dec!(100),
+            y_amount: dec!(100),
+        },
+    ];
+
+    fn helper_with_liquidity() -> PoolTestHelper {
+        let mut helper = PoolTestHelper::new();
+        helper.instantiate_default(*PRICE_BETWEEN_MIDDLE_BOUNDS_SQRT, true);
+        helper
+            .add_liquidity_default_batch(&TWO_LP)
+            .registry
+            .execute_expect_success(false);
+        helper
+    }
+
+    fn single_output(
+        helper: &mut PoolTestHelper,
+        output_address: ResourceAddress,
+        min_output: Decimal,
+    ) -> Vec<Vec<ResourceSpecifier>> {
+        let receipt = helper
+            .remove_liquidity_to(nft_ids!(1), output_address, min_output)
+            .registry
+            .execute_expect_success(false);
+        receipt.output_buckets("remove_liquidity_to")
+    }
+
+    #[test]
+    fn test_remove_liquidity_to_x() {
+        let mut helper = helper_with_liquidity();
+        let x_address = helper.x_address();
+        let output_buckets = single_output(&mut helper, x_address, dec!(0));
+        assert_eq!(output_buckets.len(), 1);
+        assert_eq!(output_buckets[0].len(), 1);
+        match &output_buckets[0][0] {
+            // The position holds about 2.9 X and 10 Y at a price of 2, swapping 10 Y returns about 4.7 X.
+            Amount(address, amount) => {
+                assert_eq!(*address, x_address);
+                assert!(
+                    dec!("7.5") < *amount && *amount < dec!("7.7"),
+                    "{:?}",
+                    amount
+                );
+            }
+            _ => panic!("Expected a fungible bucket."),
+        }
+    }
+
+    #[test]
+    fn test_remove_liquidity_to_y() {
+        let mut helper = helper_with_liquidity();
+        let y_address = helper.y_address();
+        let output_buckets = single_output(&mut helper, y_address, dec!(0));
+        match &output_buckets[0][0] {
+            // The position holds about 2.9 X and 10 Y at a price of 2, swapping 2.9 X returns about 5.6 Y.
+            Amount(address, amount) => {
+                assert_eq!(*address, y_address);
+                assert!(
+                    dec!("15.5") < *amount && *amount < dec!("15.7"),
+                    "{:?}",
+                    amount
+                );
+            }
+            _ => panic!("Expected a fungible bucket."),
+        }
+    }
+
+    #[test]
+    fn test_remove_liquidity_to_min_output_not_met() {
+        let mut helper = helper_with_liquidity();
+        let x_address = helper.x_address();
+        helper
+            .remove_liquidity_to(nft_ids!(1), x_address, dec!(8))
+            .registry
+            .execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_remove_liquidity_to_invalid_output_address() {
+        let mut helper = helper_with_liquidity();
+        let v_address = helper.v_address();
+        helper
+            .remove_liquidity_to(nft_ids!(1), v_address, dec!(0))
+            .registry
+            .execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_remove_liquidity_to_last_position_not_enough_liquidity() {
+        let mut helper = PoolTestHelper::new();
+        helper.instantiate_default(*PRICE_BETWEEN_MIDDLE_BOUNDS_SQRT, true);
+        helper
+            .add_liquidity_default_batch(&TWO_LP[..1])
+            .registry
+            .execute_expect_success(false);
+        let x_address = helper.x_address();
+        helper
+            .remove_liquidity_to(nft_ids!(1), x_address, dec!(0))
+            .registry
+            .execute_expect_failure(false);
+    }
+}